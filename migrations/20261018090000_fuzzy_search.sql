@@ -0,0 +1,5 @@
+-- Trigram indexes for typo tolerant search
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX  idx_notes_title_trgm ON notes USING gin (title gin_trgm_ops);
+CREATE INDEX  idx_notes_content_trgm ON notes USING gin (content gin_trgm_ops);
@@ -0,0 +1,21 @@
+-- Per-note text search configuration and a stored, weighted search vector
+ALTER TABLE notes
+    ADD COLUMN language TEXT NOT NULL DEFAULT 'english'
+        CHECK (language IN ('english', 'german', 'spanish', 'simple'));
+
+-- Generated columns need an immutable expression, the text to regconfig cast isn't marked as one
+CREATE FUNCTION note_search_vector(config TEXT, title TEXT, content TEXT)
+    RETURNS tsvector
+    LANGUAGE sql
+    IMMUTABLE PARALLEL SAFE
+AS $$
+    SELECT setweight(to_tsvector(config::regconfig, title), 'A')
+        || setweight(to_tsvector(config::regconfig, content), 'B')
+$$;
+
+ALTER TABLE notes
+    ADD COLUMN search_vector tsvector
+        GENERATED ALWAYS AS (note_search_vector(language, title, content)) STORED;
+
+DROP INDEX idx_notes_title_content_fts;
+CREATE INDEX  idx_notes_search_vector ON notes USING gin (search_vector);
@@ -0,0 +1,15 @@
+-- Named searches users can re-run like smart folders
+CREATE TABLE saved_searches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    mode TEXT NOT NULL DEFAULT 'fulltext' CHECK (mode IN ('fulltext', 'fuzzy')),
+    sort TEXT NOT NULL DEFAULT 'relevance'
+        CHECK (sort IN ('relevance', 'newest', 'oldest', 'recently_updated', 'title')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX  idx_saved_searches_user_id ON saved_searches (user_id);
@@ -0,0 +1,21 @@
+-- Content addressed attachment blobs, shared by every attachment with the same SHA-256
+CREATE TABLE attachment_blobs (
+    sha256 TEXT PRIMARY KEY,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    sha256 TEXT NOT NULL REFERENCES attachment_blobs(sha256),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX  idx_attachments_note_id ON attachments (note_id);
+CREATE INDEX  idx_attachments_user_id ON attachments (user_id);
+CREATE INDEX  idx_attachments_sha256 ON attachments (sha256);
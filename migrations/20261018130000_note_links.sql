@@ -0,0 +1,63 @@
+-- [[wiki links]] between notes, kept in sync with note content on create/update
+CREATE TABLE note_links (
+    source_note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- title or id as written inside the brackets
+    target TEXT NOT NULL,
+    -- set for [[note-uuid]] links
+    target_note_id UUID,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (source_note_id, position)
+);
+
+CREATE INDEX idx_note_links_user_target ON note_links (user_id, lower(target));
+CREATE INDEX idx_note_links_target_note_id ON note_links (target_note_id);
+CREATE INDEX idx_notes_user_lower_title ON notes (user_id, lower(title));
+
+-- Links resolved against the current notes, title links match case-insensitively
+-- and go to the oldest note when several share a title. Unresolved links have a
+-- NULL resolved_note_id
+CREATE VIEW resolved_note_links AS
+SELECT
+    l.source_note_id,
+    l.user_id,
+    l.target,
+    l.target_note_id,
+    l.position,
+    t.id AS resolved_note_id,
+    t.title AS resolved_title
+FROM note_links l
+LEFT JOIN LATERAL (
+    SELECT n.id, n.title
+    FROM notes n
+    WHERE n.user_id = l.user_id
+      AND (
+          n.id = l.target_note_id
+          OR (l.target_note_id IS NULL AND lower(n.title) = lower(l.target))
+      )
+    ORDER BY n.created_at ASC
+    LIMIT 1
+) t ON TRUE;
+
+-- Backfill links for existing notes. Unlike the parser this doesn't skip code
+-- blocks, notes get reparsed the next time they are saved
+INSERT INTO note_links (source_note_id, user_id, target, target_note_id, position)
+SELECT
+    source_note_id,
+    user_id,
+    target,
+    CASE
+        WHEN target ~* '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$' THEN target::uuid
+    END,
+    (ROW_NUMBER() OVER (PARTITION BY source_note_id ORDER BY first_seen) - 1)::INTEGER
+FROM (
+    SELECT DISTINCT ON (n.id, lower(trim(m.match[1])))
+        n.id AS source_note_id,
+        n.user_id,
+        trim(m.match[1]) AS target,
+        m.ordinality AS first_seen
+    FROM notes n
+    CROSS JOIN LATERAL regexp_matches(n.content, '\[\[([^][|#]+)[^][]*\]\]', 'g') WITH ORDINALITY AS m(match, ordinality)
+    WHERE trim(m.match[1]) <> ''
+    ORDER BY n.id, lower(trim(m.match[1])), m.ordinality
+) links;
@@ -0,0 +1,78 @@
+-- Delta sync: every write to a note takes the next value of a global change sequence,
+-- deletes leave a tombstone behind so clients can learn about them
+CREATE SEQUENCE note_change_seq;
+
+ALTER TABLE notes
+    ADD COLUMN version BIGINT NOT NULL DEFAULT 1,
+    ADD COLUMN change_seq BIGINT NOT NULL DEFAULT nextval('note_change_seq');
+
+CREATE INDEX idx_notes_user_change_seq ON notes (user_id, change_seq);
+
+-- No foreign key, tombstones have to outlive the note and are purged by age
+CREATE TABLE note_tombstones (
+    note_id UUID PRIMARY KEY,
+    user_id UUID NOT NULL,
+    change_seq BIGINT NOT NULL DEFAULT nextval('note_change_seq'),
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_note_tombstones_user_change_seq ON note_tombstones (user_id, change_seq);
+CREATE INDEX idx_note_tombstones_deleted_at ON note_tombstones (deleted_at);
+
+-- Highest change_seq of any purged tombstone, tokens older than this can miss deletes
+CREATE TABLE sync_horizon (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    purged_through BIGINT NOT NULL DEFAULT 0
+);
+
+INSERT INTO sync_horizon DEFAULT VALUES;
+
+-- Writers hold a per-user lock from taking a sequence value until commit and readers
+-- take it shared, so a reader never sees seq N while a lower seq is still uncommitted
+CREATE FUNCTION note_sync_lock(owner UUID)
+    RETURNS BIGINT
+    LANGUAGE sql
+    IMMUTABLE PARALLEL SAFE
+AS $$
+    SELECT hashtextextended(owner::text, 38)
+$$;
+
+CREATE FUNCTION notes_track_change() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+BEGIN
+    PERFORM pg_advisory_xact_lock(note_sync_lock(NEW.user_id));
+    NEW.change_seq := nextval('note_change_seq');
+    IF TG_OP = 'UPDATE' THEN
+        NEW.version := OLD.version + 1;
+    ELSE
+        -- a client re-creating a note it deleted earlier under the same id
+        DELETE FROM note_tombstones WHERE note_id = NEW.id;
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+CREATE FUNCTION notes_track_delete() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+BEGIN
+    -- nobody left to sync when the whole account is being deleted
+    IF EXISTS (SELECT 1 FROM users WHERE id = OLD.user_id) THEN
+        PERFORM pg_advisory_xact_lock(note_sync_lock(OLD.user_id));
+        INSERT INTO note_tombstones (note_id, user_id)
+        VALUES (OLD.id, OLD.user_id)
+        ON CONFLICT (note_id) DO UPDATE
+            SET change_seq = nextval('note_change_seq'), deleted_at = NOW();
+    END IF;
+    RETURN OLD;
+END;
+$$;
+
+CREATE TRIGGER notes_track_change
+    BEFORE INSERT OR UPDATE ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_track_change();
+
+CREATE TRIGGER notes_track_delete
+    AFTER DELETE ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_track_delete();
\ No newline at end of file
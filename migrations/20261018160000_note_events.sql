@@ -0,0 +1,34 @@
+-- Announce note writes on the note_events channel so every server instance can push
+-- them to the owner's connected clients. Notes can't be shared yet, so only the
+-- owner ever gets an event. Payloads stay small, clients fetch the note itself.
+CREATE FUNCTION notes_notify_event() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    note RECORD;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        note := OLD;
+    ELSE
+        note := NEW;
+    END IF;
+
+    PERFORM pg_notify('note_events', json_build_object(
+        'type', CASE TG_OP
+            WHEN 'INSERT' THEN 'note.created'
+            WHEN 'UPDATE' THEN 'note.updated'
+            ELSE 'note.deleted'
+        END,
+        'user_id', note.user_id,
+        'note_id', note.id,
+        'version', CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE note.version END,
+        'at', NOW()
+    )::text);
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER notes_notify_event
+    AFTER INSERT OR UPDATE OR DELETE ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_notify_event();
\ No newline at end of file
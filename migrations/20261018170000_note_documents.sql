@@ -0,0 +1,8 @@
+-- CRDT state of notes that were edited collaboratively, notes.content keeps a plain
+-- text snapshot of it for the REST API and search
+CREATE TABLE note_documents (
+    note_id UUID PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+    -- Yjs v1 update encoding of the whole document
+    state BYTEA NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
\ No newline at end of file
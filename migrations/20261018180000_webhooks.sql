@@ -0,0 +1,76 @@
+-- Endpoints users want note events POSTed to
+CREATE TABLE webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    -- HMAC key for the X-Webhook-Signature header
+    secret TEXT NOT NULL,
+    events TEXT[] NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    -- failed attempts since the last successful delivery, the webhook is disabled past a limit
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    disabled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhooks_user_id ON webhooks (user_id);
+
+-- One event for one webhook, kept as the delivery log once it was sent or gave up
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    event TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_status_code INTEGER,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries (webhook_id, created_at DESC);
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries (next_attempt_at) WHERE status = 'pending';
+
+-- Queue a delivery for every active webhook subscribed to the event, in the same
+-- transaction as the note write so no event is lost when the server goes down
+CREATE FUNCTION notes_queue_webhooks() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    note RECORD;
+    event TEXT;
+    data JSON;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        note := OLD;
+        event := 'note.deleted';
+        data := json_build_object('id', OLD.id);
+    ELSE
+        note := NEW;
+        event := CASE TG_OP WHEN 'INSERT' THEN 'note.created' ELSE 'note.updated' END;
+        data := json_build_object(
+            'id', NEW.id,
+            'title', NEW.title,
+            'content', NEW.content,
+            'language', NEW.language,
+            'version', NEW.version,
+            'created_at', NEW.created_at,
+            'updated_at', NEW.updated_at
+        );
+    END IF;
+
+    INSERT INTO webhook_deliveries (webhook_id, event, payload)
+    SELECT id, event, json_build_object('type', event, 'at', NOW(), 'user_id', note.user_id, 'note', data)
+    FROM webhooks
+    WHERE user_id = note.user_id AND active AND event = ANY(events);
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER notes_queue_webhooks
+    AFTER INSERT OR UPDATE OR DELETE ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_queue_webhooks();
\ No newline at end of file
@@ -0,0 +1,44 @@
+-- Work that runs outside request handlers, picked up by the worker pool
+CREATE TABLE jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'completed', 'dead')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 5,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- the worker running the job and until when, after that it's considered crashed
+    locked_by TEXT,
+    locked_until TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_jobs_due ON jobs (run_at) WHERE status = 'pending';
+CREATE INDEX idx_jobs_locked_until ON jobs (locked_until) WHERE status = 'running';
+CREATE INDEX idx_jobs_finished_at ON jobs (finished_at) WHERE status IN ('completed', 'dead');
+
+-- Cron entries, defined in code and synced on worker startup
+CREATE TABLE job_schedules (
+    name TEXT PRIMARY KEY,
+    cron TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    max_attempts INTEGER NOT NULL DEFAULT 5,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ
+);
+
+-- Imports used to run in memory, those still unfinished now have no job to resume them
+UPDATE imports
+SET 
+    status = 'failed',
+    errors = errors || jsonb_build_array(jsonb_build_object(
+        'file', filename,
+        'message', 'Import was interrupted by a server restart'
+    )),
+    updated_at = NOW(),
+    finished_at = NOW()
+WHERE status IN ('pending', 'running');
\ No newline at end of file
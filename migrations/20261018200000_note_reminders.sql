@@ -0,0 +1,57 @@
+-- Optional due date and reminder time per note
+ALTER TABLE notes
+    ADD COLUMN due_at TIMESTAMPTZ,
+    ADD COLUMN remind_at TIMESTAMPTZ;
+
+CREATE INDEX idx_notes_user_id_due_at ON notes (user_id, due_at) WHERE due_at IS NOT NULL;
+CREATE INDEX idx_notes_remind_at ON notes (remind_at) WHERE remind_at IS NOT NULL;
+
+-- Reminders already sent, kept apart from notes so sending one doesn't bump the
+-- note's version. Moving remind_at arms the reminder again.
+CREATE TABLE sent_reminders (
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    remind_at TIMESTAMPTZ NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (note_id, remind_at)
+);
+
+-- Secret behind the user's iCalendar feed URL, only its SHA-256 is stored
+ALTER TABLE users ADD COLUMN calendar_token_hash TEXT UNIQUE;
+
+-- Webhook payloads carry the new fields too
+CREATE OR REPLACE FUNCTION notes_queue_webhooks() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    note RECORD;
+    event TEXT;
+    data JSON;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        note := OLD;
+        event := 'note.deleted';
+        data := json_build_object('id', OLD.id);
+    ELSE
+        note := NEW;
+        event := CASE TG_OP WHEN 'INSERT' THEN 'note.created' ELSE 'note.updated' END;
+        data := json_build_object(
+            'id', NEW.id,
+            'title', NEW.title,
+            'content', NEW.content,
+            'language', NEW.language,
+            'version', NEW.version,
+            'due_at', NEW.due_at,
+            'remind_at', NEW.remind_at,
+            'created_at', NEW.created_at,
+            'updated_at', NEW.updated_at
+        );
+    END IF;
+
+    INSERT INTO webhook_deliveries (webhook_id, event, payload)
+    SELECT id, event, json_build_object('type', event, 'at', NOW(), 'user_id', note.user_id, 'note', data)
+    FROM webhooks
+    WHERE user_id = note.user_id AND active AND event = ANY(events);
+
+    RETURN NULL;
+END;
+$$;
\ No newline at end of file
@@ -0,0 +1,26 @@
+-- GFM task list items ("- [ ] todo", "1. [x] done") parsed out of note content.
+-- Lines inside ``` or ~~~ fences are skipped. task_index counts the note's tasks from 0
+-- in document order, line_number is 1-based over content split on \n.
+-- Both windows partition by user_id first so user_id filters reach the notes scan.
+CREATE VIEW note_tasks AS
+SELECT
+    note_id,
+    user_id,
+    (row_number() OVER (PARTITION BY user_id, note_id ORDER BY line_number) - 1)::INTEGER AS task_index,
+    line_number::INTEGER AS line_number,
+    task[1] <> ' ' AS done,
+    btrim(COALESCE(task[2], ''), E' \t\r') AS text
+FROM (
+    SELECT
+        n.id AS note_id,
+        n.user_id,
+        l.line_number,
+        l.line,
+        count(*) FILTER (WHERE l.line ~ '^\s*```') OVER fences % 2 = 1
+            OR count(*) FILTER (WHERE l.line ~ '^\s*~~~') OVER fences % 2 = 1 AS in_code
+    FROM notes n
+    CROSS JOIN LATERAL regexp_split_to_table(n.content, E'\n') WITH ORDINALITY AS l(line, line_number)
+    WINDOW fences AS (PARTITION BY n.user_id, n.id ORDER BY l.line_number)
+) lines
+CROSS JOIN LATERAL regexp_match(line, '^\s*(?:[-*+]|\d{1,9}[.)])\s+\[([ xX])\](?:\s+(.*))?\s*$') AS task
+WHERE NOT in_code AND task IS NOT NULL;
\ No newline at end of file
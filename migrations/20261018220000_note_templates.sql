@@ -0,0 +1,15 @@
+-- Reusable note skeletons, {{placeholders}} are filled in when a note is created from one
+CREATE TABLE note_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL DEFAULT '',
+    -- detected from the filled in note when NULL
+    language TEXT CHECK (language IN ('english', 'german', 'spanish', 'simple')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX idx_note_templates_user_id ON note_templates (user_id);
\ No newline at end of file
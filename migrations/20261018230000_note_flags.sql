@@ -0,0 +1,49 @@
+-- Per-note organisation state, set through their own endpoints rather than PUT /notes/{id}
+ALTER TABLE notes
+    ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN favorite BOOLEAN NOT NULL DEFAULT FALSE,
+    -- archived notes are left out of listings and search unless asked for
+    ADD COLUMN archived_at TIMESTAMPTZ;
+
+CREATE INDEX idx_notes_user_id_unarchived ON notes (user_id, pinned DESC, created_at DESC) WHERE archived_at IS NULL;
+
+-- Webhook payloads carry the new state too
+CREATE OR REPLACE FUNCTION notes_queue_webhooks() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    note RECORD;
+    event TEXT;
+    data JSON;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        note := OLD;
+        event := 'note.deleted';
+        data := json_build_object('id', OLD.id);
+    ELSE
+        note := NEW;
+        event := CASE TG_OP WHEN 'INSERT' THEN 'note.created' ELSE 'note.updated' END;
+        data := json_build_object(
+            'id', NEW.id,
+            'title', NEW.title,
+            'content', NEW.content,
+            'language', NEW.language,
+            'version', NEW.version,
+            'due_at', NEW.due_at,
+            'remind_at', NEW.remind_at,
+            'pinned', NEW.pinned,
+            'favorite', NEW.favorite,
+            'archived_at', NEW.archived_at,
+            'created_at', NEW.created_at,
+            'updated_at', NEW.updated_at
+        );
+    END IF;
+
+    INSERT INTO webhook_deliveries (webhook_id, event, payload)
+    SELECT id, event, json_build_object('type', event, 'at', NOW(), 'user_id', note.user_id, 'note', data)
+    FROM webhooks
+    WHERE user_id = note.user_id AND active AND event = ANY(events);
+
+    RETURN NULL;
+END;
+$$;
\ No newline at end of file
@@ -0,0 +1,46 @@
+-- Running totals checked against the per-user note quotas, kept up to date by the
+-- trigger below so writes don't have to sum every note. Sizes are the bytes of
+-- title plus content, attachment bytes are summed from the attachments table.
+CREATE TABLE user_usage (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    note_count BIGINT NOT NULL DEFAULT 0,
+    content_bytes BIGINT NOT NULL DEFAULT 0
+);
+
+INSERT INTO user_usage (user_id, note_count, content_bytes)
+SELECT user_id, COUNT(*), SUM(octet_length(title) + octet_length(content))
+FROM notes
+GROUP BY user_id;
+
+CREATE FUNCTION notes_track_usage() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO user_usage (user_id, note_count, content_bytes)
+        VALUES (NEW.user_id, 1, octet_length(NEW.title) + octet_length(NEW.content))
+        ON CONFLICT (user_id) DO UPDATE SET
+            note_count = user_usage.note_count + 1,
+            content_bytes = user_usage.content_bytes + EXCLUDED.content_bytes;
+    ELSIF TG_OP = 'UPDATE' THEN
+        UPDATE user_usage
+        SET content_bytes = content_bytes
+            + octet_length(NEW.title) + octet_length(NEW.content)
+            - octet_length(OLD.title) - octet_length(OLD.content)
+        WHERE user_id = NEW.user_id;
+    ELSE
+        -- no row left to update when the whole account is being deleted
+        UPDATE user_usage
+        SET 
+            note_count = note_count - 1,
+            content_bytes = content_bytes - octet_length(OLD.title) - octet_length(OLD.content)
+        WHERE user_id = OLD.user_id;
+    END IF;
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER notes_track_usage
+    AFTER INSERT OR DELETE OR UPDATE OF title, content ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_track_usage();
\ No newline at end of file
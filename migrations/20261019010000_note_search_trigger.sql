@@ -0,0 +1,30 @@
+-- The generated search_vector relied on an IMMUTABLE label the text to regconfig
+-- cast doesn't honour, keep the vector current from a trigger instead
+ALTER TABLE notes
+    -- cleared once a client picks the language, until then it's re-detected whenever the text changes
+    ADD COLUMN language_detected BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE notes ALTER COLUMN search_vector DROP EXPRESSION;
+
+-- STABLE, the text to regconfig cast depends on the search path
+CREATE OR REPLACE FUNCTION note_search_vector(config TEXT, title TEXT, content TEXT)
+    RETURNS tsvector
+    LANGUAGE sql
+    STABLE PARALLEL SAFE
+AS $$
+    SELECT setweight(to_tsvector(config::regconfig, title), 'A')
+        || setweight(to_tsvector(config::regconfig, content), 'B')
+$$;
+
+CREATE FUNCTION notes_update_search_vector() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+BEGIN
+    NEW.search_vector := note_search_vector(NEW.language, NEW.title, NEW.content);
+    RETURN NEW;
+END;
+$$;
+
+CREATE TRIGGER notes_search_vector
+    BEFORE INSERT OR UPDATE OF title, content, language ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_update_search_vector();
\ No newline at end of file
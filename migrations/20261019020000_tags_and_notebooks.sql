@@ -0,0 +1,26 @@
+-- Notebooks nest through parent_id, deleting one deletes its children too
+CREATE TABLE notebooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    parent_id UUID,
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (id, user_id),
+    -- a parent always belongs to the same user
+    FOREIGN KEY (parent_id, user_id) REFERENCES notebooks (id, user_id) ON DELETE CASCADE
+);
+
+-- sibling names ignore case, top level notebooks included
+CREATE UNIQUE INDEX idx_notebooks_user_parent_name ON notebooks (user_id, parent_id, lower(name)) NULLS NOT DISTINCT;
+CREATE INDEX idx_notebooks_parent_id ON notebooks (parent_id);
+
+ALTER TABLE notes
+    -- notes of a deleted notebook are kept without one
+    ADD COLUMN notebook_id UUID,
+    -- normalized, deduplicated and sorted by the API
+    ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}',
+    ADD FOREIGN KEY (notebook_id, user_id) REFERENCES notebooks (id, user_id) ON DELETE SET NULL (notebook_id);
+
+CREATE INDEX idx_notes_notebook_id ON notes (notebook_id);
+CREATE INDEX idx_notes_tags ON notes USING gin (tags);
\ No newline at end of file
@@ -0,0 +1,41 @@
+-- Other users a note is shared with, the owner stays in notes.user_id
+CREATE TABLE note_shares (
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- read only opens the note, write also lets them edit it together with the owner
+    permission TEXT NOT NULL DEFAULT 'read' CHECK (permission IN ('read', 'write')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (note_id, user_id)
+);
+
+CREATE INDEX idx_note_shares_user_id ON note_shares (user_id);
+
+-- Tell the user a note was shared with or taken away from them on the note_events channel
+CREATE FUNCTION note_shares_notify_event() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    share RECORD;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        share := OLD;
+    ELSE
+        share := NEW;
+    END IF;
+
+    PERFORM pg_notify('note_events', json_build_object(
+        'type', CASE TG_OP WHEN 'DELETE' THEN 'note.unshared' ELSE 'note.shared' END,
+        'user_id', share.user_id,
+        'note_id', share.note_id,
+        'version', NULL,
+        'at', NOW()
+    )::text);
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER note_shares_notify_event
+    AFTER INSERT OR UPDATE OF permission OR DELETE ON note_shares
+    FOR EACH ROW EXECUTE FUNCTION note_shares_notify_event();
\ No newline at end of file
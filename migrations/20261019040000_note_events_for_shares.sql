@@ -0,0 +1,48 @@
+-- Note events also go to everyone the note is shared with, one notification per user.
+-- Deletes are announced from a BEFORE trigger, after the delete the shares are gone.
+CREATE OR REPLACE FUNCTION notes_notify_event() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    note RECORD;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        note := OLD;
+    ELSE
+        note := NEW;
+    END IF;
+
+    PERFORM pg_notify('note_events', json_build_object(
+        'type', CASE TG_OP
+            WHEN 'INSERT' THEN 'note.created'
+            WHEN 'UPDATE' THEN 'note.updated'
+            ELSE 'note.deleted'
+        END,
+        'user_id', recipient.user_id,
+        'note_id', note.id,
+        'version', CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE note.version END,
+        'at', NOW()
+    )::text)
+    FROM (
+        SELECT note.user_id
+        UNION
+        SELECT user_id FROM note_shares WHERE note_id = note.id
+    ) AS recipient;
+
+    -- deletes are announced before the row goes, while its shares are still there
+    IF TG_WHEN = 'BEFORE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NULL;
+END;
+$$;
+
+DROP TRIGGER notes_notify_event ON notes;
+
+CREATE TRIGGER notes_notify_event
+    AFTER INSERT OR UPDATE ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_notify_event();
+
+CREATE TRIGGER notes_notify_deleted
+    BEFORE DELETE ON notes
+    FOR EACH ROW EXECUTE FUNCTION notes_notify_event();
\ No newline at end of file
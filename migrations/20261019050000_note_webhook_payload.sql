@@ -0,0 +1,83 @@
+-- The note as webhook payloads carry it. Triggers and the reminder job all build it
+-- here, so a new column only has to be added once.
+CREATE FUNCTION note_webhook_payload(note notes) RETURNS json
+    LANGUAGE sql
+    STABLE PARALLEL SAFE
+AS $$
+    SELECT json_build_object(
+        'id', note.id,
+        'title', note.title,
+        'content', note.content,
+        'language', note.language,
+        'notebook_id', note.notebook_id,
+        'tags', note.tags,
+        'version', note.version,
+        'due_at', note.due_at,
+        'remind_at', note.remind_at,
+        'pinned', note.pinned,
+        'favorite', note.favorite,
+        'archived_at', note.archived_at,
+        'created_at', note.created_at,
+        'updated_at', note.updated_at
+    )
+$$;
+
+CREATE OR REPLACE FUNCTION notes_queue_webhooks() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    note RECORD;
+    event TEXT;
+    data JSON;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        note := OLD;
+        event := 'note.deleted';
+        data := json_build_object('id', OLD.id);
+    ELSE
+        note := NEW;
+        event := CASE TG_OP WHEN 'INSERT' THEN 'note.created' ELSE 'note.updated' END;
+        data := note_webhook_payload(NEW);
+    END IF;
+
+    INSERT INTO webhook_deliveries (webhook_id, event, payload)
+    SELECT id, event, json_build_object('type', event, 'at', NOW(), 'user_id', note.user_id, 'note', data)
+    FROM webhooks
+    WHERE user_id = note.user_id AND active AND event = ANY(events);
+
+    RETURN NULL;
+END;
+$$;
+
+-- note.shared goes to the owner's webhooks and to those of the user the note was shared with
+CREATE FUNCTION note_shares_queue_webhooks() RETURNS trigger
+    LANGUAGE plpgsql
+AS $$
+DECLARE
+    owner_id UUID;
+    data JSON;
+BEGIN
+    SELECT notes.user_id, note_webhook_payload(notes)
+    INTO owner_id, data
+    FROM notes
+    WHERE notes.id = NEW.note_id;
+
+    INSERT INTO webhook_deliveries (webhook_id, event, payload)
+    SELECT id, 'note.shared', json_build_object(
+        'type', 'note.shared',
+        'at', NOW(),
+        'user_id', webhooks.user_id,
+        'note', data,
+        'shared_with', NEW.user_id,
+        'permission', NEW.permission
+    )
+    FROM webhooks
+    WHERE user_id IN (owner_id, NEW.user_id) AND active AND 'note.shared' = ANY(events);
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER note_shares_queue_webhooks
+    AFTER INSERT OR UPDATE OF permission ON note_shares
+    FOR EACH ROW EXECUTE FUNCTION note_shares_queue_webhooks();
\ No newline at end of file
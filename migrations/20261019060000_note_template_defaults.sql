@@ -0,0 +1,5 @@
+-- Where notes created from a template are filed and how they're tagged
+ALTER TABLE note_templates
+    ADD COLUMN notebook_id UUID,
+    ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}',
+    ADD FOREIGN KEY (notebook_id, user_id) REFERENCES notebooks (id, user_id) ON DELETE SET NULL (notebook_id);
\ No newline at end of file
@@ -1,7 +1,7 @@
 #!/bin/bash
 
 # Configuration
-BASE_URL="${API_BASE_URL:-http://localhost:8080}"
+BASE_URL="${API_BASE_URL:-http://localhost:8080/api/v1}"
 COOKIES_FILE="cookies.txt"
 TEST_EMAIL="test@example.com"
 TEST_PASSWORD="SecurePass123!"  # Updated to meet password requirements
@@ -44,6 +44,7 @@ make_request() {
     
     local http_code=$(echo $response | tr -d '\n' | sed -e 's/.*HTTPSTATUS://')
     local body=$(echo $response | sed -e 's/HTTPSTATUS:.*//g')
+    LAST_RESPONSE_BODY=$body
     
     echo "Response: $body"
     
@@ -82,6 +83,7 @@ make_get_request() {
     local body=$(echo $response | sed -e 's/HTTPSTATUS:.*//g')
     
     echo "Response: $body"
+    LAST_RESPONSE_BODY=$body
     
     if [ "$http_code" -eq "$expected_status" ]; then
         print_status $GREEN "✅ Status: $http_code (Expected: $expected_status)"
@@ -108,6 +110,38 @@ create_note() {
     echo $note_response | grep -o '"id":"[^"]*"' | cut -d'"' -f4
 }
 
+# Function to upload a file to a note and check status
+upload_file() {
+    local endpoint=$1
+    local file=$2
+    local expected_status=$3
+    local description=$4
+    
+    echo -e "\n${BLUE}Testing: ${description}${NC}"
+    
+    local response=$(curl -s -X POST "$BASE_URL$endpoint" \
+        -b $COOKIES_FILE \
+        -c $COOKIES_FILE \
+        -F "file=@$file;type=text/plain" \
+        -w "HTTPSTATUS:%{http_code}")
+    
+    local http_code=$(echo $response | tr -d '\n' | sed -e 's/.*HTTPSTATUS://')
+    local body=$(echo $response | sed -e 's/HTTPSTATUS:.*//g')
+    
+    echo "Response: $body"
+    LAST_RESPONSE_BODY=$body
+    
+    if [ "$http_code" -eq "$expected_status" ]; then
+        print_status $GREEN "✅ Status: $http_code (Expected: $expected_status)"
+        ((TESTS_PASSED++))
+        return 0
+    else
+        print_status $RED "❌ Status: $http_code (Expected: $expected_status)"
+        ((TESTS_FAILED++))
+        return 1
+    fi
+}
+
 # Function to cleanup
 cleanup() {
     rm -f $COOKIES_FILE
@@ -138,6 +172,16 @@ main() {
     make_request "GET" "/auth/me" \
         "" \
         200 "Get User Profile"
+
+    # Test 3 again on v2, which runs next to v1 with the same session
+    local v2_code=$(curl -s -o /dev/null -w "%{http_code}" -b $COOKIES_FILE "${BASE_URL%/v1}/v2/auth/me")
+    if [ "$v2_code" -eq 200 ]; then
+        print_status $GREEN "✅ Get User Profile on v2 returned 200"
+        ((TESTS_PASSED++))
+    else
+        print_status $RED "❌ Get User Profile on v2 returned $v2_code (Expected: 200)"
+        ((TESTS_FAILED++))
+    fi
     
     # Create multiple test notes for search testing
     print_status $YELLOW "\n📝 Creating test notes for search functionality..."
@@ -150,10 +194,49 @@ main() {
     NOTE_ID_6=$(create_note "React Components" "Building reusable React components with hooks and state management. Modern frontend development techniques.")
     NOTE_ID_7=$(create_note "API Development" "RESTful API design patterns and best practices. Learn about authentication, validation, and error handling.")
     
+    NOTE_ID_8=$(create_note "Wochenplanung" "Wir besprechen die Aufgaben für die nächste Woche und die offenen Fragen.")
+    
+    # Test 3a: Unsupported note language is rejected
+    make_request "POST" "/notes" \
+        '{"title":"Klingon","content":"nuqneH","language":"klingon"}' \
+        400 "Create note with unsupported language"
+    
     # Test 4: Get all notes
     make_request "GET" "/notes" \
         "" \
         200 "Get All Notes"
+
+    # Test 4a: Quotas and usage
+    make_get_request "/auth/me/usage" "" 200 "Get storage usage"
+    local oversized_file=$(mktemp)
+    python3 -c 'import json; print(json.dumps({"title": "Too big", "content": "x" * (1024 * 1024 + 1)}))' > $oversized_file
+    local oversized_code=$(curl -s -o /dev/null -w "%{http_code}" -X POST "$BASE_URL/notes" \
+        -H "Content-Type: application/json" -b $COOKIES_FILE --data-binary @$oversized_file)
+    if [ "$oversized_code" -eq 413 ]; then
+        print_status $GREEN "✅ Note over the size quota returned 413"
+        ((TESTS_PASSED++))
+    else
+        print_status $RED "❌ Note over the size quota returned $oversized_code (Expected: 413)"
+        ((TESTS_FAILED++))
+    fi
+    rm -f $oversized_file
+
+    # Test 4b: Bodies over API_MAX_REQUEST_SIZE are refused from their Content-Length, before any JSON is parsed
+    local max_request_size="${API_MAX_REQUEST_SIZE:-16777216}"
+    local huge_file=$(mktemp)
+    python3 -c "print('{\"title\":\"Huge\",\"content\":\"' + 'x' * $max_request_size + '\"}')" > $huge_file
+    local huge_response=$(curl -s -X POST "$BASE_URL/notes" \
+        -H "Content-Type: application/json" -b $COOKIES_FILE --data-binary @$huge_file -w "HTTPSTATUS:%{http_code}")
+    local huge_code=$(echo $huge_response | sed -e 's/.*HTTPSTATUS://')
+    if [ "$huge_code" -eq 413 ] && echo "$huge_response" | grep -q "Request body must not exceed"; then
+        print_status $GREEN "✅ Body over the request size limit returned 413"
+        ((TESTS_PASSED++))
+    else
+        print_status $RED "❌ Body over the request size limit returned $huge_code (Expected: 413)"
+        ((TESTS_FAILED++))
+    fi
+    rm -f $huge_file
+    make_request "POST" "/notes" '{"title":' 400 "Create note with malformed JSON"
     
     # SEARCH FUNCTIONALITY TESTS
     print_status $YELLOW "\n🔍 Testing Search Functionality..."
@@ -211,14 +294,112 @@ main() {
     
     # Test 22: Search for web development terms
     make_get_request "/notes" "search=web" 200 "Search for web development terms"
+
+    # Test 22a: Snippets escape the note text
+    make_request "POST" "/notes" '{"title":"Snippet escaping","content":"<img src=x onerror=alert(1)> zanzibar"}' 201 "Create note with markup in its content"
+    local markup_note_id=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+    make_get_request "/notes" "search=zanzibar" 200 "Search note with markup in its content"
+    if echo "$LAST_RESPONSE_BODY" | grep -q '"content_snippet":"&lt;img'; then
+        print_status $GREEN "✅ Search snippet escapes the note's markup"
+        ((TESTS_PASSED++))
+    else
+        print_status $RED "❌ Search snippet doesn't escape the note's markup"
+        ((TESTS_FAILED++))
+    fi
+    make_request "DELETE" "/notes/$markup_note_id" "" 204 "Delete note with markup"
     
     print_status $YELLOW "\n📊 Testing Search Edge Cases..."
     
     # Test 23: Search with URL encoding
     make_get_request "/notes" "search=machine%20learning" 200 "Search with URL encoding"
     
-    # Test 24: Search with quotes (expecting 400 since quotes may not be supported)
-    make_get_request "/notes" "search=data%20analysis" 200 "Search without quotes (instead of with quotes)"
+    # Test 24: Search with an exact phrase
+    make_get_request "/notes" "search=%22data%20analysis%22" 200 "Search with exact phrase"
+    
+    # Test 24a: Advanced search operators
+    make_get_request "/notes" "search=python%20-pandas" 200 "Search with exclusion"
+    make_get_request "/notes" "search=learn*" 200 "Search with prefix"
+    make_get_request "/notes" "search=C:%5Cpath*" 200 "Search with backslash in prefix"
+    make_get_request "/notes" "search=title:rust%20OR%20title:python" 200 "Search with title filter and OR"
+    make_get_request "/notes" "search=python%20after:2020-01-01%20before:2100-01-01" 200 "Search with date filters"
+    
+    # Test 24a2: Fuzzy search for misspellings and partial words
+    make_get_request "/notes" "search=javascrpt&mode=fuzzy" 200 "Fuzzy search with misspelling"
+    make_get_request "/notes" "search=Tutor&mode=fuzzy" 200 "Fuzzy search with partial word"
+    make_get_request "/notes" "search=python&mode=bogus" 400 "Search with unknown mode"
+    
+    # Test 24a3: Search uses the note's language for stemming
+    make_get_request "/notes" "search=Aufgabe" 200 "Search German note with stemming"
+    
+    # Test 24b: Malformed advanced search queries
+    make_get_request "/notes" "search=%22data%20analysis" 400 "Search with unterminated quote"
+    make_get_request "/notes" "search=before:yesterday" 400 "Search with invalid date"
+    make_get_request "/notes" "search=python%20OR" 400 "Search with dangling OR"
+    make_get_request "/notes" "search=tag:a%2Cb" 400 "Search with invalid tag"
+    
+    # Test 24c: Notebooks and tags
+    print_status $YELLOW "\n📚 Testing Notebooks and Tags..."
+    
+    local notebook_response=$(curl -s -X POST "$BASE_URL/notebooks" \
+        -H "Content-Type: application/json" \
+        -b $COOKIES_FILE \
+        -c $COOKIES_FILE \
+        -d '{"name":"Work"}')
+    NOTEBOOK_ID=$(echo $notebook_response | grep -o '"id":"[^"]*"' | cut -d'"' -f4)
+    
+    make_request "POST" "/notebooks" '{"name":"work"}' 409 "Create notebook with duplicate name"
+    make_request "POST" "/notebooks" '{"name":"a/b"}' 400 "Create notebook with a slash in its name"
+    make_request "GET" "/notebooks" "" 200 "List notebooks"
+    
+    if [ ! -z "$NOTEBOOK_ID" ]; then
+        make_request "POST" "/notebooks" "{\"name\":\"Projects\",\"parent_id\":\"$NOTEBOOK_ID\"}" 201 "Create nested notebook"
+        make_request "POST" "/notes" \
+            "{\"title\":\"Quarterly plan\",\"content\":\"Roadmap for the quarter\",\"notebook_id\":\"$NOTEBOOK_ID\",\"tags\":[\"#Planning\",\"work\"]}" \
+            201 "Create note in a notebook with tags"
+        make_get_request "/notes" "search=tag:planning" 200 "Search by tag"
+        make_get_request "/notes" "search=in:work%20roadmap" 200 "Search within a notebook"
+        make_request "PUT" "/notebooks/$NOTEBOOK_ID" "{\"name\":\"Office\"}" 200 "Rename notebook"
+    fi
+    make_request "POST" "/notes" '{"title":"Tagged","content":"x","tags":["two words"]}' 400 "Create note with invalid tag"
+    make_request "POST" "/notes" '{"title":"Filed","content":"x","notebook_id":"00000000-0000-0000-0000-000000000000"}' 400 "Create note in missing notebook"
+    
+    # Test 24d: Sharing notes
+    print_status $YELLOW "\n🤝 Testing Note Sharing..."
+    
+    local share_cookies="share_cookies.txt"
+    rm -f $share_cookies
+    curl -s -o /dev/null -X POST "$BASE_URL/auth/register" \
+        -H "Content-Type: application/json" \
+        -d "{\"username\":\"${TEST_USERNAME}_friend\",\"email\":\"friend_$TEST_EMAIL\",\"password\":\"$TEST_PASSWORD\"}"
+    curl -s -o /dev/null -X POST "$BASE_URL/auth/login" \
+        -H "Content-Type: application/json" \
+        -c $share_cookies \
+        -d "{\"email\":\"friend_$TEST_EMAIL\",\"password\":\"$TEST_PASSWORD\"}"
+    local friend_response=$(curl -s -b $share_cookies "$BASE_URL/auth/me")
+    FRIEND_ID=$(echo $friend_response | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+    
+    if [ ! -z "$NOTE_ID_1" ]; then
+        make_request "POST" "/notes/$NOTE_ID_1/shares" "{\"email\":\"friend_$TEST_EMAIL\",\"permission\":\"write\"}" 201 "Share note with another user"
+        make_request "POST" "/notes/$NOTE_ID_1/shares" "{\"email\":\"$TEST_EMAIL\"}" 400 "Share note with its owner"
+        make_request "POST" "/notes/$NOTE_ID_1/shares" '{"email":"nobody@example.com"}' 404 "Share note with unknown user"
+        make_request "GET" "/notes/$NOTE_ID_1/shares" "" 200 "List note shares"
+        
+        local shared_code=$(curl -s -o /dev/null -w "%{http_code}" -b $share_cookies "$BASE_URL/notes/$NOTE_ID_1")
+        if [ "$shared_code" = "200" ]; then
+            print_status $GREEN "✓ Shared note readable by the other user (Status: $shared_code)"
+            ((TESTS_PASSED++))
+        else
+            print_status $RED "✗ Shared note readable by the other user (Expected: 200, Got: $shared_code)"
+            ((TESTS_FAILED++))
+        fi
+        
+        if [ ! -z "$FRIEND_ID" ]; then
+            make_request "DELETE" "/notes/$NOTE_ID_1/shares/$FRIEND_ID" "" 204 "Stop sharing note"
+        fi
+    fi
+    rm -f $share_cookies
+    make_request "GET" "/notes/shared" "" 200 "List notes shared with me"
+    make_request "POST" "/notes/00000000-0000-0000-0000-000000000000/shares" '{"email":"nobody@example.com"}' 404 "Share missing note"
     
     # Test 25: Search for file extensions/formats
     make_get_request "/notes" "search=CSV" 200 "Search for file formats"
@@ -232,6 +413,43 @@ main() {
     # Test 28: Search for optimization terms
     make_get_request "/notes" "search=optimization" 200 "Search for optimization content"
     
+    # Test 28a: Search with pagination offset
+    make_get_request "/notes" "search=Python&limit=1&offset=1" 200 "Search with offset"
+    
+    # Test 28b: Search with custom highlight markers
+    make_get_request "/notes" "search=Python&highlight_start=%5B&highlight_end=%5D" 200 "Search with custom highlight markers"
+    
+    # Test 28c: Search with invalid highlight marker
+    make_get_request "/notes" "search=Python&highlight_start=%22" 400 "Search with invalid highlight marker"
+    
+    # SAVED SEARCH TESTS
+    print_status $YELLOW "\n💾 Testing Saved Searches..."
+    
+    local saved_search_response=$(curl -s -X POST "$BASE_URL/saved-searches" \
+        -H "Content-Type: application/json" \
+        -b $COOKIES_FILE \
+        -c $COOKIES_FILE \
+        -d '{"name":"Python notes","query":"python -pandas","sort":"newest"}')
+    SAVED_SEARCH_ID=$(echo $saved_search_response | grep -o '"id":"[^"]*"' | cut -d'"' -f4)
+    
+    make_request "GET" "/saved-searches" "" 200 "List saved searches"
+    make_request "POST" "/saved-searches" \
+        '{"name":"Python notes","query":"python"}' \
+        409 "Create saved search with duplicate name"
+    make_request "POST" "/saved-searches" \
+        '{"name":"Broken","query":"\"python"}' \
+        400 "Create saved search with malformed query"
+    make_get_request "/notes" "sort=title" 200 "List notes sorted by title"
+    
+    if [ ! -z "$SAVED_SEARCH_ID" ]; then
+        make_get_request "/saved-searches/$SAVED_SEARCH_ID/notes" "limit=5" 200 "Run saved search"
+        make_request "PUT" "/saved-searches/$SAVED_SEARCH_ID" \
+            '{"mode":"fuzzy","sort":"relevance"}' \
+            200 "Update saved search"
+        make_request "DELETE" "/saved-searches/$SAVED_SEARCH_ID" "" 204 "Delete saved search"
+        make_get_request "/saved-searches/$SAVED_SEARCH_ID/notes" "" 404 "Run deleted saved search"
+    fi
+    
     # Test specific note operations
     print_status $YELLOW "\n📋 Testing Note Operations..."
     
@@ -246,13 +464,285 @@ main() {
             '{"title":"Advanced JavaScript Tutorial","content":"Updated content covering advanced JavaScript concepts including async/await, promises, and modern ES features."}' \
             200 "Update Note"
         
+        # Test 30a: Attachments
+        local attachment_file=$(mktemp)
+        echo "attachment contents for the api tests" > $attachment_file
+        upload_file "/notes/$NOTE_ID_1/attachments" $attachment_file 201 "Upload attachment"
+        ATTACHMENT_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | cut -d'"' -f4)
+        make_get_request "/notes/$NOTE_ID_1/attachments" "" 200 "List attachments"
+        if [ ! -z "$ATTACHMENT_ID" ]; then
+            make_get_request "/notes/$NOTE_ID_1/attachments/$ATTACHMENT_ID" "" 200 "Download attachment"
+            local range_code=$(curl -s -o /dev/null -w "%{http_code}" -H "Range: bytes=0-9" \
+                -b $COOKIES_FILE "$BASE_URL/notes/$NOTE_ID_1/attachments/$ATTACHMENT_ID")
+            if [ "$range_code" -eq 206 ]; then
+                print_status $GREEN "✅ Ranged download returned 206"
+                ((TESTS_PASSED++))
+            else
+                print_status $RED "❌ Ranged download returned $range_code (Expected: 206)"
+                ((TESTS_FAILED++))
+            fi
+            make_request "DELETE" "/notes/$NOTE_ID_1/attachments/$ATTACHMENT_ID" "" 204 "Delete attachment"
+        fi
+        rm -f $attachment_file
+
+        # Test 30b: Rendered markdown
+        make_get_request "/notes/$NOTE_ID_1" "format=html" 200 "Get note rendered as HTML"
+        make_get_request "/notes/$NOTE_ID_1" "format=pdf" 400 "Get note with unknown format"
+        make_request "POST" "/render" \
+            '{"markdown":"# Todo\n\n- [x] done\n- [ ] open\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n```rust\nfn main() {}\n```\n\n<script>alert(1)</script>"}' \
+            200 "Render markdown"
+        make_get_request "/render/styles.css" "" 200 "Get code highlighting stylesheet"
+
+        # Test 30c: Wiki links and backlinks
+        NOTE_ID_9=$(create_note "Link Hub" "Links to [[Advanced JavaScript Tutorial]] and [[Not Written Yet]]")
+        make_get_request "/notes/$NOTE_ID_9/links" "" 200 "Get outgoing links including unresolved"
+        make_get_request "/notes/$NOTE_ID_1/backlinks" "" 200 "Get backlinks"
+        make_request "PUT" "/notes/$NOTE_ID_1?rewrite_links=true" \
+            '{"title":"Advanced JavaScript Guide"}' \
+            200 "Rename note and rewrite links"
+        make_get_request "/notes/$NOTE_ID_9" "" 200 "Get note with rewritten link"
+        make_get_request "/notes/00000000-0000-0000-0000-000000000000/backlinks" "" 404 "Backlinks of missing note"
+
+        # Test 30d: Note graph
+        make_get_request "/graph" "" 200 "Get note graph"
+        make_get_request "/graph" "format=dot&note_id=$NOTE_ID_9&depth=2" 200 "Get note neighborhood as DOT"
+        make_get_request "/graph" "format=graphml" 200 "Get note graph as GraphML"
+        make_get_request "/graph" "depth=50" 400 "Get note graph with too large depth"
+        make_get_request "/graph" "tag=work" 200 "Get note graph filtered by tag"
+        make_get_request "/graph" "notebook=Office&format=dot" 200 "Get note graph filtered by notebook"
+        make_get_request "/graph" "tag=two%20words" 400 "Get note graph filtered by invalid tag"
+
         # Test 31: Search for updated content
         make_get_request "/notes" "search=async" 200 "Search for updated content"
         
         # Test 32: Search for updated title
         make_get_request "/notes" "search=Advanced" 200 "Search for updated title"
     fi
-    
+
+    # Import tests
+    print_status $YELLOW "\n📥 Testing Imports..."
+
+    local enex_file=$(mktemp --suffix=.enex)
+    cat > $enex_file <<'ENEX'
+<?xml version="1.0" encoding="UTF-8"?>
+<en-export>
+<note><title>Imported Groceries</title><content><![CDATA[<en-note><div><en-todo checked="true"/>milk</div><div><en-todo/>eggs</div></en-note>]]></content><created>20240102T030405Z</created><tag>errands</tag></note>
+<note><title></title><content><![CDATA[<en-note>untitled</en-note>]]></content></note>
+</en-export>
+ENEX
+    upload_file "/import" $enex_file 202 "Start Evernote import"
+    IMPORT_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+    rm -f $enex_file
+    sleep 1
+    if [ ! -z "$IMPORT_ID" ]; then
+        make_get_request "/import/$IMPORT_ID" "" 200 "Get import progress and error report"
+    fi
+    make_get_request "/import" "" 200 "List imports"
+    make_get_request "/import/00000000-0000-0000-0000-000000000000" "" 404 "Get missing import"
+
+    # Batch tests
+    print_status $YELLOW "\n📦 Testing Batch Operations..."
+
+    make_request "POST" "/notes/batch" \
+        "{\"mode\":\"per_item\",\"operations\":[{\"op\":\"update\",\"id\":\"$NOTE_ID_1\",\"content\":\"Updated in a batch\"},{\"op\":\"delete\",\"id\":\"00000000-0000-0000-0000-000000000000\"}]}" \
+        200 "Per-item batch with one failing operation"
+
+    make_request "POST" "/notes/batch" \
+        '{"operations":[{"op":"create","title":"Rolled back","content":"never saved"},{"op":"delete","id":"00000000-0000-0000-0000-000000000000"}]}' \
+        404 "Atomic batch rolled back by a failing operation"
+
+    make_request "POST" "/notes/batch" \
+        '{"operations":[{"op":"archive","id":"00000000-0000-0000-0000-000000000000"}]}' \
+        400 "Batch with unknown operation"
+
+    if [ ! -z "$NOTEBOOK_ID" ]; then
+        make_request "POST" "/notes/batch" \
+            "{\"operations\":[{\"op\":\"move\",\"id\":\"$NOTE_ID_1\",\"notebook_id\":\"$NOTEBOOK_ID\"},{\"op\":\"tag\",\"id\":\"$NOTE_ID_1\",\"add\":[\"batch\"],\"remove\":[\"work\"]}]}" \
+            200 "Batch move and tag"
+    fi
+
+    make_request "POST" "/notes/batch" \
+        "{\"operations\":[{\"op\":\"move\",\"id\":\"$NOTE_ID_1\",\"notebook_id\":\"00000000-0000-0000-0000-000000000000\"}]}" \
+        400 "Batch move to a missing notebook"
+
+    # Sync tests
+    print_status $YELLOW "\n🔄 Testing Sync..."
+
+    make_get_request "/sync" "" 200 "Full sync without a token"
+    SYNC_TOKEN=$(echo $LAST_RESPONSE_BODY | grep -o '"token":"[^"]*"' | cut -d'"' -f4)
+    make_get_request "/sync" "since=$SYNC_TOKEN&limit=10" 200 "Delta sync since the last token"
+    make_get_request "/sync" "since=not-a-token" 400 "Sync with invalid token"
+
+    make_request "POST" "/sync" \
+        "{\"changes\":[{\"id\":\"$NOTE_ID_1\",\"base_version\":0,\"content\":\"Stale offline edit\"}]}" \
+        200 "Push a stale change and get a conflict report"
+
+    # Collaborative editing tests
+    print_status $YELLOW "\n🤝 Testing Collaborative Editing..."
+
+    make_get_request "/notes/$NOTE_ID_1/collab" "" 400 "Collab room without WebSocket upgrade"
+    make_get_request "/notes/00000000-0000-0000-0000-000000000000/collab" "" 404 "Collab room for missing note"
+
+    # Pin, favorite and archive tests
+    print_status $YELLOW "\n📌 Testing Pinning, Favorites and Archiving..."
+
+    if [ ! -z "$NOTE_ID_2" ]; then
+        make_request "PUT" "/notes/$NOTE_ID_2/pin" "" 200 "Pin note"
+        make_request "PUT" "/notes/$NOTE_ID_2/favorite" "" 200 "Favorite note"
+        make_request "PUT" "/notes/$NOTE_ID_2/archive" "" 200 "Archive note"
+        make_get_request "/notes" "" 200 "List notes without archived ones"
+        make_get_request "/notes" "include_archived=true" 200 "List notes including archived ones"
+        make_get_request "/notes" "search=python&include_archived=true" 200 "Search including archived notes"
+        make_request "DELETE" "/notes/$NOTE_ID_2/archive" "" 200 "Unarchive note"
+        make_request "DELETE" "/notes/$NOTE_ID_2/favorite" "" 200 "Unfavorite note"
+        make_request "DELETE" "/notes/$NOTE_ID_2/pin" "" 200 "Unpin note"
+    fi
+    make_request "PUT" "/notes/00000000-0000-0000-0000-000000000000/pin" "" 404 "Pin missing note"
+
+    # Reminder tests
+    print_status $YELLOW "\n⏰ Testing Due Dates and Reminders..."
+
+    DUE_AT=$(date -u -d "+2 days" +%Y-%m-%dT%H:%M:%SZ)
+    REMIND_AT=$(date -u -d "+1 day" +%Y-%m-%dT%H:%M:%SZ)
+    make_request "POST" "/notes" \
+        "{\"title\":\"Renew passport\",\"content\":\"Book an appointment\",\"due_at\":\"$DUE_AT\",\"remind_at\":\"$REMIND_AT\"}" \
+        201 "Create note with due date and reminder"
+    REMINDER_NOTE_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+
+    make_get_request "/notes/upcoming" "days=7" 200 "List upcoming notes"
+    make_get_request "/notes/upcoming" "days=0" 400 "List upcoming notes with invalid range"
+    make_get_request "/notes/overdue" "" 200 "List overdue notes"
+    if [ ! -z "$REMINDER_NOTE_ID" ]; then
+        make_request "PUT" "/notes/$REMINDER_NOTE_ID" '{"remind_at":null}' 200 "Clear a note's reminder"
+    fi
+
+    make_request "POST" "/calendar/token" "" 201 "Create calendar feed URL"
+    CALENDAR_TOKEN=$(echo $LAST_RESPONSE_BODY | grep -o '"token":"[^"]*"' | cut -d'"' -f4)
+    make_get_request "/calendar/$CALENDAR_TOKEN.ics" "" 200 "Fetch iCalendar feed"
+    make_request "DELETE" "/calendar/token" "" 204 "Revoke calendar feed URL"
+    make_get_request "/calendar/$CALENDAR_TOKEN.ics" "" 404 "Fetch revoked iCalendar feed"
+
+    # Task list tests
+    print_status $YELLOW "\n☑️ Testing Task Lists..."
+
+    make_request "POST" "/notes" \
+        '{"title":"Groceries","content":"- [ ] milk\n- [x] bread\n- [ ] eggs"}' \
+        201 "Create note with a task list"
+    TASK_NOTE_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+
+    make_get_request "/tasks" "status=open" 200 "List open tasks"
+    make_get_request "/tasks" "status=done" 200 "List done tasks"
+    make_get_request "/tasks" "status=someday" 400 "List tasks with unknown status"
+    if [ ! -z "$TASK_NOTE_ID" ]; then
+        make_request "PATCH" "/notes/$TASK_NOTE_ID/tasks/0" '{"version":1}' 200 "Tick a task"
+        make_request "PATCH" "/notes/$TASK_NOTE_ID/tasks/2" '{"done":true,"version":1}' 409 "Tick a task on a stale version"
+        make_request "PATCH" "/notes/$TASK_NOTE_ID/tasks/1" '{"done":false}' 200 "Untick a task"
+        make_request "PATCH" "/notes/$TASK_NOTE_ID/tasks/7" '{}' 404 "Toggle a missing task"
+    fi
+
+    make_request "POST" "/notes" \
+        '{"title":"Packing","content":"- [ ] bag\n  - [X] passport\n```\n- [ ] fenced\n```\n1. [ ] hotel"}' \
+        201 "Create note with nested and fenced tasks"
+    PACKING_NOTE_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+    if [ ! -z "$PACKING_NOTE_ID" ]; then
+        make_request "PATCH" "/notes/$PACKING_NOTE_ID/tasks/1" '{}' 200 "Untick a nested [X] task"
+        if echo "$LAST_RESPONSE_BODY" | grep -q '  - \[ \] passport'; then
+            print_status $GREEN "✅ Nested task unticked in place"
+            ((TESTS_PASSED++))
+        else
+            print_status $RED "❌ Nested task not unticked in place"
+            ((TESTS_FAILED++))
+        fi
+        make_request "PATCH" "/notes/$PACKING_NOTE_ID/tasks/2" '{}' 200 "Tick the task after a fenced block"
+        if echo "$LAST_RESPONSE_BODY" | grep -q '1. \[x\] hotel' && echo "$LAST_RESPONSE_BODY" | grep -q '\- \[ \] fenced'; then
+            print_status $GREEN "✅ Fenced task skipped"
+            ((TESTS_PASSED++))
+        else
+            print_status $RED "❌ Fenced task not skipped"
+            ((TESTS_FAILED++))
+        fi
+        make_request "PATCH" "/notes/$PACKING_NOTE_ID/tasks/3" '{}' 404 "Toggle a task inside a fenced block"
+        make_request "DELETE" "/notes/$PACKING_NOTE_ID" "" 204 "Delete task list note"
+    fi
+
+    # Template tests
+    print_status $YELLOW "\n📋 Testing Note Templates..."
+
+    make_request "POST" "/templates" \
+        '{"name":"Standup","title":"Standup {{date}}","content":"Host: {{user.username}}\nTopic: {{topic}}\n- [ ] Blockers"}' \
+        201 "Create template"
+    TEMPLATE_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+
+    make_request "POST" "/templates" '{"name":"Standup","title":"Again"}' 409 "Create template with duplicate name"
+    make_request "POST" "/templates" '{"name":"Incident","title":"Incident","tags":["ops"]}' 201 "Create template with default tags"
+    make_request "POST" "/templates" '{"name":"Outage","notebook_id":"00000000-0000-0000-0000-000000000000"}' 400 "Create template in missing notebook"
+    make_get_request "/templates" "" 200 "List templates"
+    if [ ! -z "$TEMPLATE_ID" ]; then
+        make_request "PUT" "/templates/$TEMPLATE_ID" '{"content":"Host: {{user.username}}\nTopic: {{topic}}"}' 200 "Update template"
+        make_request "POST" "/notes/from-template/$TEMPLATE_ID" '{"variables":{"topic":"Release"}}' 201 "Create note from template"
+        TEMPLATE_NOTE_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+        make_request "DELETE" "/templates/$TEMPLATE_ID" "" 204 "Delete template"
+    fi
+    make_request "POST" "/notes/from-template/00000000-0000-0000-0000-000000000000" '{}' 404 "Create note from missing template"
+
+    # Webhook tests
+    print_status $YELLOW "\n🪝 Testing Webhooks..."
+
+    make_request "POST" "/webhooks" \
+        '{"url":"https://example.com/hooks","events":["note.created","note.updated","note.deleted"]}' \
+        201 "Create webhook"
+    WEBHOOK_ID=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+
+    make_request "POST" "/webhooks" \
+        '{"url":"ftp://127.0.0.1/hooks","events":["note.created"]}' \
+        400 "Create webhook with non-HTTP URL"
+    make_request "POST" "/webhooks" \
+        '{"url":"https://example.com/hooks","events":["note.archived"]}' \
+        400 "Create webhook with unknown event"
+    make_request "POST" "/webhooks" \
+        '{"url":"http://127.0.0.1:9/hooks","events":["note.created"]}' \
+        400 "Create webhook pointing at loopback"
+    make_request "POST" "/webhooks" \
+        '{"url":"https://example.com/hooks","events":["note.shared"]}' \
+        201 "Create webhook for shared notes"
+    make_request "POST" "/webhooks" \
+        '{"url":"http://169.254.169.254/latest/meta-data","events":["note.created"]}' \
+        400 "Create webhook pointing at the metadata endpoint"
+    make_request "POST" "/webhooks" \
+        '{"url":"http://localhost:8080/hooks","events":["note.created"]}' \
+        400 "Create webhook for a host resolving to loopback"
+
+    make_get_request "/webhooks" "" 200 "List webhooks"
+    if [ ! -z "$WEBHOOK_ID" ]; then
+        make_request "POST" "/webhooks/$WEBHOOK_ID/ping" "" 202 "Ping webhook"
+        make_request "POST" "/notes" '{"title":"Webhook payload","content":"Tagged","tags":["webhook-check"]}' 201 "Create note for a webhook delivery"
+        local webhook_note_id=$(echo $LAST_RESPONSE_BODY | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4)
+        make_get_request "/webhooks/$WEBHOOK_ID/deliveries" "" 200 "Get webhook delivery log"
+        if echo "$LAST_RESPONSE_BODY" | grep -q '"tags":\["webhook-check"\]'; then
+            print_status $GREEN "✅ note.created payload carries the note's tags"
+            ((TESTS_PASSED++))
+        else
+            print_status $RED "❌ note.created payload is missing the note's tags"
+            ((TESTS_FAILED++))
+        fi
+        make_request "DELETE" "/notes/$webhook_note_id" "" 204 "Delete webhook payload note"
+        make_get_request "/webhooks/$WEBHOOK_ID/deliveries" "status=failed" 200 "Get failed webhook deliveries"
+        make_request "PUT" "/webhooks/$WEBHOOK_ID" '{"active":false}' 200 "Disable webhook"
+        make_request "DELETE" "/webhooks/$WEBHOOK_ID" "" 204 "Delete webhook"
+    fi
+    make_get_request "/webhooks/00000000-0000-0000-0000-000000000000/deliveries" "" 404 "Get deliveries of missing webhook"
+
+    # Export tests
+    print_status $YELLOW "\n📤 Testing Exports..."
+
+    make_get_request "/export" "format=json" 200 "Export notes as JSON"
+    make_get_request "/export" "format=json&search=python&limit=5" 200 "Export search results as JSON"
+    make_get_request "/export" "format=html" 200 "Export notes as HTML"
+    make_get_request "/export" "format=enex" 200 "Export notes as ENEX"
+    make_get_request "/export" "format=markdown-zip" 200 "Export notes as Markdown ZIP" 2>/dev/null
+    make_get_request "/export" "format=pdf" 400 "Export with unknown format"
+    make_get_request "/export" "format=json&search=tag:work" 200 "Export with tag filter"
+
     # Authentication and cleanup tests
     print_status $YELLOW "\n🔐 Testing Authentication Edge Cases..."
     
@@ -263,6 +753,7 @@ main() {
     
     # Test 34: Try to search after logout
     make_get_request "/notes" "search=python" 401 "Search without authentication"
+    make_get_request "/events" "" 401 "Event stream without authentication"
     
     # Login again for cleanup
     make_request "POST" "/auth/login" \
@@ -272,7 +763,7 @@ main() {
     # Clean up created notes
     print_status $YELLOW "\n🧹 Cleaning up test notes..."
     
-    for note_id in "$NOTE_ID_1" "$NOTE_ID_2" "$NOTE_ID_3" "$NOTE_ID_4" "$NOTE_ID_5" "$NOTE_ID_6" "$NOTE_ID_7"; do
+    for note_id in "$NOTE_ID_1" "$NOTE_ID_2" "$NOTE_ID_3" "$NOTE_ID_4" "$NOTE_ID_5" "$NOTE_ID_6" "$NOTE_ID_7" "$NOTE_ID_8" "$NOTE_ID_9" "$REMINDER_NOTE_ID" "$TASK_NOTE_ID" "$TEMPLATE_NOTE_ID"; do
         if [ ! -z "$note_id" ]; then
             make_request "DELETE" "/notes/$note_id" \
                 "" \
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+use rust_notes_api::config::{create_pool, run_migrations, Settings};
+use rust_notes_api::jobs::{create_job_registry, WorkerPool};
+use rust_notes_api::services::WebhookService;
+
+// Runs background jobs and webhook deliveries without serving the API, for
+// deployments that set JOBS_RUN_IN_PROCESS=false on the API servers
+#[actix_web::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = Settings::new()?;
+    let db_pool = create_pool(&settings.database).await?;
+
+    run_migrations(&db_pool).await?;
+
+    let webhook_service = WebhookService::new(db_pool.clone(), settings.webhooks.clone());
+    actix_web::rt::spawn(async move {
+        webhook_service.dispatch().await;
+    });
+
+    let worker_pool = Arc::new(WorkerPool::new(
+        db_pool.clone(),
+        create_job_registry(&db_pool, &settings)?,
+        settings.jobs.clone()
+    ));
+    worker_pool.run().await?;
+
+    Ok(())
+}
\ No newline at end of file
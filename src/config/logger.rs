@@ -0,0 +1,54 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::middleware::Logger;
+
+// Same fields as Logger::default(), but the request line goes through
+// redact_path so calendar feed tokens never reach the access log
+pub fn create_logger() -> Logger {
+    Logger::new(r#"%a "%{request}xi" %s %b "%{Referer}i" "%{User-Agent}i" %T"#)
+        .custom_request_replace("request", request_line)
+}
+
+fn request_line(req: &ServiceRequest) -> String {
+    let target = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or_else(|| req.path());
+    format!("{} {} {:?}", req.method(), redact_path(target), req.version())
+}
+
+// The feed token is the whole credential for /calendar/{token}.ics
+pub fn redact_path(target: &str) -> String {
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    };
+
+    let redacted = match path.strip_prefix("/calendar/") {
+        Some(file) if file.ends_with(".ics") && !file.contains('/') => "/calendar/[redacted].ics",
+        _ => path,
+    };
+
+    match query {
+        Some(query) => format!("{}?{}", redacted, query),
+        None => redacted.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_calendar_feed_token() {
+        assert_eq!(redact_path("/calendar/abc123def.ics"), "/calendar/[redacted].ics");
+        assert_eq!(redact_path("/calendar/abc123def.ics?x=1"), "/calendar/[redacted].ics?x=1");
+    }
+
+    #[test]
+    fn leaves_other_paths_alone() {
+        assert_eq!(redact_path("/calendar/token"), "/calendar/token");
+        assert_eq!(redact_path("/api/v1/notes?q=calendar"), "/api/v1/notes?q=calendar");
+        assert_eq!(redact_path("/calendar/a/b.ics"), "/calendar/a/b.ics");
+    }
+}
\ No newline at end of file
@@ -1,9 +1,17 @@
 pub mod database;
 pub mod redis;
 pub mod settings;
+pub mod storage;
 mod cors;
+mod logger;
+mod payload;
+mod session;
 
 pub use settings::*;
 pub use database::*;
 pub use redis::*;
-pub use cors::*;
\ No newline at end of file
+pub use storage::*;
+pub use cors::*;
+pub use logger::*;
+pub use payload::*;
+pub use session::*;
\ No newline at end of file
@@ -0,0 +1,33 @@
+use actix_web::error::{InternalError, JsonPayloadError, PayloadError};
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+use crate::config::ApiSettings;
+
+// Limit for JSON bodies, checked against Content-Length before anything is read
+// and again while streaming, so an oversized note is never deserialized
+pub fn create_json_config(api: &ApiSettings) -> web::JsonConfig {
+    let limit = api.max_request_size as usize;
+    web::JsonConfig::default()
+        .limit(limit)
+        .error_handler(move |err, _req| json_error(err, limit))
+}
+
+// Limit for raw Bytes/String bodies
+pub fn create_payload_config(api: &ApiSettings) -> web::PayloadConfig {
+    web::PayloadConfig::new(api.max_request_size as usize)
+}
+
+fn json_error(err: JsonPayloadError, limit: usize) -> actix_web::Error {
+    let response = match &err {
+        JsonPayloadError::OverflowKnownLength { .. }
+        | JsonPayloadError::Overflow { .. }
+        | JsonPayloadError::Payload(PayloadError::Overflow) => HttpResponse::PayloadTooLarge().json(json!({
+            "message": format!("Request body must not exceed {} bytes", limit)
+        })),
+        JsonPayloadError::ContentType => HttpResponse::UnsupportedMediaType()
+            .json(json!({ "message": "Expected a JSON body with Content-Type: application/json" })),
+        _ => HttpResponse::BadRequest().json(json!({ "message": format!("Invalid JSON body: {}", err) })),
+    };
+
+    InternalError::from_response(err, response).into()
+}
\ No newline at end of file
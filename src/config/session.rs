@@ -0,0 +1,42 @@
+use actix_session::config::{BrowserSession, PersistentSession, SessionLifecycle, TtlExtensionPolicy};
+use actix_session::storage::SessionStore;
+use actix_session::SessionMiddleware;
+use actix_web::cookie::time::Duration;
+use actix_web::cookie::Key;
+use crate::config::CookieSettings;
+
+pub fn create_session_middleware<S: SessionStore>(
+    store: S,
+    key: Key,
+    cookie: &CookieSettings
+) -> SessionMiddleware<S> {
+    let renewal = if cookie.rolling {
+        TtlExtensionPolicy::OnEveryRequest
+    } else {
+        TtlExtensionPolicy::OnStateChanges
+    };
+
+    // without a max age the cookie goes away with the browser, the stored state still expires on its own
+    let lifecycle: SessionLifecycle = match cookie.max_age {
+        Some(max_age) => PersistentSession::default()
+            .session_ttl(Duration::seconds(max_age as i64))
+            .session_ttl_extension_policy(renewal)
+            .into(),
+        None => BrowserSession::default()
+            .state_ttl_extension_policy(renewal)
+            .into(),
+    };
+
+    let domain = Some(cookie.domain.clone()).filter(|domain| !domain.is_empty());
+
+    SessionMiddleware::builder(store, key)
+        .cookie_name(cookie.name.clone())
+        .cookie_secure(cookie.secure)
+        .cookie_http_only(cookie.http_only)
+        // validated at startup
+        .cookie_same_site(cookie.same_site().unwrap_or(actix_web::cookie::SameSite::Lax))
+        .cookie_domain(domain)
+        .cookie_path(cookie.path.clone())
+        .session_lifecycle(lifecycle)
+        .build()
+}
\ No newline at end of file
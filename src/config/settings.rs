@@ -1,5 +1,7 @@
 use serde::{Deserialize, Deserializer};
+use actix_web::cookie::SameSite;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use dotenvy;
 use std::env;
 
@@ -46,9 +48,24 @@ pub struct CookieSettings {
     pub http_only: bool,
     pub secure: bool,
     pub same_site: String,
+    // empty means a host-only cookie
     pub domain: String,
+    // seconds, unset keeps the session until the browser is closed
     pub max_age: Option<u32>,
     pub path: String,
+    // extend the session on every request instead of only when it changes
+    pub rolling: bool,
+}
+
+impl CookieSettings {
+    pub fn same_site(&self) -> Result<SameSite> {
+        match self.same_site.to_lowercase().as_str() {
+            "strict" => Ok(SameSite::Strict),
+            "lax" => Ok(SameSite::Lax),
+            "none" => Ok(SameSite::None),
+            _ => Err(anyhow::anyhow!("Invalid cookie SameSite value: {}", self.same_site)),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -73,6 +90,81 @@ pub struct ApiSettings {
     pub max_request_size: u32,
     pub rate_limit: Option<u32>,
     pub api_prefix: String,
+    // set once v1 is on its way out, v1 responses then carry Deprecation/Sunset headers
+    pub v1_deprecation: Option<VersionDeprecation>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct VersionDeprecation {
+    pub deprecated_at: DateTime<Utc>,
+    pub sunset_at: Option<DateTime<Utc>>,
+}
+
+impl ApiSettings {
+    // The prefix with a version segment, "/api" becomes "/api/v1"
+    pub fn version_path(&self, version: u32) -> String {
+        format!("{}/v{}", self.api_prefix.trim_end_matches('/'), version)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    Local,
+    S3
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct StorageSettings {
+    pub backend: StorageBackend,
+    pub local_path: String,
+    pub s3_bucket: String,
+    pub s3_region: String,
+    pub s3_endpoint: Option<String>,
+    pub s3_access_key: Option<String>,
+    pub s3_secret_key: Option<String>,
+    pub max_file_size: u64,
+    pub user_quota: u64,
+    pub max_import_size: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SyncSettings {
+    pub tombstone_retention_days: u32,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct WebhookSettings {
+    pub max_attempts: u32,
+    // seconds before the first retry, doubled for every retry after that
+    pub retry_base_delay: u64,
+    pub disable_after_failures: u32,
+    pub timeout: u64,
+    // lets webhooks reach localhost and private networks, for local testing only
+    pub allow_private_networks: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct JobSettings {
+    // false when jobs are left to separate `worker` processes
+    pub run_in_process: bool,
+    pub concurrency: u32,
+    // seconds between queue polls while there's nothing to do
+    pub poll_interval: u64,
+    pub timeout: u64,
+    // seconds before the first retry, doubled for every retry after that
+    pub retry_base_delay: u64,
+    pub retention_days: u32,
+}
+
+// Per-user limits on notes, attachments are limited by StorageSettings::user_quota
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct QuotaSettings {
+    pub max_notes: u64,
+    // bytes of title plus content of a single note
+    pub max_note_size: u64,
+    // bytes of title plus content over all of a user's notes
+    pub max_content_bytes: u64,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
@@ -84,6 +176,11 @@ pub struct Settings {
     pub api: ApiSettings,
     pub cors: CorsSettings,
     pub cookie: CookieSettings,
+    pub storage: StorageSettings,
+    pub sync: SyncSettings,
+    pub webhooks: WebhookSettings,
+    pub jobs: JobSettings,
+    pub quotas: QuotaSettings,
 }
 
 impl Settings {
@@ -161,6 +258,14 @@ impl Settings {
                     .and_then(|s| s.parse().ok()),
                 api_prefix: env::var("API_PREFIX")
                     .unwrap_or_else(|_| "/api".to_string()),
+                v1_deprecation: parse_env_datetime("API_V1_DEPRECATED_AT")?
+                    .map(|deprecated_at| -> Result<VersionDeprecation> {
+                        Ok(VersionDeprecation {
+                            deprecated_at,
+                            sunset_at: parse_env_datetime("API_V1_SUNSET_AT")?,
+                        })
+                    })
+                    .transpose()?,
             },
 
             cors: CorsSettings {
@@ -212,12 +317,119 @@ impl Settings {
                 same_site: env::var("COOKIE_SAME_SITE")
                     .unwrap_or_else(|_| "Lax".to_string()),
                 domain: env::var("COOKIE_DOMAIN")
-                    .unwrap_or_else(|_| "localhost".to_string()),
+                    .unwrap_or_default(),
                 max_age: env::var("COOKIE_MAX_AGE")
                     .ok()
                     .and_then(|s| s.parse().ok()),
                 path: env::var("COOKIE_PATH")
                     .unwrap_or_else(|_| "/".to_string()),
+                rolling: env::var("COOKIE_ROLLING")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap_or(true),
+            },
+
+            storage: StorageSettings {
+                backend: match env::var("STORAGE_BACKEND").unwrap_or_default().to_lowercase().as_str() {
+                    "s3" => StorageBackend::S3,
+                    _ => StorageBackend::Local,
+                },
+                local_path: env::var("STORAGE_LOCAL_PATH")
+                    .unwrap_or_else(|_| "./data/attachments".to_string()),
+                s3_bucket: env::var("S3_BUCKET")
+                    .unwrap_or_default(),
+                s3_region: env::var("S3_REGION")
+                    .unwrap_or_else(|_| "us-east-1".to_string()),
+                s3_endpoint: env::var("S3_ENDPOINT")
+                    .ok(),
+                s3_access_key: env::var("S3_ACCESS_KEY")
+                    .ok(),
+                s3_secret_key: env::var("S3_SECRET_KEY")
+                    .ok(),
+                max_file_size: env::var("ATTACHMENT_MAX_FILE_SIZE")
+                    .unwrap_or_else(|_| "10485760".to_string())
+                    .parse()
+                    .unwrap_or(10485760),
+                user_quota: env::var("ATTACHMENT_USER_QUOTA")
+                    .unwrap_or_else(|_| "104857600".to_string())
+                    .parse()
+                    .unwrap_or(104857600),
+                max_import_size: env::var("IMPORT_MAX_FILE_SIZE")
+                    .unwrap_or_else(|_| "104857600".to_string())
+                    .parse()
+                    .unwrap_or(104857600),
+            },
+
+            sync: SyncSettings {
+                tombstone_retention_days: env::var("SYNC_TOMBSTONE_RETENTION_DAYS")
+                    .unwrap_or_else(|_| "90".to_string())
+                    .parse()
+                    .unwrap_or(90),
+            },
+
+            webhooks: WebhookSettings {
+                max_attempts: env::var("WEBHOOK_MAX_ATTEMPTS")
+                    .unwrap_or_else(|_| "8".to_string())
+                    .parse()
+                    .unwrap_or(8),
+                retry_base_delay: env::var("WEBHOOK_RETRY_BASE_DELAY")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30),
+                disable_after_failures: env::var("WEBHOOK_DISABLE_AFTER_FAILURES")
+                    .unwrap_or_else(|_| "20".to_string())
+                    .parse()
+                    .unwrap_or(20),
+                timeout: env::var("WEBHOOK_TIMEOUT")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10),
+                allow_private_networks: env::var("WEBHOOK_ALLOW_PRIVATE_NETWORKS")
+                    .unwrap_or_else(|_| "false".to_string())
+                    .parse()
+                    .unwrap_or(false),
+            },
+
+            jobs: JobSettings {
+                run_in_process: env::var("JOBS_RUN_IN_PROCESS")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()
+                    .unwrap_or(true),
+                concurrency: env::var("JOBS_CONCURRENCY")
+                    .unwrap_or_else(|_| "4".to_string())
+                    .parse()
+                    .unwrap_or(4),
+                poll_interval: env::var("JOBS_POLL_INTERVAL")
+                    .unwrap_or_else(|_| "1".to_string())
+                    .parse()
+                    .unwrap_or(1),
+                timeout: env::var("JOBS_TIMEOUT")
+                    .unwrap_or_else(|_| "600".to_string())
+                    .parse()
+                    .unwrap_or(600),
+                retry_base_delay: env::var("JOBS_RETRY_BASE_DELAY")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10),
+                retention_days: env::var("JOBS_RETENTION_DAYS")
+                    .unwrap_or_else(|_| "7".to_string())
+                    .parse()
+                    .unwrap_or(7),
+            },
+
+            quotas: QuotaSettings {
+                max_notes: env::var("QUOTA_MAX_NOTES")
+                    .unwrap_or_else(|_| "10000".to_string())
+                    .parse()
+                    .unwrap_or(10000),
+                max_note_size: env::var("QUOTA_MAX_NOTE_SIZE")
+                    .unwrap_or_else(|_| "1048576".to_string())
+                    .parse()
+                    .unwrap_or(1048576),
+                max_content_bytes: env::var("QUOTA_MAX_CONTENT_BYTES")
+                    .unwrap_or_else(|_| "104857600".to_string())
+                    .parse()
+                    .unwrap_or(104857600),
             },
         };
 
@@ -247,6 +459,20 @@ impl Settings {
             return Err(anyhow::anyhow!("Min db idle connections must be less than max db connections"));
         }
 
+        // Session cookies must never travel over plain http in production
+        if self.is_production() && !self.cookie.secure {
+            return Err(anyhow::anyhow!("COOKIE_SECURE must be true in production"));
+        }
+        if self.cookie.same_site()? == SameSite::None && !self.cookie.secure {
+            return Err(anyhow::anyhow!("Cookies with SameSite=None must be secure"));
+        }
+        if self.cookie.max_age == Some(0) {
+            return Err(anyhow::anyhow!("Cookie max age must be at least 1 second, leave it unset for browser sessions"));
+        }
+        if !self.cookie.path.starts_with('/') {
+            return Err(anyhow::anyhow!("Cookie path must start with /"));
+        }
+
         // Validate CORS origins are valid URLs (basic check)
         for origin in &self.cors.allowed_origins {
             if !origin.starts_with("http://") && !origin.starts_with("https://") {
@@ -254,15 +480,60 @@ impl Settings {
             }
         }
 
+        if self.storage.backend == StorageBackend::S3 && self.storage.s3_bucket.is_empty() {
+            return Err(anyhow::anyhow!("S3_BUCKET must be set when using the s3 storage backend"));
+        }
+        if self.storage.max_file_size > self.storage.user_quota {
+            return Err(anyhow::anyhow!("Attachment max file size must not exceed the per-user quota"));
+        }
+
+        if self.sync.tombstone_retention_days == 0 {
+            return Err(anyhow::anyhow!("Sync tombstone retention must be at least one day"));
+        }
+
+        if self.webhooks.max_attempts == 0 || self.webhooks.disable_after_failures == 0 || self.webhooks.timeout == 0 {
+            return Err(anyhow::anyhow!("Webhook attempts, failure limit and timeout must be at least 1"));
+        }
+
+        if self.jobs.concurrency == 0 || self.jobs.poll_interval == 0 || self.jobs.timeout == 0 {
+            return Err(anyhow::anyhow!("Job concurrency, poll interval and timeout must be at least 1"));
+        }
+
+        if self.quotas.max_notes == 0 || self.quotas.max_note_size == 0 {
+            return Err(anyhow::anyhow!("Note count and note size quotas must be at least 1"));
+        }
+        if self.quotas.max_note_size > self.quotas.max_content_bytes {
+            return Err(anyhow::anyhow!("Max note size must not exceed the per-user content quota"));
+        }
+
+        if self.api.request_timeout == 0 || self.api.max_request_size == 0 {
+            return Err(anyhow::anyhow!("API request timeout and max request size must be at least 1"));
+        }
+
         // Validate API prefix starts with /
         if !self.api.api_prefix.starts_with('/') {
             return Err(anyhow::anyhow!("API prefix must start with /"));
         }
+        let sunset_too_early = self.api.v1_deprecation
+            .is_some_and(|deprecation| deprecation.sunset_at.is_some_and(|sunset_at| sunset_at < deprecation.deprecated_at));
+        if sunset_too_early {
+            return Err(anyhow::anyhow!("API v1 sunset must not be before its deprecation"));
+        }
 
         Ok(())
     }
 }
 
+// Optional RFC 3339 timestamp, set but unparseable is an error rather than silently ignored
+fn parse_env_datetime(name: &str) -> Result<Option<DateTime<Utc>>> {
+    match env::var(name) {
+        Ok(value) => DateTime::parse_from_rfc3339(&value)
+            .map(|datetime| Some(datetime.with_timezone(&Utc)))
+            .map_err(|e| anyhow::anyhow!("{} must be an RFC 3339 timestamp: {}", name, e)),
+        Err(_) => Ok(None),
+    }
+}
+
 pub fn get_settings() -> Result<Settings> {
     Settings::new()
 }
\ No newline at end of file
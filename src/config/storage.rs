@@ -0,0 +1,24 @@
+use std::sync::Arc;
+use anyhow::Result;
+use crate::config::settings::{StorageBackend, StorageSettings};
+use crate::storage::{BlobStore, LocalBlobStore, S3BlobStore};
+
+pub fn create_blob_store(settings: &StorageSettings) -> Result<Arc<dyn BlobStore>> {
+    let store: Arc<dyn BlobStore> = match settings.backend {
+        StorageBackend::Local => Arc::new(LocalBlobStore::new(&settings.local_path)),
+        StorageBackend::S3 => Arc::new(
+            S3BlobStore::new(
+                &settings.s3_bucket,
+                &settings.s3_region,
+                settings.s3_endpoint.as_deref(),
+                settings.s3_access_key.as_deref(),
+                settings.s3_secret_key.as_deref(),
+            )
+                .map_err(|e| anyhow::anyhow!("Failed to create S3 blob store: {}", e))?
+        ),
+    };
+
+    println!("✅ Attachment storage configured with {:?} backend", settings.backend);
+
+    Ok(store)
+}
\ No newline at end of file
@@ -0,0 +1,61 @@
+use actix_multipart::Multipart;
+use actix_web::{get, post, delete, web, HttpRequest, HttpResponse, Error};
+use actix_web::http::header;
+use uuid::Uuid;
+use crate::models::{AttachmentPath, AuthenticatedUser};
+use crate::services::AttachmentService;
+
+#[post("")]
+async fn upload_attachment(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    payload: Multipart,
+    service: web::Data<AttachmentService>
+) -> Result<HttpResponse, Error> {
+    let note_id = path.into_inner();
+    service.upload_attachment(user.0, note_id, payload).await
+}
+
+#[get("")]
+async fn get_attachments(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<AttachmentService>
+) -> Result<HttpResponse, Error> {
+    let note_id = path.into_inner();
+    service.get_note_attachments(user.0, note_id).await
+}
+
+#[get("/{attachment_id}")]
+async fn download_attachment(
+    req: HttpRequest,
+    user: AuthenticatedUser,
+    path: web::Path<AttachmentPath>,
+    service: web::Data<AttachmentService>
+) -> Result<HttpResponse, Error> {
+    let range = req
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+    service.download_attachment(user.0, path.note_id, path.attachment_id, range).await
+}
+
+#[delete("/{attachment_id}")]
+async fn delete_attachment(
+    user: AuthenticatedUser,
+    path: web::Path<AttachmentPath>,
+    service: web::Data<AttachmentService>
+) -> Result<HttpResponse, Error> {
+    service.delete_attachment(user.0, path.note_id, path.attachment_id).await
+}
+
+// Mounted inside the /notes scope, which already applies the auth middleware
+pub fn configure_attachments_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/{note_id}/attachments")
+            .service(upload_attachment)
+            .service(get_attachments)
+            .service(download_attachment)
+            .service(delete_attachment)
+    );
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use crate::middleware::auth_middleware;
+use crate::models::AuthenticatedUser;
+use crate::services::ReminderService;
+
+#[get("/{token}.ics")]
+async fn get_calendar_feed(
+    path: web::Path<String>,
+    service: web::Data<ReminderService>
+) -> Result<HttpResponse, Error> {
+    service.get_calendar_feed(&path.into_inner()).await
+}
+
+#[post("/token")]
+async fn rotate_calendar_token(
+    user: AuthenticatedUser,
+    req: HttpRequest,
+    service: web::Data<ReminderService>
+) -> Result<HttpResponse, Error> {
+    service.rotate_calendar_token(user.0, &req).await
+}
+
+#[delete("/token")]
+async fn revoke_calendar_token(
+    user: AuthenticatedUser,
+    service: web::Data<ReminderService>
+) -> Result<HttpResponse, Error> {
+    service.revoke_calendar_token(user.0).await
+}
+
+pub fn configure_calendar_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/calendar")
+            // Public, calendar apps authenticate with the token in the URL
+            .service(get_calendar_feed)
+            // Protected sub-scope
+            .service(
+                web::scope("")
+                    .wrap(from_fn(auth_middleware))
+                    .service(rotate_calendar_token)
+                    .service(revoke_calendar_token)
+            )
+    );
+}
\ No newline at end of file
@@ -0,0 +1,21 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Error};
+use uuid::Uuid;
+use crate::models::AuthenticatedUser;
+use crate::services::CollabService;
+
+#[get("/{note_id}/collab")]
+async fn join_room(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    req: HttpRequest,
+    body: web::Payload,
+    service: web::Data<CollabService>
+) -> Result<HttpResponse, Error> {
+    let note_id = path.into_inner();
+    service.into_inner().join_room(user.0, note_id, &req, body).await
+}
+
+// Mounted inside the /notes scope, which already applies the auth middleware
+pub fn configure_collab_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(join_room);
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use crate::middleware::auth_middleware;
+use crate::models::AuthenticatedUser;
+use crate::services::EventService;
+
+#[get("")]
+async fn stream_events(
+    user: AuthenticatedUser,
+    service: web::Data<EventService>
+) -> HttpResponse {
+    service.stream_events(user.0)
+}
+
+#[get("/ws")]
+async fn open_websocket(
+    user: AuthenticatedUser,
+    req: HttpRequest,
+    body: web::Payload,
+    service: web::Data<EventService>
+) -> Result<HttpResponse, Error> {
+    service.open_websocket(user.0, &req, body)
+}
+
+pub fn configure_events_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/events")
+            .wrap(from_fn(auth_middleware))
+            .service(stream_events)
+            .service(open_websocket)
+    );
+}
\ No newline at end of file
@@ -0,0 +1,23 @@
+use actix_web::{get, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, ExportParams, QueryParams};
+use crate::services::ExportService;
+
+#[get("")]
+async fn export_notes(
+    user: AuthenticatedUser,
+    export: web::Query<ExportParams>,
+    query: web::Query<QueryParams>,
+    service: web::Data<ExportService>
+) -> Result<HttpResponse, Error> {
+    service.export_notes(user.0, export.format.unwrap_or_default(), &query).await
+}
+
+pub fn configure_exports_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/export")
+            .wrap(from_fn(auth_middleware))
+            .service(export_notes)
+    );
+}
\ No newline at end of file
@@ -0,0 +1,22 @@
+use actix_web::{get, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, GraphParams};
+use crate::services::GraphService;
+
+#[get("")]
+async fn get_graph(
+    user: AuthenticatedUser,
+    query: web::Query<GraphParams>,
+    service: web::Data<GraphService>
+) -> Result<HttpResponse, Error> {
+    service.get_graph(user.0, &query).await
+}
+
+pub fn configure_graph_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/graph")
+            .wrap(from_fn(auth_middleware))
+            .service(get_graph)
+    );
+}
\ No newline at end of file
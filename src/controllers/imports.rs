@@ -0,0 +1,45 @@
+use actix_multipart::Multipart;
+use actix_web::{get, post, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use uuid::Uuid;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, ImportParams};
+use crate::services::ImportService;
+
+#[post("")]
+async fn start_import(
+    user: AuthenticatedUser,
+    query: web::Query<ImportParams>,
+    payload: Multipart,
+    service: web::Data<ImportService>
+) -> Result<HttpResponse, Error> {
+    service.start_import(user.0, query.format, payload).await
+}
+
+#[get("")]
+async fn get_imports(
+    user: AuthenticatedUser,
+    service: web::Data<ImportService>
+) -> Result<HttpResponse, Error> {
+    service.get_imports(user.0).await
+}
+
+#[get("/{import_id}")]
+async fn get_import(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<ImportService>
+) -> Result<HttpResponse, Error> {
+    let import_id = path.into_inner();
+    service.get_import(user.0, import_id).await
+}
+
+pub fn configure_imports_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/import")
+            .wrap(from_fn(auth_middleware))
+            .service(start_import)
+            .service(get_imports)
+            .service(get_import)
+    );
+}
\ No newline at end of file
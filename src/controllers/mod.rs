@@ -1,4 +1,38 @@
+pub mod attachments;
+pub mod calendar;
+pub mod collab;
+pub mod events;
+pub mod exports;
+pub mod graph;
+pub mod imports;
 pub mod users;
+pub mod notebooks;
 pub mod notes;
+pub mod reminders;
+pub mod render;
+pub mod saved_searches;
+pub mod shares;
+pub mod sync;
+pub mod tasks;
+pub mod templates;
+pub mod versions;
+pub mod webhooks;
+pub use attachments::*;
+pub use calendar::*;
+pub use collab::*;
+pub use events::*;
+pub use exports::*;
+pub use graph::*;
+pub use imports::*;
 pub use users::*;
-pub use notes::*;
\ No newline at end of file
+pub use notebooks::*;
+pub use notes::*;
+pub use reminders::*;
+pub use render::*;
+pub use saved_searches::*;
+pub use shares::*;
+pub use sync::*;
+pub use tasks::*;
+pub use templates::*;
+pub use versions::*;
+pub use webhooks::*;
\ No newline at end of file
@@ -0,0 +1,66 @@
+use actix_web::{get, post, put, delete, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use uuid::Uuid;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, CreateNotebookDto, UpdateNotebook};
+use crate::services::NotebookService;
+
+#[get("")]
+async fn get_notebooks(
+    user: AuthenticatedUser,
+    service: web::Data<NotebookService>
+) -> Result<HttpResponse, Error> {
+    service.get_notebooks(user.0).await
+}
+
+#[get("/{notebook_id}")]
+async fn get_notebook(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NotebookService>
+) -> Result<HttpResponse, Error> {
+    let notebook_id = path.into_inner();
+    service.get_notebook_by_id(user.0, notebook_id).await
+}
+
+#[post("")]
+async fn create_notebook(
+    user: AuthenticatedUser,
+    payload: web::Json<CreateNotebookDto>,
+    service: web::Data<NotebookService>
+) -> Result<HttpResponse, Error> {
+    service.create_notebook(user.0, payload.into_inner()).await
+}
+
+#[put("/{notebook_id}")]
+async fn update_notebook(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    payload: web::Json<UpdateNotebook>,
+    service: web::Data<NotebookService>
+) -> Result<HttpResponse, Error> {
+    let notebook_id = path.into_inner();
+    service.update_notebook(user.0, notebook_id, payload.into_inner()).await
+}
+
+#[delete("/{notebook_id}")]
+async fn delete_notebook(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NotebookService>
+) -> Result<HttpResponse, Error> {
+    let notebook_id = path.into_inner();
+    service.delete_notebook(user.0, notebook_id).await
+}
+
+pub fn configure_notebooks_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/notebooks")
+            .wrap(from_fn(auth_middleware))
+            .service(get_notebooks)
+            .service(get_notebook)
+            .service(create_notebook)
+            .service(update_notebook)
+            .service(delete_notebook)
+    );
+}
\ No newline at end of file
@@ -1,8 +1,9 @@
 use actix_web::{get, post, put, delete, web, HttpResponse, Error};
 use actix_web::middleware::from_fn;
 use uuid::Uuid;
+use crate::controllers::{configure_attachments_controller, configure_collab_controller, configure_note_tasks_controller, configure_reminders_controller, configure_shares_controller, configure_template_notes_controller};
 use crate::middleware::auth_middleware;
-use crate::models::{CreateNoteDto, QueryParams, UpdateNote, NewNote, AuthenticatedUser};
+use crate::models::{BatchRequest, CreateNoteDto, NoteFlags, NoteFormatParams, QueryParams, UpdateNote, UpdateNoteParams, NewNote, AuthenticatedUser};
 use crate::services::NoteService;
 
 #[get("")]
@@ -11,21 +12,18 @@ async fn get_notes(
     query: web::Query<QueryParams>,
     service: web::Data<NoteService>
 ) -> Result<HttpResponse, Error> {
-    if let Some(search_term) = &query.search {
-        service.search_notes(user.0, search_term.clone(), query.limit).await
-    } else {
-        service.get_users_notes(user.0, query.limit, query.offset).await
-    }
+    service.list_notes(user.0, &query).await
 }
 
 #[get("/{note_id}")]
 async fn get_note(
     user: AuthenticatedUser,
     path: web::Path<Uuid>,
+    query: web::Query<NoteFormatParams>,
     service: web::Data<NoteService>
 ) -> Result<HttpResponse, Error> {
     let note_id = path.into_inner();
-    service.get_note_by_id(user.0, note_id).await
+    service.get_note_by_id(user.0, note_id, query.format.unwrap_or_default()).await
 }
 
 #[post("")]
@@ -34,19 +32,57 @@ async fn create_note(
     payload: web::Json<CreateNoteDto>,
     service: web::Data<NoteService>
 ) -> Result<HttpResponse, Error> {
-    let new_note = NewNote::new(user.0, payload.title.clone(), payload.content.clone());
+    let mut new_note = NewNote::new(user.0, payload.title.clone(), payload.content.clone());
+    if let Some(language) = payload.language {
+        new_note = new_note.with_language(language);
+    }
+    new_note = new_note
+        .with_schedule(payload.due_at, payload.remind_at)
+        .with_notebook(payload.notebook_id)
+        .with_tags(payload.tags.clone());
     service.create_note(new_note).await
 }
 
+#[post("/batch")]
+async fn run_batch(
+    user: AuthenticatedUser,
+    payload: web::Json<BatchRequest>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    service.run_batch(user.0, payload.into_inner()).await
+}
+
 #[put("/{note_id}")]
 async fn update_note(
     user: AuthenticatedUser,
     path: web::Path<Uuid>,
+    query: web::Query<UpdateNoteParams>,
     payload: web::Json<UpdateNote>,
     service: web::Data<NoteService>
 ) -> Result<HttpResponse, Error> {
     let note_id = path.into_inner();
-    service.update_note(user.0, note_id, payload.into_inner()).await
+    let rewrite_links = query.rewrite_links.unwrap_or(false);
+    service.update_note(user.0, note_id, payload.into_inner(), rewrite_links).await
+}
+
+#[get("/{note_id}/links")]
+async fn get_note_links(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let note_id = path.into_inner();
+    service.get_note_links(user.0, note_id).await
+}
+
+#[get("/{note_id}/backlinks")]
+async fn get_note_backlinks(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let note_id = path.into_inner();
+    service.get_note_backlinks(user.0, note_id).await
 }
 
 #[delete("/{note_id}")]
@@ -59,14 +95,89 @@ async fn delete_note(
     service.delete_note(user.0, note_id).await
 }
 
+#[put("/{note_id}/pin")]
+async fn pin_note(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let flags = NoteFlags { pinned: Some(true), ..NoteFlags::default() };
+    service.set_note_flags(user.0, path.into_inner(), flags).await
+}
+
+#[delete("/{note_id}/pin")]
+async fn unpin_note(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let flags = NoteFlags { pinned: Some(false), ..NoteFlags::default() };
+    service.set_note_flags(user.0, path.into_inner(), flags).await
+}
+
+#[put("/{note_id}/favorite")]
+async fn favorite_note(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let flags = NoteFlags { favorite: Some(true), ..NoteFlags::default() };
+    service.set_note_flags(user.0, path.into_inner(), flags).await
+}
+
+#[delete("/{note_id}/favorite")]
+async fn unfavorite_note(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let flags = NoteFlags { favorite: Some(false), ..NoteFlags::default() };
+    service.set_note_flags(user.0, path.into_inner(), flags).await
+}
+
+#[put("/{note_id}/archive")]
+async fn archive_note(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let flags = NoteFlags { archived: Some(true), ..NoteFlags::default() };
+    service.set_note_flags(user.0, path.into_inner(), flags).await
+}
+
+#[delete("/{note_id}/archive")]
+async fn unarchive_note(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let flags = NoteFlags { archived: Some(false), ..NoteFlags::default() };
+    service.set_note_flags(user.0, path.into_inner(), flags).await
+}
+
 pub fn configure_notes_controller(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/notes")
             .wrap(from_fn(auth_middleware))
             .service(get_notes)
+            .configure(configure_reminders_controller)
+            .configure(configure_shares_controller)
             .service(get_note)
             .service(create_note)
+            .service(run_batch)
             .service(update_note)
             .service(delete_note)
+            .service(get_note_links)
+            .service(get_note_backlinks)
+            .service(pin_note)
+            .service(unpin_note)
+            .service(favorite_note)
+            .service(unfavorite_note)
+            .service(archive_note)
+            .service(unarchive_note)
+            .configure(configure_attachments_controller)
+            .configure(configure_collab_controller)
+            .configure(configure_note_tasks_controller)
+            .configure(configure_template_notes_controller)
     );
 }
\ No newline at end of file
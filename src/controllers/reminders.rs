@@ -0,0 +1,27 @@
+use actix_web::{get, web, HttpResponse, Error};
+use crate::models::{AuthenticatedUser, DueNotesParams};
+use crate::services::ReminderService;
+
+#[get("/upcoming")]
+async fn get_upcoming_notes(
+    user: AuthenticatedUser,
+    query: web::Query<DueNotesParams>,
+    service: web::Data<ReminderService>
+) -> Result<HttpResponse, Error> {
+    service.get_upcoming_notes(user.0, &query).await
+}
+
+#[get("/overdue")]
+async fn get_overdue_notes(
+    user: AuthenticatedUser,
+    query: web::Query<DueNotesParams>,
+    service: web::Data<ReminderService>
+) -> Result<HttpResponse, Error> {
+    service.get_overdue_notes(user.0, &query).await
+}
+
+// Mounted inside the /notes scope ahead of /{note_id}, which would take these paths otherwise
+pub fn configure_reminders_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_upcoming_notes)
+        .service(get_overdue_notes);
+}
\ No newline at end of file
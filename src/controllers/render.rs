@@ -0,0 +1,32 @@
+use actix_web::{get, post, web, HttpResponse};
+use actix_web::middleware::from_fn;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, RenderRequest};
+use crate::services::NoteService;
+use crate::utils::highlight_stylesheet;
+
+#[post("")]
+async fn render(
+    _user: AuthenticatedUser,
+    payload: web::Json<RenderRequest>,
+    service: web::Data<NoteService>
+) -> HttpResponse {
+    service.render_markdown(&payload.markdown)
+}
+
+// Colors for the hl-* classes on highlighted code blocks
+#[get("/styles.css")]
+async fn render_styles() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/css; charset=utf-8")
+        .body(highlight_stylesheet())
+}
+
+pub fn configure_render_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/render")
+            .wrap(from_fn(auth_middleware))
+            .service(render)
+            .service(render_styles)
+    );
+}
\ No newline at end of file
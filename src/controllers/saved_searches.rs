@@ -0,0 +1,79 @@
+use actix_web::{get, post, put, delete, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use uuid::Uuid;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, CreateSavedSearchDto, SavedSearchRunParams, UpdateSavedSearch};
+use crate::services::{NoteService, SavedSearchService};
+
+#[get("")]
+async fn get_saved_searches(
+    user: AuthenticatedUser,
+    service: web::Data<SavedSearchService>
+) -> Result<HttpResponse, Error> {
+    service.get_saved_searches(user.0).await
+}
+
+#[get("/{saved_search_id}")]
+async fn get_saved_search(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<SavedSearchService>
+) -> Result<HttpResponse, Error> {
+    let saved_search_id = path.into_inner();
+    service.get_saved_search_by_id(user.0, saved_search_id).await
+}
+
+#[get("/{saved_search_id}/notes")]
+async fn get_saved_search_notes(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    query: web::Query<SavedSearchRunParams>,
+    service: web::Data<SavedSearchService>,
+    note_service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let saved_search_id = path.into_inner();
+    service.get_saved_search_notes(user.0, saved_search_id, query.into_inner(), &note_service).await
+}
+
+#[post("")]
+async fn create_saved_search(
+    user: AuthenticatedUser,
+    payload: web::Json<CreateSavedSearchDto>,
+    service: web::Data<SavedSearchService>
+) -> Result<HttpResponse, Error> {
+    service.create_saved_search(user.0, payload.into_inner()).await
+}
+
+#[put("/{saved_search_id}")]
+async fn update_saved_search(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    payload: web::Json<UpdateSavedSearch>,
+    service: web::Data<SavedSearchService>
+) -> Result<HttpResponse, Error> {
+    let saved_search_id = path.into_inner();
+    service.update_saved_search(user.0, saved_search_id, payload.into_inner()).await
+}
+
+#[delete("/{saved_search_id}")]
+async fn delete_saved_search(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<SavedSearchService>
+) -> Result<HttpResponse, Error> {
+    let saved_search_id = path.into_inner();
+    service.delete_saved_search(user.0, saved_search_id).await
+}
+
+pub fn configure_saved_searches_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/saved-searches")
+            .wrap(from_fn(auth_middleware))
+            .service(get_saved_searches)
+            .service(get_saved_search)
+            .service(get_saved_search_notes)
+            .service(create_saved_search)
+            .service(update_saved_search)
+            .service(delete_saved_search)
+    );
+}
\ No newline at end of file
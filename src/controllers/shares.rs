@@ -0,0 +1,50 @@
+use actix_web::{get, post, delete, web, HttpResponse, Error};
+use uuid::Uuid;
+use crate::models::{AuthenticatedUser, CreateShareDto, SharePath};
+use crate::services::ShareService;
+
+#[get("/shared")]
+async fn get_shared_notes(
+    user: AuthenticatedUser,
+    service: web::Data<ShareService>
+) -> Result<HttpResponse, Error> {
+    service.get_shared_notes(user.0).await
+}
+
+#[post("/{note_id}/shares")]
+async fn share_note(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    payload: web::Json<CreateShareDto>,
+    service: web::Data<ShareService>
+) -> Result<HttpResponse, Error> {
+    let note_id = path.into_inner();
+    service.share_note(user.0, note_id, payload.into_inner()).await
+}
+
+#[get("/{note_id}/shares")]
+async fn get_note_shares(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<ShareService>
+) -> Result<HttpResponse, Error> {
+    let note_id = path.into_inner();
+    service.get_note_shares(user.0, note_id).await
+}
+
+#[delete("/{note_id}/shares/{user_id}")]
+async fn delete_share(
+    user: AuthenticatedUser,
+    path: web::Path<SharePath>,
+    service: web::Data<ShareService>
+) -> Result<HttpResponse, Error> {
+    service.delete_share(user.0, path.note_id, path.user_id).await
+}
+
+// Mounted inside the /notes scope ahead of /{note_id}, which would take /shared otherwise
+pub fn configure_shares_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_shared_notes)
+        .service(share_note)
+        .service(get_note_shares)
+        .service(delete_share);
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+use actix_web::{get, post, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, SyncParams, SyncPush};
+use crate::services::SyncService;
+
+#[get("")]
+async fn get_changes(
+    user: AuthenticatedUser,
+    query: web::Query<SyncParams>,
+    service: web::Data<SyncService>
+) -> Result<HttpResponse, Error> {
+    service.get_changes(user.0, &query).await
+}
+
+#[post("")]
+async fn push_changes(
+    user: AuthenticatedUser,
+    payload: web::Json<SyncPush>,
+    service: web::Data<SyncService>
+) -> Result<HttpResponse, Error> {
+    service.push_changes(user.0, payload.into_inner()).await
+}
+
+pub fn configure_sync_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/sync")
+            .wrap(from_fn(auth_middleware))
+            .service(get_changes)
+            .service(push_changes)
+    );
+}
\ No newline at end of file
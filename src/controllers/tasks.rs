@@ -0,0 +1,37 @@
+use actix_web::{get, patch, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, TaskParams, TaskPath, UpdateTaskDto};
+use crate::services::TaskService;
+
+#[get("")]
+async fn get_tasks(
+    user: AuthenticatedUser,
+    query: web::Query<TaskParams>,
+    service: web::Data<TaskService>
+) -> Result<HttpResponse, Error> {
+    service.get_tasks(user.0, &query).await
+}
+
+#[patch("/{note_id}/tasks/{task_index}")]
+async fn update_task(
+    user: AuthenticatedUser,
+    path: web::Path<TaskPath>,
+    payload: web::Json<UpdateTaskDto>,
+    service: web::Data<TaskService>
+) -> Result<HttpResponse, Error> {
+    service.update_task(user.0, path.note_id, path.task_index, payload.into_inner()).await
+}
+
+pub fn configure_tasks_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/tasks")
+            .wrap(from_fn(auth_middleware))
+            .service(get_tasks)
+    );
+}
+
+// Mounted inside the /notes scope, which already applies the auth middleware
+pub fn configure_note_tasks_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(update_task);
+}
\ No newline at end of file
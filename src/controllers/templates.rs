@@ -0,0 +1,83 @@
+use actix_web::{get, post, put, delete, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use uuid::Uuid;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, CreateNoteTemplateDto, InstantiateTemplateDto, UpdateNoteTemplate};
+use crate::services::{NoteService, NoteTemplateService};
+
+#[get("")]
+async fn get_templates(
+    user: AuthenticatedUser,
+    service: web::Data<NoteTemplateService>
+) -> Result<HttpResponse, Error> {
+    service.get_templates(user.0).await
+}
+
+#[get("/{template_id}")]
+async fn get_template(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteTemplateService>
+) -> Result<HttpResponse, Error> {
+    let template_id = path.into_inner();
+    service.get_template_by_id(user.0, template_id).await
+}
+
+#[post("")]
+async fn create_template(
+    user: AuthenticatedUser,
+    payload: web::Json<CreateNoteTemplateDto>,
+    service: web::Data<NoteTemplateService>
+) -> Result<HttpResponse, Error> {
+    service.create_template(user.0, payload.into_inner()).await
+}
+
+#[put("/{template_id}")]
+async fn update_template(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    payload: web::Json<UpdateNoteTemplate>,
+    service: web::Data<NoteTemplateService>
+) -> Result<HttpResponse, Error> {
+    let template_id = path.into_inner();
+    service.update_template(user.0, template_id, payload.into_inner()).await
+}
+
+#[delete("/{template_id}")]
+async fn delete_template(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<NoteTemplateService>
+) -> Result<HttpResponse, Error> {
+    let template_id = path.into_inner();
+    service.delete_template(user.0, template_id).await
+}
+
+#[post("/from-template/{template_id}")]
+async fn create_note_from_template(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    payload: web::Json<InstantiateTemplateDto>,
+    service: web::Data<NoteTemplateService>,
+    note_service: web::Data<NoteService>
+) -> Result<HttpResponse, Error> {
+    let template_id = path.into_inner();
+    service.create_note_from_template(user.0, template_id, payload.into_inner(), &note_service).await
+}
+
+pub fn configure_templates_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/templates")
+            .wrap(from_fn(auth_middleware))
+            .service(get_templates)
+            .service(get_template)
+            .service(create_template)
+            .service(update_template)
+            .service(delete_template)
+    );
+}
+
+// Mounted inside the /notes scope, which already applies the auth middleware
+pub fn configure_template_notes_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_note_from_template);
+}
\ No newline at end of file
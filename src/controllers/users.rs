@@ -2,8 +2,8 @@ use actix_session::Session;
 use actix_web::{post, web, HttpResponse, Error, get};
 use actix_web::middleware::from_fn;
 use crate::middleware::auth_middleware;
-use crate::models::{LoginRequest, RegistrationRequest};
-use crate::services::UserService;
+use crate::models::{AuthenticatedUser, LoginRequest, RegistrationRequest};
+use crate::services::{QuotaService, UserService};
 
 
 #[post("/login")]
@@ -39,6 +39,14 @@ pub async fn me(
     service.get_current_user(session).await
 }
 
+#[get("/me/usage")]
+pub async fn me_usage(
+    user: AuthenticatedUser,
+    service: web::Data<QuotaService>
+) -> Result<HttpResponse, Error> {
+    service.get_usage(user.0).await
+}
+
 pub fn configure_auth_controller(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
@@ -51,6 +59,7 @@ pub fn configure_auth_controller(cfg: &mut web::ServiceConfig) {
                     .wrap(from_fn(auth_middleware))
                     .service(logout)
                     .service(me)
+                    .service(me_usage)
             )
     );
 }
\ No newline at end of file
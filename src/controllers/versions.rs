@@ -0,0 +1,26 @@
+use actix_web::web;
+use crate::controllers::*;
+
+// Every route of the v1 API, mounted under <api_prefix>/v1
+pub fn configure_api_v1(cfg: &mut web::ServiceConfig) {
+    cfg.configure(configure_auth_controller)
+        .configure(configure_notes_controller)
+        .configure(configure_notebooks_controller)
+        .configure(configure_saved_searches_controller)
+        .configure(configure_render_controller)
+        .configure(configure_graph_controller)
+        .configure(configure_imports_controller)
+        .configure(configure_exports_controller)
+        .configure(configure_sync_controller)
+        .configure(configure_events_controller)
+        .configure(configure_webhooks_controller)
+        .configure(configure_calendar_controller)
+        .configure(configure_tasks_controller)
+        .configure(configure_templates_controller);
+}
+
+// v2 starts out the same as v1. Breaking changes are made here, by swapping in
+// v2 versions of the controllers, while v1 keeps serving existing clients
+pub fn configure_api_v2(cfg: &mut web::ServiceConfig) {
+    configure_api_v1(cfg);
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use actix_web::{get, post, put, delete, web, HttpResponse, Error};
+use actix_web::middleware::from_fn;
+use uuid::Uuid;
+use crate::middleware::auth_middleware;
+use crate::models::{AuthenticatedUser, CreateWebhookDto, DeliveryParams, UpdateWebhook};
+use crate::services::WebhookService;
+
+#[get("")]
+async fn get_webhooks(
+    user: AuthenticatedUser,
+    service: web::Data<WebhookService>
+) -> Result<HttpResponse, Error> {
+    service.get_webhooks(user.0).await
+}
+
+#[get("/{webhook_id}")]
+async fn get_webhook(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<WebhookService>
+) -> Result<HttpResponse, Error> {
+    let webhook_id = path.into_inner();
+    service.get_webhook_by_id(user.0, webhook_id).await
+}
+
+#[get("/{webhook_id}/deliveries")]
+async fn get_deliveries(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    query: web::Query<DeliveryParams>,
+    service: web::Data<WebhookService>
+) -> Result<HttpResponse, Error> {
+    let webhook_id = path.into_inner();
+    service.get_deliveries(user.0, webhook_id, query.into_inner()).await
+}
+
+#[post("")]
+async fn create_webhook(
+    user: AuthenticatedUser,
+    payload: web::Json<CreateWebhookDto>,
+    service: web::Data<WebhookService>
+) -> Result<HttpResponse, Error> {
+    service.create_webhook(user.0, payload.into_inner()).await
+}
+
+#[post("/{webhook_id}/ping")]
+async fn ping_webhook(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<WebhookService>
+) -> Result<HttpResponse, Error> {
+    let webhook_id = path.into_inner();
+    service.ping_webhook(user.0, webhook_id).await
+}
+
+#[put("/{webhook_id}")]
+async fn update_webhook(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    payload: web::Json<UpdateWebhook>,
+    service: web::Data<WebhookService>
+) -> Result<HttpResponse, Error> {
+    let webhook_id = path.into_inner();
+    service.update_webhook(user.0, webhook_id, payload.into_inner()).await
+}
+
+#[delete("/{webhook_id}")]
+async fn delete_webhook(
+    user: AuthenticatedUser,
+    path: web::Path<Uuid>,
+    service: web::Data<WebhookService>
+) -> Result<HttpResponse, Error> {
+    let webhook_id = path.into_inner();
+    service.delete_webhook(user.0, webhook_id).await
+}
+
+pub fn configure_webhooks_controller(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/webhooks")
+            .wrap(from_fn(auth_middleware))
+            .service(get_webhooks)
+            .service(get_webhook)
+            .service(get_deliveries)
+            .service(create_webhook)
+            .service(ping_webhook)
+            .service(update_webhook)
+            .service(delete_webhook)
+    );
+}
\ No newline at end of file
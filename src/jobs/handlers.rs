@@ -0,0 +1,133 @@
+use std::sync::Arc;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::config::{create_blob_store, Settings};
+use crate::jobs::{Job, JobHandler, JobQueue, JobRegistry};
+use crate::models::ImportFormat;
+use crate::services::{AttachmentService, ImportService, QuotaService, ReminderService, SyncService};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PurgeOrphanedBlobs {}
+
+impl Job for PurgeOrphanedBlobs {
+    const KIND: &'static str = "attachments.purge_orphaned_blobs";
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PurgeTombstones {}
+
+impl Job for PurgeTombstones {
+    const KIND: &'static str = "sync.purge_tombstones";
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PurgeFinishedJobs {
+    pub retention_days: u32,
+}
+
+impl Job for PurgeFinishedJobs {
+    const KIND: &'static str = "jobs.purge_finished";
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SendDueReminders {}
+
+impl Job for SendDueReminders {
+    const KIND: &'static str = "reminders.send_due";
+}
+
+// The upload waits in the blob store, see ImportService::upload_key
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RunImport {
+    pub import_id: Uuid,
+    pub user_id: Uuid,
+    pub format: ImportFormat,
+}
+
+impl Job for RunImport {
+    const KIND: &'static str = "imports.run";
+    const MAX_ATTEMPTS: i32 = 3;
+}
+
+#[async_trait]
+impl JobHandler<PurgeOrphanedBlobs> for AttachmentService {
+    async fn handle(&self, _job: PurgeOrphanedBlobs) -> Result<()> {
+        let purged = self.purge_orphaned_blobs().await?;
+        if purged > 0 {
+            println!("🧹 Purged {} orphaned attachment blobs", purged);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobHandler<PurgeTombstones> for SyncService {
+    async fn handle(&self, _job: PurgeTombstones) -> Result<()> {
+        let purged = self.purge_tombstones().await?;
+        if purged > 0 {
+            println!("🧹 Purged {} expired sync tombstones", purged);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobHandler<PurgeFinishedJobs> for JobQueue {
+    async fn handle(&self, job: PurgeFinishedJobs) -> Result<()> {
+        let purged = self.purge_finished_jobs(job.retention_days).await?;
+        if purged > 0 {
+            println!("🧹 Purged {} finished jobs", purged);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobHandler<SendDueReminders> for ReminderService {
+    async fn handle(&self, _job: SendDueReminders) -> Result<()> {
+        let sent = self.send_due_reminders().await?;
+        if sent > 0 {
+            println!("⏰ Sent {} note reminders", sent);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobHandler<RunImport> for ImportService {
+    async fn handle(&self, job: RunImport) -> Result<()> {
+        self.run_queued_import(job.import_id, job.user_id, job.format).await
+    }
+
+    async fn dead(&self, job: RunImport, _error: &str) -> Result<()> {
+        self.fail_queued_import(job.import_id, job.user_id).await
+    }
+}
+
+// Every job the workers know how to run and the cron entries that enqueue them
+pub fn create_job_registry(pool: &PgPool, settings: &Settings) -> Result<JobRegistry> {
+    let blob_store = create_blob_store(&settings.storage)?;
+    let attachments = AttachmentService::new(
+        pool.clone(),
+        blob_store.clone(),
+        settings.storage.max_file_size,
+        settings.storage.user_quota
+    );
+    let quotas = Arc::new(QuotaService::new(pool.clone(), settings.quotas.clone(), settings.storage.user_quota));
+    let sync = SyncService::new(pool.clone(), settings.sync.tombstone_retention_days, quotas.clone());
+    let imports = ImportService::new(pool.clone(), settings.storage.max_import_size, blob_store, quotas);
+
+    JobRegistry::default()
+        .register::<PurgeOrphanedBlobs, _>(Arc::new(attachments))
+        .register::<PurgeTombstones, _>(Arc::new(sync))
+        .register::<PurgeFinishedJobs, _>(Arc::new(JobQueue::new(pool.clone())))
+        .register::<RunImport, _>(Arc::new(imports))
+        .register::<SendDueReminders, _>(Arc::new(ReminderService::new(pool.clone())))
+        .schedule("purge_orphaned_blobs", "0 0 * * * *", PurgeOrphanedBlobs {})?
+        .schedule("purge_tombstones", "0 15 * * * *", PurgeTombstones {})?
+        .schedule("send_due_reminders", "0 * * * * *", SendDueReminders {})?
+        .schedule("purge_finished_jobs", "0 30 3 * * *", PurgeFinishedJobs { retention_days: settings.jobs.retention_days })
+}
\ No newline at end of file
@@ -0,0 +1,193 @@
+pub mod handlers;
+pub mod worker;
+
+pub use handlers::*;
+pub use worker::*;
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use sqlx::types::Json;
+use crate::models::{JobSchedule, NewJob, QueuedJob};
+use crate::repositories::JobRepository;
+
+// A job's payload, stored as JSON and dispatched to its handler by KIND
+pub trait Job: Serialize + DeserializeOwned + Send + 'static {
+    const KIND: &'static str;
+    // tries before the job is dead-lettered
+    const MAX_ATTEMPTS: i32 = 5;
+}
+
+#[async_trait]
+pub trait JobHandler<J: Job>: Send + Sync + 'static {
+    // Errors are retried with backoff until the job runs out of attempts
+    async fn handle(&self, job: J) -> Result<()>;
+
+    // Called once after the last attempt failed
+    async fn dead(&self, _job: J, _error: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+// A handler for one job type, taking the payload as stored
+#[async_trait]
+trait ErasedHandler: Send + Sync {
+    async fn handle(&self, payload: Value) -> Result<()>;
+    async fn dead(&self, payload: Value, error: &str) -> Result<()>;
+}
+
+struct TypedHandler<J, H> {
+    handler: Arc<H>,
+    job: PhantomData<fn() -> J>,
+}
+
+#[async_trait]
+impl<J: Job, H: JobHandler<J>> ErasedHandler for TypedHandler<J, H> {
+    async fn handle(&self, payload: Value) -> Result<()> {
+        let job = serde_json::from_value(payload).map_err(|e| anyhow!("Invalid {} payload: {}", J::KIND, e))?;
+        self.handler.handle(job).await
+    }
+
+    async fn dead(&self, payload: Value, error: &str) -> Result<()> {
+        let job = serde_json::from_value(payload).map_err(|e| anyhow!("Invalid {} payload: {}", J::KIND, e))?;
+        self.handler.dead(job, error).await
+    }
+}
+
+// Which handler runs each kind of job and which jobs run on a schedule
+#[derive(Default)]
+pub struct JobRegistry {
+    handlers: HashMap<&'static str, Arc<dyn ErasedHandler>>,
+    schedules: Vec<JobSchedule>,
+}
+
+// The next time a cron expression fires after `after`. Expressions have a seconds field,
+// "0 0 * * * *" is every hour on the hour
+pub fn next_cron_run(cron: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let schedule = cron::Schedule::from_str(cron).map_err(|e| anyhow!("Invalid cron expression '{}': {}", cron, e))?;
+    schedule
+        .after(&after)
+        .next()
+        .ok_or_else(|| anyhow!("Cron expression '{}' never fires again", cron))
+}
+
+impl JobRegistry {
+    pub fn register<J: Job, H: JobHandler<J>>(mut self, handler: Arc<H>) -> Self {
+        self.handlers.insert(J::KIND, Arc::new(TypedHandler { handler, job: PhantomData }));
+        self
+    }
+
+    pub fn schedule<J: Job>(mut self, name: &str, cron: &str, job: J) -> Result<Self> {
+        if !self.handlers.contains_key(J::KIND) {
+            return Err(anyhow!("Schedule {} needs a handler for {}", name, J::KIND));
+        }
+
+        self.schedules.push(JobSchedule {
+            name: name.to_string(),
+            cron: cron.to_string(),
+            kind: J::KIND.to_string(),
+            payload: Json(serde_json::to_value(job)?),
+            max_attempts: J::MAX_ATTEMPTS,
+            next_run_at: next_cron_run(cron, Utc::now())?,
+            last_run_at: None,
+        });
+        Ok(self)
+    }
+
+    pub fn kinds(&self) -> Vec<String> {
+        self.handlers.keys().map(|kind| kind.to_string()).collect()
+    }
+
+    pub fn schedules(&self) -> &[JobSchedule] {
+        &self.schedules
+    }
+
+    fn handler(&self, kind: &str) -> Option<Arc<dyn ErasedHandler>> {
+        self.handlers.get(kind).cloned()
+    }
+}
+
+// Where request handlers and services put work for the workers
+pub struct JobQueue {
+    repo: JobRepository,
+}
+
+impl JobQueue {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: JobRepository::new(pool),
+        }
+    }
+
+    pub async fn enqueue<J: Job>(&self, job: &J) -> Result<QueuedJob> {
+        self.enqueue_at(job, Utc::now()).await
+    }
+
+    pub async fn enqueue_at<J: Job>(&self, job: &J, run_at: DateTime<Utc>) -> Result<QueuedJob> {
+        self.repo
+            .enqueue(NewJob {
+                kind: J::KIND.to_string(),
+                payload: serde_json::to_value(job)?,
+                max_attempts: J::MAX_ATTEMPTS,
+                run_at,
+            })
+            .await
+    }
+
+    pub async fn purge_finished_jobs(&self, retention_days: u32) -> Result<u64> {
+        self.repo.purge_finished_jobs(retention_days).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize)]
+    struct Noop {}
+
+    impl Job for Noop {
+        const KIND: &'static str = "noop";
+    }
+
+    fn at(hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 18, hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn next_run_is_strictly_after() {
+        assert_eq!(next_cron_run("0 0 * * * *", at(14, 20, 5)).unwrap(), at(15, 0, 0));
+        assert_eq!(next_cron_run("0 0 * * * *", at(15, 0, 0)).unwrap(), at(16, 0, 0));
+        assert_eq!(next_cron_run("0 * * * * *", at(15, 0, 59)).unwrap(), at(15, 1, 0));
+    }
+
+    #[test]
+    fn daily_schedules_roll_over_to_the_next_day() {
+        let next = next_cron_run("0 30 3 * * *", at(4, 0, 0)).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 10, 19, 3, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_and_exhausted_expressions() {
+        let invalid = next_cron_run("every hour", at(0, 0, 0)).unwrap_err();
+        assert!(invalid.to_string().contains("Invalid cron expression"));
+
+        let exhausted = next_cron_run("0 0 0 1 1 * 2020", at(0, 0, 0)).unwrap_err();
+        assert!(exhausted.to_string().contains("never fires again"));
+    }
+
+    #[test]
+    fn schedules_need_a_handler() {
+        assert!(JobRegistry::default().schedule("noop", "0 0 * * * *", Noop {}).is_err());
+    }
+}
\ No newline at end of file
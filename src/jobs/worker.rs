@@ -0,0 +1,168 @@
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use sqlx::PgPool;
+use crate::config::JobSettings;
+use crate::jobs::{next_cron_run, JobRegistry};
+use crate::models::{NewJob, QueuedJob};
+use crate::repositories::JobRepository;
+
+const MAX_RETRY_DELAY_SECS: u64 = 3600;
+// Extra time on top of the job timeout before a running job counts as abandoned
+const LEASE_MARGIN_SECS: u64 = 60;
+
+// Runs queued jobs and enqueues scheduled ones. Any number of pools can share the queue,
+// in the API server and in `worker` processes alike
+pub struct WorkerPool {
+    repo: JobRepository,
+    registry: JobRegistry,
+    settings: JobSettings,
+    worker_id: String,
+}
+
+impl WorkerPool {
+    pub fn new(pool: PgPool, registry: JobRegistry, settings: JobSettings) -> Self {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+
+        Self {
+            repo: JobRepository::new(pool),
+            registry,
+            settings,
+            worker_id: format!("{}:{}", host, std::process::id()),
+        }
+    }
+
+    // Runs until the process exits
+    pub async fn run(self: Arc<Self>) -> Result<()> {
+        self.sync_schedules().await?;
+
+        for worker in 0..self.settings.concurrency {
+            actix_web::rt::spawn(self.clone().work(worker));
+        }
+
+        println!("👷 Started {} job workers on {}", self.settings.concurrency, self.worker_id);
+
+        let mut interval = tokio::time::interval(Duration::from_secs(self.settings.poll_interval));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.enqueue_scheduled_jobs().await {
+                eprintln!("Failed to enqueue scheduled jobs: {}", e);
+            }
+            if let Err(e) = self.release_expired_jobs().await {
+                eprintln!("Failed to release abandoned jobs: {}", e);
+            }
+        }
+    }
+
+    async fn sync_schedules(&self) -> Result<()> {
+        for schedule in self.registry.schedules() {
+            self.repo.upsert_schedule(schedule).await?;
+        }
+
+        // a schedule removed from the code stops running
+        let names: Vec<String> = self.registry.schedules().iter().map(|schedule| schedule.name.clone()).collect();
+        self.repo.delete_schedules_except(&names).await?;
+
+        Ok(())
+    }
+
+    async fn work(self: Arc<Self>, worker: u32) {
+        let worker_id = format!("{}#{}", self.worker_id, worker);
+        let kinds = self.registry.kinds();
+
+        loop {
+            match self.run_next_job(&worker_id, &kinds).await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => eprintln!("Job worker {} failed: {}", worker_id, e),
+            }
+            tokio::time::sleep(Duration::from_secs(self.settings.poll_interval)).await;
+        }
+    }
+
+    // false when there was nothing to do
+    async fn run_next_job(&self, worker_id: &str, kinds: &[String]) -> Result<bool> {
+        let lease = (self.settings.timeout + LEASE_MARGIN_SECS) as f64;
+        let Some(job) = self.repo.claim_next(worker_id, kinds, lease).await? else {
+            return Ok(false);
+        };
+
+        let Some(handler) = self.registry.handler(&job.kind) else {
+            return Err(anyhow!("Claimed job {} of unknown kind {}", job.id, job.kind));
+        };
+
+        // in its own task so a panicking handler fails the job instead of the worker
+        let payload = job.payload.0.clone();
+        let timeout = Duration::from_secs(self.settings.timeout);
+        let run = actix_web::rt::spawn(async move {
+            tokio::time::timeout(timeout, handler.handle(payload)).await
+        });
+
+        let result = match run.await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(anyhow!("Timed out after {} seconds", timeout.as_secs())),
+            Err(e) => Err(anyhow!("Handler panicked: {}", e)),
+        };
+
+        match result {
+            Ok(()) => self.repo.complete(job.id).await?,
+            Err(e) if job.attempts >= job.max_attempts => self.bury(&job, &e.to_string()).await?,
+            Err(e) => {
+                let delay = self.settings.retry_base_delay
+                    .saturating_mul(2u64.saturating_pow(job.attempts as u32 - 1))
+                    .min(MAX_RETRY_DELAY_SECS);
+                let run_at = Utc::now() + chrono::Duration::seconds(delay as i64);
+                eprintln!("Job {} ({}) failed, retrying in {}s: {}", job.id, job.kind, delay, e);
+                self.repo.retry(job.id, &e.to_string(), run_at).await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn bury(&self, job: &QueuedJob, error: &str) -> Result<()> {
+        eprintln!("Job {} ({}) failed for good after {} attempts: {}", job.id, job.kind, job.attempts, error);
+        self.repo.bury(job.id, error).await?;
+        self.run_dead_handler(job, error).await;
+        Ok(())
+    }
+
+    async fn run_dead_handler(&self, job: &QueuedJob, error: &str) {
+        let Some(handler) = self.registry.handler(&job.kind) else {
+            return;
+        };
+        if let Err(e) = handler.dead(job.payload.0.clone(), error).await {
+            eprintln!("Cleanup after dead job {} ({}) failed: {}", job.id, job.kind, e);
+        }
+    }
+
+    async fn enqueue_scheduled_jobs(&self) -> Result<()> {
+        let mut tx = self.repo.begin().await?;
+
+        for schedule in self.repo.get_due_schedules_in(&mut tx).await? {
+            // runs missed while no worker was up collapse into this one
+            let next_run_at = next_cron_run(&schedule.cron, Utc::now())?;
+            let new_job = NewJob {
+                kind: schedule.kind.clone(),
+                payload: schedule.payload.0.clone(),
+                max_attempts: schedule.max_attempts,
+                run_at: Utc::now(),
+            };
+            self.repo.enqueue_in(&mut tx, new_job).await?;
+            self.repo.reschedule_in(&mut tx, &schedule.name, next_run_at).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn release_expired_jobs(&self) -> Result<()> {
+        for job in self.repo.release_expired().await? {
+            let error = job.last_error.clone().unwrap_or_default();
+            eprintln!("Job {} ({}) failed for good after {} attempts: {}", job.id, job.kind, job.attempts, error);
+            self.run_dead_handler(&job, &error).await;
+        }
+        Ok(())
+    }
+}
\ No newline at end of file
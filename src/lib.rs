@@ -0,0 +1,9 @@
+pub mod config;
+pub mod controllers;
+pub mod jobs;
+pub mod middleware;
+pub mod models;
+pub mod repositories;
+pub mod services;
+pub mod storage;
+pub mod utils;
\ No newline at end of file
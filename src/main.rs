@@ -1,22 +1,17 @@
-mod config;
-mod models;
-mod repositories;
-mod controllers;
-mod middleware;
-mod services;
-mod utils;
-
 use env_logger::{
     Env,
     init_from_env
 };
-use actix_session::SessionMiddleware;
 use actix_web::{web, App, HttpServer, HttpResponse, Result};
 use actix_web::cookie::Key;
-use actix_web::middleware::Logger;
-use config::{create_pool, create_redis_session_store, run_migrations, Settings, create_cors_config};
-use crate::controllers::{configure_auth_controller, configure_notes_controller};
-use crate::services::{UserService, NoteService};
+use actix_web::middleware::from_fn;
+use std::sync::Arc;
+use std::time::Duration;
+use rust_notes_api::config::{create_pool, create_redis_session_store, run_migrations, Settings, create_cors_config, create_logger, create_blob_store, create_json_config, create_payload_config, create_session_middleware};
+use rust_notes_api::middleware::{deprecation_middleware, timeout_middleware};
+use rust_notes_api::jobs::{create_job_registry, WorkerPool};
+use rust_notes_api::controllers::{configure_api_v1, configure_api_v2};
+use rust_notes_api::services::{UserService, NoteService, SavedSearchService, AttachmentService, GraphService, ImportService, ExportService, SyncService, EventService, CollabService, WebhookService, ReminderService, TaskService, NoteTemplateService, QuotaService, NotebookService, ShareService};
 
 // Health check endpoint
 async fn health() -> Result<HttpResponse> {
@@ -37,7 +32,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create database pool and services
     let db_pool = create_pool(&settings.database).await?;
     let user_service = web::Data::new(UserService::new(db_pool.clone()));
-    let note_service = web::Data::new(NoteService::new(db_pool.clone()));
+    let quota_service = web::Data::new(QuotaService::new(
+        db_pool.clone(),
+        settings.quotas.clone(),
+        settings.storage.user_quota
+    ));
+    let note_service = web::Data::new(NoteService::new(db_pool.clone(), quota_service.clone().into_inner()));
+    let notebook_service = web::Data::new(NotebookService::new(db_pool.clone()));
+    let share_service = web::Data::new(ShareService::new(db_pool.clone()));
+    let saved_search_service = web::Data::new(SavedSearchService::new(db_pool.clone()));
+    let graph_service = web::Data::new(GraphService::new(db_pool.clone()));
+    let blob_store = create_blob_store(&settings.storage)?;
+    let import_service = web::Data::new(ImportService::new(
+        db_pool.clone(),
+        settings.storage.max_import_size,
+        blob_store.clone(),
+        quota_service.clone().into_inner()
+    ));
+    let export_service = web::Data::new(ExportService::new(db_pool.clone(), note_service.clone().into_inner()));
+    let sync_service = web::Data::new(SyncService::new(
+        db_pool.clone(),
+        settings.sync.tombstone_retention_days,
+        quota_service.clone().into_inner()
+    ));
+    let event_service = web::Data::new(EventService::new());
+    let collab_service = web::Data::new(CollabService::new(db_pool.clone(), quota_service.clone().into_inner()));
+    let webhook_service = web::Data::new(WebhookService::new(db_pool.clone(), settings.webhooks.clone()));
+    let reminder_service = web::Data::new(ReminderService::new(db_pool.clone()));
+    let task_service = web::Data::new(TaskService::new(db_pool.clone()));
+    let template_service = web::Data::new(NoteTemplateService::new(db_pool.clone()));
+    let attachment_service = web::Data::new(AttachmentService::new(
+        db_pool.clone(),
+        blob_store,
+        settings.storage.max_file_size,
+        settings.storage.user_quota
+    ));
 
     // Run migrations
     run_migrations(&db_pool).await?;
@@ -49,33 +78,99 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let redis_store = create_redis_session_store(&settings.redis).await?;
     let secret_key = Key::from(settings.secret_key.as_bytes());
 
+    // Push note changes from any instance to the clients connected to this one
+    let listener_service = event_service.clone();
+    let listener_pool = db_pool.clone();
+    actix_web::rt::spawn(async move {
+        listener_service.listen(listener_pool).await;
+    });
+
+    // Collab rooms follow shares being revoked
+    let share_events = event_service.subscribe();
+    let revoking_service = collab_service.clone();
+    actix_web::rt::spawn(async move {
+        revoking_service.close_revoked_connections(share_events).await;
+    });
+
+    // Background jobs and webhook deliveries, unless separate `worker` processes handle them
+    if settings.jobs.run_in_process {
+        let worker_pool = Arc::new(WorkerPool::new(
+            db_pool.clone(),
+            create_job_registry(&db_pool, &settings)?,
+            settings.jobs.clone()
+        ));
+        actix_web::rt::spawn(async move {
+            if let Err(e) = worker_pool.run().await {
+                eprintln!("Job workers stopped: {}", e);
+            }
+        });
+
+        let dispatch_service = webhook_service.clone();
+        actix_web::rt::spawn(async move {
+            dispatch_service.dispatch().await;
+        });
+    }
+
     // Clone values needed after the move
     let host = settings.api.host.clone();
     let port = settings.api.port;
+    let request_timeout = Duration::from_secs(settings.api.request_timeout);
+    let v1_path = settings.api.version_path(1);
+    let v2_path = settings.api.version_path(2);
+    let v1_deprecation = settings.api.v1_deprecation;
 
-    println!("🚀 Starting server on {}:{}", host, port);
+    println!("🚀 Starting server on {}:{} (API at {} and {})", host, port, v1_path, v2_path);
 
     HttpServer::new(move || {
         // Create session middleware with the pre-created store
-        let session_middleware = SessionMiddleware::new(
+        let session_middleware = create_session_middleware(
             redis_store.clone(),
-            secret_key.clone()
+            secret_key.clone(),
+            &settings.cookie
         );
 
         // Create CORS configuration
         let cors = create_cors_config(&settings);
 
         App::new()
+            .app_data(create_json_config(&settings.api))
+            .app_data(create_payload_config(&settings.api))
             .app_data(user_service.clone())
             .app_data(note_service.clone())
-            .wrap(Logger::default())
+            .app_data(notebook_service.clone())
+            .app_data(share_service.clone())
+            .app_data(saved_search_service.clone())
+            .app_data(graph_service.clone())
+            .app_data(import_service.clone())
+            .app_data(export_service.clone())
+            .app_data(sync_service.clone())
+            .app_data(event_service.clone())
+            .app_data(collab_service.clone())
+            .app_data(webhook_service.clone())
+            .app_data(reminder_service.clone())
+            .app_data(task_service.clone())
+            .app_data(template_service.clone())
+            .app_data(attachment_service.clone())
+            .app_data(quota_service.clone())
+            .wrap(from_fn(move |req, next| timeout_middleware(req, next, request_timeout)))
+            .wrap(create_logger())
             .wrap(session_middleware)
             .wrap(cors) // Apply the CORS middleware
             // Health check endpoint (no authentication required)
             .route("/health", web::get().to(health))
-            .configure(configure_auth_controller)
-            .configure(configure_notes_controller)
+            .service(
+                web::scope(&v1_path)
+                    .wrap(from_fn({
+                        let successor = v2_path.clone();
+                        move |req, next| deprecation_middleware(req, next, v1_deprecation, successor.clone())
+                    }))
+                    .configure(configure_api_v1)
+            )
+            .service(web::scope(&v2_path).configure(configure_api_v2))
     })
+        // slow clients get as long to send their headers as handlers get to run
+        .client_request_timeout(request_timeout)
+        .client_disconnect_timeout(Duration::from_secs(5))
         .bind((host.as_str(), port))?
         .run()
         .await?;
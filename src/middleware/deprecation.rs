@@ -0,0 +1,38 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue, LINK};
+use actix_web::middleware::Next;
+use actix_web::Error;
+use crate::config::VersionDeprecation;
+
+// Marks every response of an old API version as deprecated (RFC 9745), with its
+// sunset date (RFC 8594) and a link to the version replacing it
+pub async fn deprecation_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+    deprecation: Option<VersionDeprecation>,
+    successor: String
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let mut res = next.call(req).await?.map_into_boxed_body();
+
+    let Some(deprecation) = deprecation else {
+        return Ok(res);
+    };
+
+    let headers = res.headers_mut();
+    let deprecated = format!("@{}", deprecation.deprecated_at.timestamp());
+    if let Ok(value) = HeaderValue::from_str(&deprecated) {
+        headers.insert(HeaderName::from_static("deprecation"), value);
+    }
+    if let Some(sunset_at) = deprecation.sunset_at {
+        let sunset = sunset_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        if let Ok(value) = HeaderValue::from_str(&sunset) {
+            headers.insert(HeaderName::from_static("sunset"), value);
+        }
+    }
+    if let Ok(value) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor)) {
+        headers.append(LINK, value);
+    }
+
+    Ok(res)
+}
\ No newline at end of file
@@ -1,2 +1,6 @@
 pub mod auth;
-pub use auth::*;
\ No newline at end of file
+pub mod deprecation;
+pub mod timeout;
+pub use auth::*;
+pub use deprecation::*;
+pub use timeout::*;
\ No newline at end of file
@@ -0,0 +1,64 @@
+use std::time::Duration;
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::InternalError;
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse};
+use serde_json::json;
+
+// Uploads read their body inside the handler and are bounded by their own size limits.
+// Runs before routing, so the routes are matched on the path under any prefix and version
+fn is_upload(method: &Method, path: &str) -> bool {
+    if method != Method::POST {
+        return false;
+    }
+
+    let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    matches!(segments.as_slice(), [.., "import"] | [.., "notes", _, "attachments"])
+}
+
+pub async fn timeout_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+    timeout: Duration
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    if is_upload(req.method(), req.path()) {
+        let res = next.call(req).await?;
+        return Ok(res.map_into_boxed_body());
+    }
+
+    // only the handler is timed, streamed bodies like the event stream keep going afterwards.
+    // The request is gone once the handler was dropped, so the 504 goes out as an error response
+    match actix_web::rt::time::timeout(timeout, next.call(req)).await {
+        Ok(res) => Ok(res?.map_into_boxed_body()),
+        Err(_) => {
+            let response = HttpResponse::GatewayTimeout().json(json!({
+                "message": format!("Request did not complete within {} seconds", timeout.as_secs())
+            }));
+            Err(InternalError::from_response("request timed out", response).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exempts_upload_routes_under_any_prefix() {
+        assert!(is_upload(&Method::POST, "/api/v1/import"));
+        assert!(is_upload(&Method::POST, "/v2/notes/6f2b6a4e-0d7e-4f6a-9c3b-2f1d8e5a7b90/attachments"));
+        assert!(is_upload(&Method::POST, "/api/v2/notes/abc/attachments/"));
+    }
+
+    #[test]
+    fn times_everything_else() {
+        assert!(!is_upload(&Method::GET, "/api/v1/import"));
+        assert!(!is_upload(&Method::GET, "/api/v1/notes/abc/attachments"));
+        assert!(!is_upload(&Method::POST, "/api/v1/notes"));
+        assert!(!is_upload(&Method::POST, "/api/v1/notes/batch"));
+        assert!(!is_upload(&Method::POST, "/api/v1/notes/abc/attachments/def"));
+        assert!(!is_upload(&Method::POST, "/api/v1/import/abc"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NewAttachment {
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttachmentPath {
+    pub note_id: Uuid,
+    pub attachment_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoteAttachments {
+    pub attachments: Vec<Attachment>,
+}
+
+// ===== HELPER METHODS =====
+
+impl NewAttachment {
+    pub fn new(
+        note_id: Uuid,
+        user_id: Uuid,
+        filename: String,
+        content_type: String,
+        size_bytes: i64,
+        sha256: String
+    ) -> Self {
+        Self {
+            note_id,
+            user_id,
+            filename,
+            content_type,
+            size_bytes,
+            sha256,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::models::{deserialize_some, CreateNoteDto, Note, NoteLanguage};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    // the first failing operation rolls back the whole batch
+    #[default]
+    Atomic,
+    // failed operations are rolled back on their own, the rest is committed
+    PerItem,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BatchOperation {
+    Create(CreateNoteDto),
+    Update {
+        id: Uuid,
+        title: Option<String>,
+        content: Option<String>,
+        language: Option<NoteLanguage>,
+        #[serde(default, deserialize_with = "deserialize_some")]
+        due_at: Option<Option<DateTime<Utc>>>,
+        #[serde(default, deserialize_with = "deserialize_some")]
+        remind_at: Option<Option<DateTime<Utc>>>,
+    },
+    Delete {
+        id: Uuid,
+    },
+    // a null notebook takes the note out of its notebook
+    Move {
+        id: Uuid,
+        notebook_id: Option<Uuid>,
+    },
+    Tag {
+        id: Uuid,
+        #[serde(default)]
+        add: Vec<String>,
+        #[serde(default)]
+        remove: Vec<String>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BatchRequest {
+    pub mode: Option<BatchMode>,
+    pub operations: Vec<BatchOperation>,
+}
+
+// Outcome of one operation, `status` is what the single-note endpoint would have answered
+#[derive(Serialize, Debug)]
+pub struct BatchResult {
+    pub index: usize,
+    pub status: u16,
+    pub note: Option<Note>,
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BatchResponse {
+    pub committed: bool,
+    pub results: Vec<BatchResult>,
+}
+
+// ===== HELPER METHODS =====
+
+impl BatchResult {
+    pub fn ok(index: usize, status: u16, note: Option<Note>) -> Self {
+        Self { index, status, note, message: None }
+    }
+
+    pub fn failed(index: usize, status: u16, message: impl Into<String>) -> Self {
+        Self { index, status, note: None, message: Some(message.into()) }
+    }
+
+    pub fn succeeded(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
\ No newline at end of file
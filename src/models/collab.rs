@@ -0,0 +1,61 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PresenceUser {
+    pub user_id: Uuid,
+    pub username: String,
+}
+
+// Sent as a text frame to everyone in a note's room whenever someone joins or leaves
+#[derive(Serialize, Debug)]
+pub struct RoomPresence {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub note_id: Uuid,
+    pub users: Vec<PresenceUser>,
+    // open editors, one user can have several
+    pub connections: usize,
+}
+
+// Sent as a text frame to the room when its edits couldn't be saved
+#[derive(Serialize, Debug)]
+pub struct RoomError {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub note_id: Uuid,
+    pub status: u16,
+    pub message: String,
+}
+
+// ===== HELPER METHODS =====
+
+impl RoomPresence {
+    pub fn new(note_id: Uuid, members: Vec<PresenceUser>) -> Self {
+        let connections = members.len();
+        let mut users: Vec<PresenceUser> = Vec::new();
+        for member in members {
+            if !users.contains(&member) {
+                users.push(member);
+            }
+        }
+
+        Self {
+            kind: "presence",
+            note_id,
+            users,
+            connections,
+        }
+    }
+}
+
+impl RoomError {
+    pub fn new(note_id: Uuid, status: u16, message: impl Into<String>) -> Self {
+        Self {
+            kind: "error",
+            note_id,
+            status,
+            message: message.into(),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum NoteEventKind {
+    #[serde(rename = "note.created")]
+    Created,
+    #[serde(rename = "note.updated")]
+    Updated,
+    #[serde(rename = "note.deleted")]
+    Deleted,
+    // sent by the reminder job when a note's remind_at comes up
+    #[serde(rename = "note.reminder")]
+    Reminder,
+    // someone shared their note with the user or changed what they may do with it
+    #[serde(rename = "note.shared")]
+    Shared,
+    #[serde(rename = "note.unshared")]
+    Unshared,
+    // events were lost on the way, clients should refetch or run a delta sync
+    #[serde(rename = "resync")]
+    Resync,
+}
+
+// Published by the notes and note_shares table triggers on the note_events channel,
+// once for the owner and once for every user the note is shared with
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct NoteEvent {
+    #[serde(rename = "type")]
+    pub kind: NoteEventKind,
+    // who gets the event, None for events every connected client gets
+    pub user_id: Option<Uuid>,
+    pub note_id: Option<Uuid>,
+    pub version: Option<i64>,
+    pub at: DateTime<Utc>,
+}
+
+// ===== HELPER METHODS =====
+
+impl NoteEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteEventKind::Created => "note.created",
+            NoteEventKind::Updated => "note.updated",
+            NoteEventKind::Deleted => "note.deleted",
+            NoteEventKind::Reminder => "note.reminder",
+            NoteEventKind::Shared => "note.shared",
+            NoteEventKind::Unshared => "note.unshared",
+            NoteEventKind::Resync => "resync",
+        }
+    }
+}
+
+impl NoteEvent {
+    pub fn resync() -> Self {
+        Self {
+            kind: NoteEventKind::Resync,
+            user_id: None,
+            note_id: None,
+            version: None,
+            at: Utc::now(),
+        }
+    }
+
+    pub fn is_for(&self, user_id: Uuid) -> bool {
+        self.user_id.is_none_or(|recipient| recipient == user_id)
+    }
+
+    // One Server-Sent Events message, named after the event type
+    pub fn to_sse(&self) -> String {
+        format!(
+            "event: {}\ndata: {}\n\n",
+            self.kind.as_str(),
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+}
\ No newline at end of file
@@ -0,0 +1,214 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct GraphNode {
+    pub id: Uuid,
+    pub title: String,
+    pub notebook_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// A resolved [[link]] from source to target
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct GraphEdge {
+    pub source: Uuid,
+    pub target: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoteGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Json,
+    Dot,
+    Graphml,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GraphParams {
+    pub format: Option<GraphFormat>,
+    // only return notes within `depth` links of this note
+    pub note_id: Option<Uuid>,
+    pub depth: Option<u32>,
+    pub tag: Option<String>,
+    // notebook name, notes in nested notebooks are included
+    pub notebook: Option<String>,
+}
+
+// ===== HELPER METHODS =====
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl NoteGraph {
+    // Drops the edges leading out of the graph once its nodes were filtered
+    pub fn new(nodes: Vec<GraphNode>, edges: Vec<GraphEdge>) -> Self {
+        let ids: HashSet<Uuid> = nodes.iter().map(|node| node.id).collect();
+        let edges = edges
+            .into_iter()
+            .filter(|edge| ids.contains(&edge.source) && ids.contains(&edge.target))
+            .collect();
+        Self { nodes, edges }
+    }
+
+    // Notes reachable from `note_id` in at most `depth` links, following links either way
+    pub fn neighborhood(self, note_id: Uuid, depth: u32) -> Self {
+        let mut adjacent: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for edge in &self.edges {
+            adjacent.entry(edge.source).or_default().push(edge.target);
+            adjacent.entry(edge.target).or_default().push(edge.source);
+        }
+
+        let mut reached = HashSet::from([note_id]);
+        let mut queue = VecDeque::from([(note_id, 0)]);
+        while let Some((current, distance)) = queue.pop_front() {
+            if distance == depth {
+                continue;
+            }
+            for next in adjacent.get(&current).into_iter().flatten() {
+                if reached.insert(*next) {
+                    queue.push_back((*next, distance + 1));
+                }
+            }
+        }
+
+        Self {
+            nodes: self.nodes.into_iter().filter(|node| reached.contains(&node.id)).collect(),
+            edges: self.edges
+                .into_iter()
+                .filter(|edge| reached.contains(&edge.source) && reached.contains(&edge.target))
+                .collect(),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph notes {\n");
+        for node in &self.nodes {
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\", tags=\"{}\"];\n",
+                node.id,
+                escape_dot(&node.title),
+                escape_dot(&node.tags.join(","))
+            ));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!("    \"{}\" -> \"{}\";\n", edge.source, edge.target));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+            "  <key id=\"tags\" for=\"node\" attr.name=\"tags\" attr.type=\"string\"/>\n",
+            "  <key id=\"notebook_id\" for=\"node\" attr.name=\"notebook_id\" attr.type=\"string\"/>\n",
+            "  <key id=\"created_at\" for=\"node\" attr.name=\"created_at\" attr.type=\"string\"/>\n",
+            "  <key id=\"updated_at\" for=\"node\" attr.name=\"updated_at\" attr.type=\"string\"/>\n",
+            "  <graph id=\"notes\" edgedefault=\"directed\">\n",
+        ));
+        for node in &self.nodes {
+            graphml.push_str(&format!(
+                "    <node id=\"{}\">\n      <data key=\"title\">{}</data>\n      <data key=\"tags\">{}</data>\n      <data key=\"notebook_id\">{}</data>\n      <data key=\"created_at\">{}</data>\n      <data key=\"updated_at\">{}</data>\n    </node>\n",
+                node.id,
+                escape_xml(&node.title),
+                escape_xml(&node.tags.join(",")),
+                node.notebook_id.map(|id| id.to_string()).unwrap_or_default(),
+                node.created_at.to_rfc3339(),
+                node.updated_at.to_rfc3339()
+            ));
+        }
+        for edge in &self.edges {
+            graphml.push_str(&format!("    <edge source=\"{}\" target=\"{}\"/>\n", edge.source, edge.target));
+        }
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(title: &str) -> GraphNode {
+        GraphNode {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            notebook_id: None,
+            tags: Vec::new(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn edge(source: &GraphNode, target: &GraphNode) -> GraphEdge {
+        GraphEdge { source: source.id, target: target.id }
+    }
+
+    fn titles(graph: &NoteGraph) -> Vec<&str> {
+        graph.nodes.iter().map(|node| node.title.as_str()).collect()
+    }
+
+    #[test]
+    fn new_drops_edges_to_filtered_out_nodes() {
+        let (a, b, c) = (node("a"), node("b"), node("c"));
+        let edges = vec![edge(&a, &b), edge(&b, &c)];
+        let graph = NoteGraph::new(vec![a.clone(), b.clone()], edges);
+        assert_eq!(graph.edges, vec![edge(&a, &b)]);
+    }
+
+    #[test]
+    fn neighborhood_follows_links_both_ways_up_to_depth() {
+        // a -> b <- c -> d
+        let (a, b, c, d) = (node("a"), node("b"), node("c"), node("d"));
+        let edges = vec![edge(&a, &b), edge(&c, &b), edge(&c, &d)];
+        let nodes = vec![a.clone(), b.clone(), c.clone(), d.clone()];
+
+        let graph = NoteGraph::new(nodes.clone(), edges.clone()).neighborhood(a.id, 0);
+        assert_eq!(titles(&graph), vec!["a"]);
+
+        let graph = NoteGraph::new(nodes.clone(), edges.clone()).neighborhood(a.id, 2);
+        assert_eq!(titles(&graph), vec!["a", "b", "c"]);
+        assert_eq!(graph.edges, vec![edge(&a, &b), edge(&c, &b)]);
+
+        let graph = NoteGraph::new(nodes, edges).neighborhood(a.id, 3);
+        assert_eq!(titles(&graph), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn exports_escape_titles_and_tags() {
+        let mut quoted = node("say \"hi\" <now> & then");
+        quoted.tags = vec!["x".to_string(), "y".to_string()];
+        let graph = NoteGraph::new(vec![quoted], Vec::new());
+
+        assert!(graph.to_dot().contains(r#"[label="say \"hi\" <now> & then", tags="x,y"]"#));
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains("<data key=\"title\">say &quot;hi&quot; &lt;now&gt; &amp; then</data>"));
+        assert!(graphml.contains("<data key=\"tags\">x,y</data>"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ImportFormat {
+    // ZIP of Markdown files with optional YAML front matter
+    Markdown,
+    // ZIP of an Obsidian vault, titles come from file names
+    Obsidian,
+    // Evernote .enex export
+    Enex,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum ImportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+// Why a single file of an import was skipped, or what of it was left out
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ImportFileError {
+    pub file: String,
+    pub message: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct Import {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub format: ImportFormat,
+    pub filename: String,
+    pub status: ImportStatus,
+    pub total_files: i32,
+    pub processed_files: i32,
+    pub imported_notes: i32,
+    pub errors: Json<Vec<ImportFileError>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ImportParams {
+    // detected from the upload when missing
+    pub format: Option<ImportFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserImports {
+    pub imports: Vec<Import>,
+}
+
+// ===== HELPER METHODS =====
+
+impl ImportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Markdown => "markdown",
+            ImportFormat::Obsidian => "obsidian",
+            ImportFormat::Enex => "enex",
+        }
+    }
+}
+
+impl ImportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportStatus::Pending => "pending",
+            ImportStatus::Running => "running",
+            ImportStatus::Completed => "completed",
+            ImportStatus::Failed => "failed",
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::Json;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    // out of attempts, kept for inspection until the retention runs out
+    Dead,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct QueuedJob {
+    pub id: Uuid,
+    pub kind: String,
+    pub payload: Json<Value>,
+    pub status: JobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    pub locked_by: Option<String>,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+// A cron entry, enqueues a job of `kind` every time it comes due
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct JobSchedule {
+    pub name: String,
+    pub cron: String,
+    pub kind: String,
+    pub payload: Json<Value>,
+    pub max_attempts: i32,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub kind: String,
+    pub payload: Value,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+}
+
+// ===== HELPER METHODS =====
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Dead => "dead",
+        }
+    }
+}
\ No newline at end of file
@@ -1,5 +1,39 @@
+pub mod attachments;
+pub mod batch;
+pub mod collab;
+pub mod events;
+pub mod graph;
+pub mod imports;
+pub mod jobs;
+pub mod note_links;
+pub mod notebooks;
 pub mod notes;
+pub mod reminders;
+pub mod saved_searches;
+pub mod shares;
+pub mod sync;
+pub mod tasks;
+pub mod templates;
+pub mod usage;
 pub mod users;
+pub mod webhooks;
 
+pub use attachments::*;
+pub use batch::*;
+pub use collab::*;
+pub use events::*;
+pub use graph::*;
+pub use imports::*;
+pub use jobs::*;
+pub use note_links::*;
+pub use notebooks::*;
 pub use notes::*;
-pub use users::*;
\ No newline at end of file
+pub use reminders::*;
+pub use saved_searches::*;
+pub use shares::*;
+pub use sync::*;
+pub use tasks::*;
+pub use templates::*;
+pub use usage::*;
+pub use users::*;
+pub use webhooks::*;
\ No newline at end of file
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// ===== DATABASE MODELS =====
+
+// An outgoing [[link]], note_id and title are null while it doesn't resolve to a note
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct NoteLink {
+    pub target: String,
+    pub resolved: bool,
+    pub note_id: Option<Uuid>,
+    pub title: Option<String>,
+}
+
+// A note linking to the requested note
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct Backlink {
+    pub note_id: Uuid,
+    pub title: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoteLinks {
+    pub links: Vec<NoteLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoteBacklinks {
+    pub backlinks: Vec<Backlink>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UpdateNoteParams {
+    // rewrite [[Old Title]] links in other notes when the title changes
+    pub rewrite_links: Option<bool>,
+}
\ No newline at end of file
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::models::deserialize_some;
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct Notebook {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    // None for top level notebooks
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewNotebook {
+    pub user_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateNotebookDto {
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateNotebook {
+    pub name: Option<String>,
+    // missing leaves the notebook where it is, null moves it to the top level
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub parent_id: Option<Option<Uuid>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UserNotebooks {
+    pub notebooks: Vec<Notebook>,
+}
+
+// ===== HELPER METHODS =====
+
+// Names from the top level notebook down to each notebook
+pub fn notebook_paths(notebooks: &[Notebook]) -> HashMap<Uuid, Vec<String>> {
+    let by_id: HashMap<Uuid, &Notebook> = notebooks.iter().map(|notebook| (notebook.id, notebook)).collect();
+
+    notebooks
+        .iter()
+        .map(|notebook| {
+            let mut path = vec![notebook.name.clone()];
+            let mut parent_id = notebook.parent_id;
+            // the depth bound only matters if the parents ever loop
+            while let Some(parent) = parent_id.and_then(|id| by_id.get(&id)).filter(|_| path.len() <= notebooks.len()) {
+                path.push(parent.name.clone());
+                parent_id = parent.parent_id;
+            }
+            path.reverse();
+            (notebook.id, path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notebook(name: &str, parent_id: Option<Uuid>) -> Notebook {
+        Notebook {
+            id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            parent_id,
+            name: name.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn paths_run_from_the_top_level_down() {
+        let work = notebook("Work", None);
+        let projects = notebook("Projects", Some(work.id));
+        let archive = notebook("Archive", Some(projects.id));
+        let paths = notebook_paths(&[archive.clone(), work.clone(), projects.clone()]);
+
+        assert_eq!(paths[&work.id], vec!["Work"]);
+        assert_eq!(paths[&archive.id], vec!["Work", "Projects", "Archive"]);
+    }
+
+    #[test]
+    fn paths_stop_at_a_loop() {
+        let mut first = notebook("First", None);
+        let second = notebook("Second", Some(first.id));
+        first.parent_id = Some(second.id);
+
+        assert!(notebook_paths(&[first.clone(), second])[&first.id].len() <= 3);
+    }
+}
\ No newline at end of file
@@ -1,17 +1,42 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use crate::utils::{detect_language, SearchQuery};
 
 // ===== DATABASE MODELS =====
 
+// Text search configuration used to build the note's search vector
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum NoteLanguage {
+    #[default]
+    English,
+    German,
+    Spanish,
+    // no stemming or stopwords, for languages we don't have a configuration for
+    Simple,
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
 pub struct Note {
     pub id: Uuid,
     pub user_id: Uuid,
     pub title: String,
     pub content: String,
+    pub language: NoteLanguage,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // bumped on every update, sync clients send it back as their base version
+    pub version: i64,
+    pub due_at: Option<DateTime<Utc>>,
+    // when the reminder goes out, independent of the due date
+    pub remind_at: Option<DateTime<Utc>>,
+    pub pinned: bool,
+    pub favorite: bool,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub notebook_id: Option<Uuid>,
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -19,12 +44,29 @@ pub struct NewNote {
     pub user_id: Uuid,
     pub title: String,
     pub content: String,
+    pub language: NoteLanguage,
+    // false when a client picked the language, detected ones follow later edits
+    pub language_detected: bool,
+    pub due_at: Option<DateTime<Utc>>,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub notebook_id: Option<Uuid>,
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateNote {
     pub title: Option<String>,
     pub content: Option<String>,
+    pub language: Option<NoteLanguage>,
+    // missing leaves the value alone, null clears it
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub remind_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub notebook_id: Option<Option<Uuid>>,
+    // replaces all of the note's tags
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -32,6 +74,11 @@ pub struct QueryParams {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
     pub search: Option<String>,
+    pub mode: Option<SearchMode>,
+    pub sort: Option<NoteSort>,
+    pub highlight_start: Option<String>,
+    pub highlight_end: Option<String>,
+    pub include_archived: Option<bool>,
 }
 
 
@@ -39,6 +86,13 @@ pub struct QueryParams {
 pub struct CreateNoteDto {
     pub title: String,
     pub content: String,
+    // detected from the title and content when missing
+    pub language: Option<NoteLanguage>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub remind_at: Option<DateTime<Utc>>,
+    pub notebook_id: Option<Uuid>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 
@@ -47,16 +101,280 @@ pub struct UserNotes {
     pub notes: Vec<Note>,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum SearchMode {
+    // tsvector matching only
+    #[default]
+    Fulltext,
+    // tsvector matching plus pg_trgm similarity for misspellings and partial words
+    Fuzzy,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum NoteSort {
+    // best match first when searching, newest first otherwise
+    #[default]
+    Relevance,
+    Newest,
+    Oldest,
+    RecentlyUpdated,
+    Title,
+}
+
+#[derive(Serialize, Deserialize, Debug, sqlx::FromRow)]
+pub struct SearchHit {
+    #[sqlx(flatten)]
+    pub note: Note,
+    pub score: f32,
+    // HTML, the note text is escaped and matches are wrapped in the highlight markers
+    pub title_snippet: String,
+    pub content_snippet: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+// Representation returned by GET /notes/{id}
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteFormat {
+    #[default]
+    Json,
+    Html,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NoteFormatParams {
+    pub format: Option<NoteFormat>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RenderedNote {
+    #[serde(flatten)]
+    pub note: Note,
+    pub html: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RenderRequest {
+    pub markdown: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RenderedMarkdown {
+    pub html: String,
+}
+
+// Organisation state changed by the pin, favorite and archive endpoints, None leaves it alone
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoteFlags {
+    pub pinned: Option<bool>,
+    pub favorite: Option<bool>,
+    pub archived: Option<bool>,
+}
+
+// How a parsed search query is matched, ordered and highlighted
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub sort: NoteSort,
+    pub markers: HighlightMarkers,
+    pub include_archived: bool,
+}
+
+// The notes GET /notes selects for a set of query params, regardless of pagination
+#[derive(Debug, Clone)]
+pub enum NoteFilter {
+    All {
+        sort: NoteSort,
+        include_archived: bool,
+    },
+    Search {
+        query: SearchQuery,
+        options: SearchOptions,
+    },
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum ExportFormat {
+    #[serde(rename = "markdown-zip")]
+    MarkdownZip,
+    #[default]
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "html")]
+    Html,
+    #[serde(rename = "enex")]
+    Enex,
+}
+
+// Read next to QueryParams, which carries the filters
+#[derive(Deserialize, Debug)]
+pub struct ExportParams {
+    pub format: Option<ExportFormat>,
+}
+
+// Markers wrapped around matched terms by ts_headline
+#[derive(Debug, Clone)]
+pub struct HighlightMarkers {
+    pub start: String,
+    pub end: String,
+}
+
 // ===== HELPER METHODS =====
 
+// Wraps present values in Some, so with #[serde(default)] an explicit null becomes
+// Some(None) and can be told apart from a missing field
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl NoteLanguage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteLanguage::English => "english",
+            NoteLanguage::German => "german",
+            NoteLanguage::Spanish => "spanish",
+            NoteLanguage::Simple => "simple",
+        }
+    }
+}
+
+impl SearchMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchMode::Fulltext => "fulltext",
+            SearchMode::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+impl NoteSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NoteSort::Relevance => "relevance",
+            NoteSort::Newest => "newest",
+            NoteSort::Oldest => "oldest",
+            NoteSort::RecentlyUpdated => "recently_updated",
+            NoteSort::Title => "title",
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::MarkdownZip => "application/zip",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Html => "text/html; charset=utf-8",
+            ExportFormat::Enex => "application/enex+xml",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::MarkdownZip => "zip",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+            ExportFormat::Enex => "enex",
+        }
+    }
+}
+
 impl NewNote {
     pub fn new(user_id: Uuid, title: String, content: String) -> Self {
+        let language = detect_language(&format!("{} {}", title, content));
         Self {
             user_id,
             title,
             content,
+            language,
+            language_detected: true,
+            due_at: None,
+            remind_at: None,
+            notebook_id: None,
+            tags: Vec::new(),
         }
     }
+
+    pub fn with_language(mut self, language: NoteLanguage) -> Self {
+        self.language = language;
+        self.language_detected = false;
+        self
+    }
+
+    pub fn with_notebook(mut self, notebook_id: Option<Uuid>) -> Self {
+        self.notebook_id = notebook_id;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn with_schedule(mut self, due_at: Option<DateTime<Utc>>, remind_at: Option<DateTime<Utc>>) -> Self {
+        self.due_at = due_at;
+        self.remind_at = remind_at;
+        self
+    }
+}
+
+impl HighlightMarkers {
+    pub fn new(start: Option<String>, end: Option<String>) -> Result<Self, String> {
+        let markers = Self {
+            start: start.unwrap_or_else(|| "<mark>".to_string()),
+            end: end.unwrap_or_else(|| "</mark>".to_string()),
+        };
+
+        // markers end up inside the ts_headline options string, so keep them to
+        // characters that can't break out of a double-quoted option value
+        for marker in [&markers.start, &markers.end] {
+            if marker.is_empty() || marker.len() > 32 {
+                return Err("Highlight markers must be between 1 and 32 characters".to_string());
+            }
+            if marker.contains('"') || marker.contains('\\') {
+                return Err("Highlight markers must not contain quotes or backslashes".to_string());
+            }
+        }
+
+        Ok(markers)
+    }
+
+    // ts_headline options for the title: highlight every match in the whole title
+    pub fn title_options(&self) -> String {
+        format!(
+            "StartSel=\"{}\", StopSel=\"{}\", HighlightAll=true",
+            self.start, self.end
+        )
+    }
+
+    // ts_headline options for the content: a few short fragments around the matches
+    pub fn content_options(&self) -> String {
+        format!(
+            "StartSel=\"{}\", StopSel=\"{}\", MaxFragments=3, MaxWords=30, MinWords=10, FragmentDelimiter=\" ... \"",
+            self.start, self.end
+        )
+    }
+}
+
+impl Default for UpdateNote {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl UpdateNote {
@@ -64,6 +382,11 @@ impl UpdateNote {
         Self {
             title: None,
             content: None,
+            language: None,
+            due_at: None,
+            remind_at: None,
+            notebook_id: None,
+            tags: None,
         }
     }
 
@@ -76,4 +399,9 @@ impl UpdateNote {
         self.content = Some(content);
         self
     }
+
+    pub fn with_language(mut self, language: NoteLanguage) -> Self {
+        self.language = Some(language);
+        self
+    }
 }
\ No newline at end of file
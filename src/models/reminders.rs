@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Debug)]
+pub struct DueNotesParams {
+    // how far ahead GET /notes/upcoming looks
+    pub days: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+// Only returned when the token is created, the server keeps just its hash
+#[derive(Serialize, Debug)]
+pub struct CalendarFeed {
+    pub token: String,
+    pub url: String,
+}
\ No newline at end of file
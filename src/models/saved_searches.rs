@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::models::{NoteSort, QueryParams, SearchMode};
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub mode: SearchMode,
+    pub sort: NoteSort,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NewSavedSearch {
+    pub user_id: Uuid,
+    pub name: String,
+    pub query: String,
+    pub mode: SearchMode,
+    pub sort: NoteSort,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSavedSearch {
+    pub name: Option<String>,
+    pub query: Option<String>,
+    pub mode: Option<SearchMode>,
+    pub sort: Option<NoteSort>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateSavedSearchDto {
+    pub name: String,
+    pub query: String,
+    pub mode: Option<SearchMode>,
+    pub sort: Option<NoteSort>,
+}
+
+// Paging and highlighting for a saved search run, everything else comes from the saved search
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SavedSearchRunParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub highlight_start: Option<String>,
+    pub highlight_end: Option<String>,
+    pub include_archived: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserSavedSearches {
+    pub saved_searches: Vec<SavedSearch>,
+}
+
+// ===== HELPER METHODS =====
+
+impl NewSavedSearch {
+    pub fn new(user_id: Uuid, dto: CreateSavedSearchDto) -> Self {
+        Self {
+            user_id,
+            name: dto.name.trim().to_string(),
+            query: dto.query,
+            mode: dto.mode.unwrap_or_default(),
+            sort: dto.sort.unwrap_or_default(),
+        }
+    }
+}
+
+impl SavedSearch {
+    // The GET /notes parameters equivalent to running this saved search
+    pub fn to_query_params(&self, run: SavedSearchRunParams) -> QueryParams {
+        // an empty saved query is a sorted view of every note rather than a search
+        let search = if self.query.trim().is_empty() {
+            None
+        } else {
+            Some(self.query.clone())
+        };
+
+        QueryParams {
+            limit: run.limit,
+            offset: run.offset,
+            search,
+            mode: Some(self.mode),
+            sort: Some(self.sort),
+            highlight_start: run.highlight_start,
+            highlight_end: run.highlight_end,
+            include_archived: run.include_archived,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::models::Note;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum SharePermission {
+    #[default]
+    Read,
+    Write,
+}
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct NoteShare {
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+    pub username: String,
+    pub permission: SharePermission,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShareDto {
+    // who to share with, sharing again changes the permission
+    pub email: String,
+    pub permission: Option<SharePermission>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SharePath {
+    pub note_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NoteShares {
+    pub shares: Vec<NoteShare>,
+}
+
+// A note someone else owns, as seen by a user it's shared with
+#[derive(Serialize, Debug)]
+pub struct SharedNote {
+    #[serde(flatten)]
+    pub note: Note,
+    pub permission: SharePermission,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SharedNotes {
+    pub notes: Vec<SharedNote>,
+}
+
+// ===== HELPER METHODS =====
+
+impl SharePermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SharePermission::Read => "read",
+            SharePermission::Write => "write",
+        }
+    }
+}
\ No newline at end of file
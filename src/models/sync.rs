@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::models::{Note, NoteLanguage, QuotaExceeded};
+
+// ===== DATABASE MODELS =====
+
+// Left behind by a deleted note until the retention period is over
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct Tombstone {
+    pub id: Uuid,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// Where a page of changes ends and whether anything comes after it
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeWindow {
+    pub until: i64,
+    pub has_more: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SyncParams {
+    // token from the last sync, everything is returned without one
+    pub since: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SyncChanges {
+    pub notes: Vec<Note>,
+    pub deleted: Vec<Tombstone>,
+    // pass back as `since`, call again right away while has_more is set
+    pub token: String,
+    pub has_more: bool,
+}
+
+// A change made on the client. Without base_version it creates the note under the
+// client generated id, otherwise it only applies if the note is still at that version
+#[derive(Deserialize, Debug)]
+pub struct SyncChange {
+    pub id: Uuid,
+    pub base_version: Option<i64>,
+    #[serde(default)]
+    pub deleted: bool,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub language: Option<NoteLanguage>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SyncPush {
+    pub changes: Vec<SyncChange>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    // someone else changed the note since base_version
+    VersionMismatch,
+    // the note was deleted on the server
+    Deleted,
+    // a create for an id that is already taken
+    AlreadyExists,
+    Invalid,
+    // the change would go over one of the user's quotas
+    QuotaExceeded,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SyncConflict {
+    pub id: Uuid,
+    pub reason: ConflictReason,
+    pub message: String,
+    // current server copy for the client to merge against
+    pub server_note: Option<Note>,
+    // 413 or 507 for a change over quota, what POST or PUT /notes would have answered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct SyncPushResult {
+    pub applied: Vec<Note>,
+    pub deleted: Vec<Uuid>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+// ===== HELPER METHODS =====
+
+impl SyncConflict {
+    pub fn new(id: Uuid, reason: ConflictReason, message: impl Into<String>, server_note: Option<Note>) -> Self {
+        Self { id, reason, message: message.into(), server_note, status: None }
+    }
+
+    pub fn quota_exceeded(id: Uuid, exceeded: QuotaExceeded, server_note: Option<Note>) -> Self {
+        Self {
+            id,
+            reason: ConflictReason::QuotaExceeded,
+            message: exceeded.message(),
+            server_note,
+            status: Some(exceeded.status().as_u16()),
+        }
+    }
+}
\ No newline at end of file
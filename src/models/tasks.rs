@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// ===== DATABASE MODELS =====
+
+// A checkbox line from the note_tasks view, along with the note it's in
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct NoteTask {
+    pub note_id: Uuid,
+    pub note_title: String,
+    // position among the note's tasks, what PATCH /notes/{id}/tasks/{index} takes
+    pub task_index: i32,
+    pub line_number: i32,
+    pub done: bool,
+    pub text: String,
+    pub note_updated_at: DateTime<Utc>,
+}
+
+// The task's line as of one version of the note, read together so the toggle can
+// be written back with a version check
+#[derive(Debug, Clone)]
+pub struct TaskLine {
+    pub line_number: i32,
+    pub done: bool,
+    pub version: i64,
+    pub content: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    #[default]
+    Open,
+    Done,
+    All,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TaskParams {
+    pub status: Option<TaskStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TaskPath {
+    pub note_id: Uuid,
+    pub task_index: i32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UpdateTaskDto {
+    // flips the checkbox when missing
+    pub done: Option<bool>,
+    // the note version the client saw, the toggle is refused if the note moved on
+    pub version: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UserTasks {
+    pub tasks: Vec<NoteTask>,
+}
+
+// ===== HELPER METHODS =====
+
+impl TaskStatus {
+    // done filter for the query, None for every task
+    pub fn done(&self) -> Option<bool> {
+        match self {
+            TaskStatus::Open => Some(false),
+            TaskStatus::Done => Some(true),
+            TaskStatus::All => None,
+        }
+    }
+}
\ No newline at end of file
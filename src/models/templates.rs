@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::models::{deserialize_some, NoteLanguage};
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct NoteTemplate {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    // title and content may hold {{placeholders}}
+    pub title: String,
+    pub content: String,
+    pub language: Option<NoteLanguage>,
+    // given to every note created from the template
+    pub notebook_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewNoteTemplate {
+    pub user_id: Uuid,
+    pub name: String,
+    pub title: String,
+    pub content: String,
+    pub language: Option<NoteLanguage>,
+    pub notebook_id: Option<Uuid>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateNoteTemplateDto {
+    pub name: String,
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+    pub language: Option<NoteLanguage>,
+    pub notebook_id: Option<Uuid>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateNoteTemplate {
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub language: Option<NoteLanguage>,
+    // missing keeps the notebook, null stops filing new notes in one
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub notebook_id: Option<Option<Uuid>>,
+    pub tags: Option<Vec<String>>,
+}
+
+// Body of POST /notes/from-template/{id}
+#[derive(Debug, Deserialize, Default)]
+pub struct InstantiateTemplateDto {
+    // values for the template's own {{placeholders}}, these win over the built-in ones
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub remind_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UserNoteTemplates {
+    pub templates: Vec<NoteTemplate>,
+}
+
+// ===== HELPER METHODS =====
+
+impl NewNoteTemplate {
+    pub fn new(user_id: Uuid, dto: CreateNoteTemplateDto) -> Self {
+        Self {
+            user_id,
+            name: dto.name.trim().to_string(),
+            title: dto.title,
+            content: dto.content,
+            language: dto.language,
+            notebook_id: dto.notebook_id,
+            tags: dto.tags,
+        }
+    }
+}
\ No newline at end of file
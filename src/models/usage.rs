@@ -0,0 +1,55 @@
+use actix_web::http::StatusCode;
+use serde::Serialize;
+
+// ===== DATABASE MODELS =====
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct NoteUsage {
+    pub note_count: i64,
+    pub content_bytes: i64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct UsageLimit {
+    pub used: i64,
+    pub limit: u64,
+}
+
+// Body of GET /auth/me/usage
+#[derive(Serialize, Debug)]
+pub struct UserUsage {
+    pub notes: UsageLimit,
+    pub content_bytes: UsageLimit,
+    pub attachment_bytes: UsageLimit,
+    pub max_note_size: u64,
+}
+
+// Why a note write was refused
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuotaExceeded {
+    NoteTooLarge { limit: u64 },
+    TooManyNotes { limit: u64 },
+    ContentStorage { limit: u64, used: i64 },
+}
+
+// ===== HELPER METHODS =====
+
+impl QuotaExceeded {
+    // 413 for a note that could never fit, 507 when the account is full
+    pub fn status(&self) -> StatusCode {
+        match self {
+            QuotaExceeded::NoteTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            QuotaExceeded::TooManyNotes { .. } | QuotaExceeded::ContentStorage { .. } => StatusCode::INSUFFICIENT_STORAGE,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            QuotaExceeded::NoteTooLarge { limit } => {
+                format!("Notes must not exceed {} bytes of title and content", limit)
+            }
+            QuotaExceeded::TooManyNotes { limit } => format!("Note quota of {} notes exceeded", limit),
+            QuotaExceeded::ContentStorage { limit, .. } => format!("Note storage quota of {} bytes exceeded", limit),
+        }
+    }
+}
\ No newline at end of file
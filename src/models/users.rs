@@ -145,6 +145,12 @@ impl NewUser {
     }
 }
 
+impl Default for UpdateUser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UpdateUser {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::Json;
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+
+// Events a webhook can subscribe to, queued by the notes and note_shares table triggers
+// and by the reminder job
+pub const WEBHOOK_EVENTS: [&str; 5] = ["note.created", "note.updated", "note.deleted", "note.reminder", "note.shared"];
+// Only sent by POST /webhooks/{id}/ping
+pub const PING_EVENT: &str = "ping";
+
+// ===== DATABASE MODELS =====
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    // only shown once, when the webhook is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub consecutive_failures: i32,
+    pub disabled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    // gave up after the last retry
+    Failed,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub payload: Json<Value>,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+// A delivery claimed by the dispatcher along with where it goes
+#[derive(Clone, Debug, sqlx::FromRow)]
+pub struct DueDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub event: String,
+    pub payload: Json<Value>,
+    pub attempts: i32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NewWebhook {
+    pub user_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreateWebhookDto {
+    pub url: String,
+    pub events: Vec<String>,
+    // generated when missing
+    pub secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateWebhook {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    // re-enabling a disabled webhook also resets its failure count
+    pub active: Option<bool>,
+}
+
+// The create response, the only one including the secret
+#[derive(Serialize, Debug)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DeliveryParams {
+    pub status: Option<DeliveryStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserWebhooks {
+    pub webhooks: Vec<Webhook>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookDeliveries {
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+// ===== HELPER METHODS =====
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl NewWebhook {
+    pub fn new(user_id: Uuid, url: String, secret: String, events: Vec<String>) -> Self {
+        Self { user_id, url, secret, events }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,201 @@
+use sqlx::{PgPool, Postgres, Transaction};
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{Attachment, NewAttachment};
+
+pub struct AttachmentRepository {
+    pool: PgPool,
+}
+
+impl AttachmentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>> {
+        Ok(self.pool.begin().await?)
+    }
+
+    pub async fn get_attachment(&self, attachment_id: Uuid, note_id: Uuid, user_id: Uuid) -> Result<Option<Attachment>> {
+        let attachment = sqlx::query_as!(
+            Attachment,
+            r#"
+            SELECT 
+                id, 
+                note_id, 
+                user_id, 
+                filename, 
+                content_type, 
+                size_bytes, 
+                sha256, 
+                created_at
+            FROM attachments 
+            WHERE id = $1 AND note_id = $2 AND user_id = $3
+            "#,
+            attachment_id,
+            note_id,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(attachment)
+    }
+
+    pub async fn get_note_attachments(&self, note_id: Uuid, user_id: Uuid) -> Result<Vec<Attachment>> {
+        let attachments = sqlx::query_as!(
+            Attachment,
+            r#"
+            SELECT 
+                id, 
+                note_id, 
+                user_id, 
+                filename, 
+                content_type, 
+                size_bytes, 
+                sha256, 
+                created_at
+            FROM attachments 
+            WHERE note_id = $1 AND user_id = $2
+            ORDER BY created_at ASC
+            "#,
+            note_id,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(attachments)
+    }
+
+    // Bytes a user's attachments count against their quota, duplicates included
+    pub async fn get_user_usage(&self, user_id: Uuid) -> Result<i64> {
+        let usage = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(size_bytes), 0)::BIGINT AS "usage!"
+            FROM attachments 
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(usage)
+    }
+
+    // Serialize uploads and deletes of the same content until the transaction ends
+    pub async fn lock_blob(&self, tx: &mut Transaction<'_, Postgres>, sha256: &str) -> Result<()> {
+        sqlx::query!(
+            "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+            sha256
+        )
+            .fetch_one(&mut **tx)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn blob_exists(&self, tx: &mut Transaction<'_, Postgres>, sha256: &str) -> Result<bool> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM attachment_blobs WHERE sha256 = $1) AS "exists!"
+            "#,
+            sha256
+        )
+            .fetch_one(&mut **tx)
+            .await?;
+
+        Ok(exists)
+    }
+
+    pub async fn create_blob(&self, tx: &mut Transaction<'_, Postgres>, sha256: &str, size_bytes: i64) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO attachment_blobs (sha256, size_bytes)
+            VALUES ($1, $2)
+            "#,
+            sha256,
+            size_bytes
+        )
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_attachment(&self, tx: &mut Transaction<'_, Postgres>, new_attachment: NewAttachment) -> Result<Attachment> {
+        let attachment = sqlx::query_as!(
+            Attachment,
+            r#"
+            INSERT INTO attachments (note_id, user_id, filename, content_type, size_bytes, sha256)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING 
+                id, 
+                note_id, 
+                user_id, 
+                filename, 
+                content_type, 
+                size_bytes, 
+                sha256, 
+                created_at
+            "#,
+            new_attachment.note_id,
+            new_attachment.user_id,
+            new_attachment.filename,
+            new_attachment.content_type,
+            new_attachment.size_bytes,
+            new_attachment.sha256
+        )
+            .fetch_one(&mut **tx)
+            .await?;
+
+        Ok(attachment)
+    }
+
+    pub async fn delete_attachment(&self, tx: &mut Transaction<'_, Postgres>, attachment_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM attachments 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            attachment_id,
+            user_id
+        )
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Remove the blob row once nothing references it, returns whether it was removed
+    pub async fn delete_blob_if_orphaned(&self, tx: &mut Transaction<'_, Postgres>, sha256: &str) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM attachment_blobs 
+            WHERE sha256 = $1 
+                AND NOT EXISTS (SELECT 1 FROM attachments WHERE attachments.sha256 = attachment_blobs.sha256)
+            "#,
+            sha256
+        )
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Blobs left behind when notes (and with them their attachments) were deleted
+    pub async fn find_orphaned_blobs(&self) -> Result<Vec<String>> {
+        let blobs = sqlx::query_scalar!(
+            r#"
+            SELECT sha256 
+            FROM attachment_blobs 
+            WHERE NOT EXISTS (SELECT 1 FROM attachments WHERE attachments.sha256 = attachment_blobs.sha256)
+            "#
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(blobs)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,161 @@
+use sqlx::PgPool;
+use sqlx::types::Json;
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{Import, ImportFileError, ImportFormat, ImportStatus};
+
+pub struct ImportRepository {
+    pool: PgPool,
+}
+
+impl ImportRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_import(&self, user_id: Uuid, format: ImportFormat, filename: &str) -> Result<Import> {
+        let import = sqlx::query_as!(
+            Import,
+            r#"
+            INSERT INTO imports (user_id, format, filename)
+            VALUES ($1, $2, $3)
+            RETURNING 
+                id, 
+                user_id, 
+                format AS "format: ImportFormat", 
+                filename, 
+                status AS "status: ImportStatus", 
+                total_files, 
+                processed_files, 
+                imported_notes, 
+                errors AS "errors: Json<Vec<ImportFileError>>", 
+                created_at, 
+                updated_at, 
+                finished_at
+            "#,
+            user_id,
+            format.as_str(),
+            filename
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(import)
+    }
+
+    pub async fn get_import(&self, import_id: Uuid, user_id: Uuid) -> Result<Option<Import>> {
+        let import = sqlx::query_as!(
+            Import,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                format AS "format: ImportFormat", 
+                filename, 
+                status AS "status: ImportStatus", 
+                total_files, 
+                processed_files, 
+                imported_notes, 
+                errors AS "errors: Json<Vec<ImportFileError>>", 
+                created_at, 
+                updated_at, 
+                finished_at
+            FROM imports
+            WHERE id = $1 AND user_id = $2
+            "#,
+            import_id,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(import)
+    }
+
+    pub async fn get_user_imports(&self, user_id: Uuid) -> Result<Vec<Import>> {
+        let imports = sqlx::query_as!(
+            Import,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                format AS "format: ImportFormat", 
+                filename, 
+                status AS "status: ImportStatus", 
+                total_files, 
+                processed_files, 
+                imported_notes, 
+                errors AS "errors: Json<Vec<ImportFileError>>", 
+                created_at, 
+                updated_at, 
+                finished_at
+            FROM imports
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            LIMIT 50
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(imports)
+    }
+
+    pub async fn start_import(&self, import_id: Uuid, total_files: i32) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE imports
+            SET status = 'running', total_files = $2, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            import_id,
+            total_files
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_progress(
+        &self,
+        import_id: Uuid,
+        processed_files: i32,
+        imported_notes: i32,
+        errors: &[ImportFileError]
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE imports
+            SET processed_files = $2, imported_notes = $3, errors = $4, updated_at = NOW()
+            WHERE id = $1
+            "#,
+            import_id,
+            processed_files,
+            imported_notes,
+            Json(errors) as _
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn finish_import(&self, import_id: Uuid, status: ImportStatus, errors: &[ImportFileError]) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE imports
+            SET status = $2, errors = $3, updated_at = NOW(), finished_at = NOW()
+            WHERE id = $1
+            "#,
+            import_id,
+            status.as_str(),
+            Json(errors) as _
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,305 @@
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+use sqlx::types::Json;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use crate::models::{JobSchedule, JobStatus, NewJob, QueuedJob};
+
+pub struct JobRepository {
+    pool: PgPool,
+}
+
+impl JobRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>> {
+        Ok(self.pool.begin().await?)
+    }
+
+    pub async fn enqueue(&self, new_job: NewJob) -> Result<QueuedJob> {
+        let mut conn = self.pool.acquire().await?;
+        self.enqueue_in(&mut conn, new_job).await
+    }
+
+    pub async fn enqueue_in(&self, conn: &mut PgConnection, new_job: NewJob) -> Result<QueuedJob> {
+        let job = sqlx::query_as!(
+            QueuedJob,
+            r#"
+            INSERT INTO jobs (kind, payload, max_attempts, run_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING 
+                id, 
+                kind, 
+                payload AS "payload: Json<Value>", 
+                status AS "status: JobStatus", 
+                attempts, 
+                max_attempts, 
+                run_at, 
+                locked_by, 
+                locked_until, 
+                last_error, 
+                created_at, 
+                updated_at, 
+                finished_at
+            "#,
+            new_job.kind,
+            new_job.payload,
+            new_job.max_attempts,
+            new_job.run_at
+        )
+            .fetch_one(conn)
+            .await?;
+
+        Ok(job)
+    }
+
+    // The oldest due job of one of `kinds`, locked to this worker for `lease_secs`
+    pub async fn claim_next(&self, worker_id: &str, kinds: &[String], lease_secs: f64) -> Result<Option<QueuedJob>> {
+        let job = sqlx::query_as!(
+            QueuedJob,
+            r#"
+            UPDATE jobs
+            SET 
+                status = 'running',
+                attempts = attempts + 1,
+                locked_by = $1,
+                locked_until = NOW() + make_interval(secs => $3),
+                updated_at = NOW()
+            WHERE id = (
+                SELECT id 
+                FROM jobs 
+                WHERE status = 'pending' AND run_at <= NOW() AND kind = ANY($2)
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING 
+                id, 
+                kind, 
+                payload AS "payload: Json<Value>", 
+                status AS "status: JobStatus", 
+                attempts, 
+                max_attempts, 
+                run_at, 
+                locked_by, 
+                locked_until, 
+                last_error, 
+                created_at, 
+                updated_at, 
+                finished_at
+            "#,
+            worker_id,
+            kinds,
+            lease_secs
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(job)
+    }
+
+    pub async fn complete(&self, job_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET 
+                status = 'completed',
+                locked_by = NULL,
+                locked_until = NULL,
+                updated_at = NOW(),
+                finished_at = NOW()
+            WHERE id = $1
+            "#,
+            job_id
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn retry(&self, job_id: Uuid, error: &str, run_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET 
+                status = 'pending',
+                run_at = $3,
+                last_error = $2,
+                locked_by = NULL,
+                locked_until = NULL,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            job_id,
+            error,
+            run_at
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Dead-letter a job that ran out of attempts
+    pub async fn bury(&self, job_id: Uuid, error: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET 
+                status = 'dead',
+                last_error = $2,
+                locked_by = NULL,
+                locked_until = NULL,
+                updated_at = NOW(),
+                finished_at = NOW()
+            WHERE id = $1
+            "#,
+            job_id,
+            error
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Jobs whose worker died or hung past the lease go back in the queue, or to the
+    // dead-letter state when that was their last attempt. Returns the buried ones
+    pub async fn release_expired(&self) -> Result<Vec<QueuedJob>> {
+        let jobs = sqlx::query_as!(
+            QueuedJob,
+            r#"
+            UPDATE jobs
+            SET 
+                status = CASE WHEN attempts >= max_attempts THEN 'dead' ELSE 'pending' END,
+                run_at = NOW(),
+                last_error = 'Worker ' || COALESCE(locked_by, '') || ' stopped before the job finished',
+                locked_by = NULL,
+                locked_until = NULL,
+                updated_at = NOW(),
+                finished_at = CASE WHEN attempts >= max_attempts THEN NOW() END
+            WHERE status = 'running' AND locked_until < NOW()
+            RETURNING 
+                id, 
+                kind, 
+                payload AS "payload: Json<Value>", 
+                status AS "status: JobStatus", 
+                attempts, 
+                max_attempts, 
+                run_at, 
+                locked_by, 
+                locked_until, 
+                last_error, 
+                created_at, 
+                updated_at, 
+                finished_at
+            "#
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(jobs.into_iter().filter(|job| job.status == JobStatus::Dead).collect())
+    }
+
+    pub async fn purge_finished_jobs(&self, retention_days: u32) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM jobs
+            WHERE status IN ('completed', 'dead') AND finished_at < NOW() - make_interval(days => $1)
+            "#,
+            retention_days as i32
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Keeps the next run of schedules whose cron didn't change, so restarts don't skip or repeat runs
+    pub async fn upsert_schedule(&self, schedule: &JobSchedule) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO job_schedules (name, cron, kind, payload, max_attempts, next_run_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (name) DO UPDATE
+            SET 
+                cron = EXCLUDED.cron,
+                kind = EXCLUDED.kind,
+                payload = EXCLUDED.payload,
+                max_attempts = EXCLUDED.max_attempts,
+                next_run_at = CASE 
+                    WHEN job_schedules.cron = EXCLUDED.cron THEN job_schedules.next_run_at 
+                    ELSE EXCLUDED.next_run_at 
+                END
+            "#,
+            schedule.name,
+            schedule.cron,
+            schedule.kind,
+            schedule.payload.0,
+            schedule.max_attempts,
+            schedule.next_run_at
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_schedules_except(&self, names: &[String]) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM job_schedules 
+            WHERE NOT (name = ANY($1))
+            "#,
+            names
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Locked until the transaction ends, so every due run is enqueued by one worker only
+    pub async fn get_due_schedules_in(&self, conn: &mut PgConnection) -> Result<Vec<JobSchedule>> {
+        let schedules = sqlx::query_as!(
+            JobSchedule,
+            r#"
+            SELECT 
+                name, 
+                cron, 
+                kind, 
+                payload AS "payload: Json<Value>", 
+                max_attempts, 
+                next_run_at, 
+                last_run_at
+            FROM job_schedules 
+            WHERE next_run_at <= NOW()
+            FOR UPDATE SKIP LOCKED
+            "#
+        )
+            .fetch_all(conn)
+            .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn reschedule_in(&self, conn: &mut PgConnection, name: &str, next_run_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE job_schedules
+            SET next_run_at = $2, last_run_at = NOW()
+            WHERE name = $1
+            "#,
+            name,
+            next_run_at
+        )
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,5 +1,31 @@
+pub mod attachments;
+pub mod imports;
+pub mod jobs;
 pub mod users;
 pub mod notes;
+pub mod note_documents;
+pub mod note_links;
+pub mod notebooks;
+pub mod saved_searches;
+pub mod shares;
+pub mod sync;
+pub mod tasks;
+pub mod templates;
+pub mod usage;
+pub mod webhooks;
 
+pub use attachments::*;
+pub use imports::*;
+pub use jobs::*;
 pub use users::*;
-pub use notes::*;
\ No newline at end of file
+pub use notes::*;
+pub use note_documents::*;
+pub use note_links::*;
+pub use notebooks::*;
+pub use saved_searches::*;
+pub use shares::*;
+pub use sync::*;
+pub use tasks::*;
+pub use templates::*;
+pub use usage::*;
+pub use webhooks::*;
\ No newline at end of file
@@ -0,0 +1,45 @@
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+
+pub struct NoteDocumentRepository {
+    pool: PgPool,
+}
+
+impl NoteDocumentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_document_state(&self, note_id: Uuid) -> Result<Option<Vec<u8>>> {
+        let state = sqlx::query_scalar!(
+            r#"
+            SELECT state
+            FROM note_documents
+            WHERE note_id = $1
+            "#,
+            note_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(state)
+    }
+
+    pub async fn save_document_state(&self, note_id: Uuid, state: &[u8]) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO note_documents (note_id, state)
+            VALUES ($1, $2)
+            ON CONFLICT (note_id) DO UPDATE
+                SET state = EXCLUDED.state, updated_at = NOW()
+            "#,
+            note_id,
+            state
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
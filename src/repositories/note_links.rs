@@ -0,0 +1,192 @@
+use sqlx::{Acquire, PgConnection, PgPool};
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{Backlink, GraphEdge, GraphNode, NoteLink};
+use crate::utils::WikiLink;
+
+pub struct NoteLinkRepository {
+    pool: PgPool,
+}
+
+impl NoteLinkRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Swap out everything a note links to for the links just parsed from its content
+    pub async fn replace_note_links(&self, note_id: Uuid, user_id: Uuid, links: &[WikiLink]) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        self.replace_note_links_in(&mut conn, note_id, user_id, links).await
+    }
+
+    pub async fn replace_note_links_in(
+        &self,
+        conn: &mut PgConnection,
+        note_id: Uuid,
+        user_id: Uuid,
+        links: &[WikiLink]
+    ) -> Result<()> {
+        // a savepoint when the caller is already inside a transaction
+        let mut tx = conn.begin().await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM note_links
+            WHERE source_note_id = $1 AND user_id = $2
+            "#,
+            note_id,
+            user_id
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        let targets: Vec<String> = links.iter().map(|link| link.target.clone()).collect();
+        let target_note_ids: Vec<Option<Uuid>> = links.iter().map(|link| link.note_id).collect();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO note_links (source_note_id, user_id, target, target_note_id, position)
+            SELECT $1, $2, link.target, link.target_note_id, (link.position - 1)::INTEGER
+            FROM UNNEST($3::TEXT[], $4::UUID[]) WITH ORDINALITY AS link(target, target_note_id, position)
+            "#,
+            note_id,
+            user_id,
+            &targets,
+            &target_note_ids as &[Option<Uuid>]
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn get_note_links(&self, note_id: Uuid, user_id: Uuid) -> Result<Vec<NoteLink>> {
+        let links = sqlx::query_as!(
+            NoteLink,
+            r#"
+            SELECT 
+                target AS "target!",
+                (resolved_note_id IS NOT NULL) AS "resolved!",
+                resolved_note_id AS note_id,
+                resolved_title AS title
+            FROM resolved_note_links
+            WHERE source_note_id = $1 AND user_id = $2
+            ORDER BY position ASC
+            "#,
+            note_id,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(links)
+    }
+
+    pub async fn get_note_backlinks(&self, note_id: Uuid, user_id: Uuid) -> Result<Vec<Backlink>> {
+        let backlinks = sqlx::query_as!(
+            Backlink,
+            r#"
+            SELECT 
+                n.id AS note_id,
+                n.title,
+                n.updated_at
+            FROM notes n
+            WHERE n.user_id = $2
+              AND n.id <> $1
+              AND EXISTS (
+                  SELECT 1 
+                  FROM resolved_note_links l
+                  WHERE l.source_note_id = n.id AND l.resolved_note_id = $1
+              )
+            ORDER BY n.updated_at DESC
+            "#,
+            note_id,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(backlinks)
+    }
+
+    // Notes whose [[title]] links currently resolve to this note, by title rather than id
+    pub async fn get_title_referrers(&self, note_id: Uuid, user_id: Uuid) -> Result<Vec<Uuid>> {
+        let referrers = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT source_note_id AS "source_note_id!"
+            FROM resolved_note_links
+            WHERE user_id = $2 
+              AND resolved_note_id = $1 
+              AND target_note_id IS NULL
+            "#,
+            note_id,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(referrers)
+    }
+
+    // `notebook` matches by name like in:, including the notebooks nested inside it
+    pub async fn get_graph_nodes(
+        &self,
+        user_id: Uuid,
+        tag: Option<&str>,
+        notebook: Option<&str>
+    ) -> Result<Vec<GraphNode>> {
+        let nodes = sqlx::query_as!(
+            GraphNode,
+            r#"
+            SELECT 
+                id, 
+                title, 
+                notebook_id,
+                tags,
+                created_at, 
+                updated_at
+            FROM notes
+            WHERE user_id = $1
+              AND ($2::TEXT IS NULL OR tags @> ARRAY[$2::TEXT])
+              AND ($3::TEXT IS NULL OR notebook_id IN (
+                  WITH RECURSIVE matched AS (
+                      SELECT id FROM notebooks WHERE user_id = $1 AND lower(name) = lower($3)
+                      UNION
+                      SELECT child.id FROM notebooks child JOIN matched ON child.parent_id = matched.id
+                  )
+                  SELECT id FROM matched
+              ))
+            ORDER BY created_at ASC
+            "#,
+            user_id,
+            tag,
+            notebook
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(nodes)
+    }
+
+    // Links between the user's notes, unresolved links have nothing to point at
+    pub async fn get_graph_edges(&self, user_id: Uuid) -> Result<Vec<GraphEdge>> {
+        let edges = sqlx::query_as!(
+            GraphEdge,
+            r#"
+            SELECT DISTINCT
+                source_note_id AS "source!",
+                resolved_note_id AS "target!"
+            FROM resolved_note_links
+            WHERE user_id = $1 AND resolved_note_id IS NOT NULL
+            ORDER BY 1, 2
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(edges)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,216 @@
+use sqlx::{PgConnection, PgPool};
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{NewNotebook, Notebook, UpdateNotebook};
+
+pub struct NotebookRepository {
+    pool: PgPool,
+}
+
+impl NotebookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_notebook_by_id(&self, notebook_id: Uuid, user_id: Uuid) -> Result<Option<Notebook>> {
+        let mut conn = self.pool.acquire().await?;
+        self.get_notebook_by_id_in(&mut conn, notebook_id, user_id).await
+    }
+
+    pub async fn get_notebook_by_id_in(
+        &self,
+        conn: &mut PgConnection,
+        notebook_id: Uuid,
+        user_id: Uuid
+    ) -> Result<Option<Notebook>> {
+        let notebook = sqlx::query_as!(
+            Notebook,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                parent_id, 
+                name, 
+                created_at, 
+                updated_at
+            FROM notebooks 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            notebook_id,
+            user_id
+        )
+            .fetch_optional(conn)
+            .await?;
+
+        Ok(notebook)
+    }
+
+    pub async fn get_user_notebooks(&self, user_id: Uuid) -> Result<Vec<Notebook>> {
+        let notebooks = sqlx::query_as!(
+            Notebook,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                parent_id, 
+                name, 
+                created_at, 
+                updated_at
+            FROM notebooks 
+            WHERE user_id = $1
+            ORDER BY lower(name) ASC
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(notebooks)
+    }
+
+    // Sibling names are compared ignoring case, like the unique index does
+    pub async fn find_by_name(&self, user_id: Uuid, parent_id: Option<Uuid>, name: &str) -> Result<Option<Notebook>> {
+        let notebook = sqlx::query_as!(
+            Notebook,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                parent_id, 
+                name, 
+                created_at, 
+                updated_at
+            FROM notebooks 
+            WHERE user_id = $1 AND parent_id IS NOT DISTINCT FROM $2 AND lower(name) = lower($3)
+            "#,
+            user_id,
+            parent_id,
+            name
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(notebook)
+    }
+
+    // True when `candidate_id` is the notebook itself or nested somewhere inside it
+    pub async fn is_within(&self, notebook_id: Uuid, candidate_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let within = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM notebooks WHERE id = $1 AND user_id = $3
+                UNION
+                SELECT child.id FROM notebooks child JOIN subtree ON child.parent_id = subtree.id
+            )
+            SELECT EXISTS (SELECT 1 FROM subtree WHERE id = $2) AS "within!"
+            "#,
+            notebook_id,
+            candidate_id,
+            user_id
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(within)
+    }
+
+    pub async fn create_notebook(&self, new_notebook: NewNotebook) -> Result<Notebook> {
+        let notebook = sqlx::query_as!(
+            Notebook,
+            r#"
+            INSERT INTO notebooks (user_id, parent_id, name)
+            VALUES ($1, $2, $3)
+            RETURNING 
+                id, 
+                user_id, 
+                parent_id, 
+                name, 
+                created_at, 
+                updated_at
+            "#,
+            new_notebook.user_id,
+            new_notebook.parent_id,
+            new_notebook.name
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(notebook)
+    }
+
+    // Notebook with this name under the parent, created when missing
+    pub async fn find_or_create(&self, user_id: Uuid, parent_id: Option<Uuid>, name: &str) -> Result<Uuid> {
+        let notebook_id = sqlx::query_scalar!(
+            r#"
+            WITH inserted AS (
+                INSERT INTO notebooks (user_id, parent_id, name)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, parent_id, lower(name)) DO NOTHING
+                RETURNING id
+            )
+            SELECT id AS "id!" FROM inserted
+            UNION ALL
+            SELECT id FROM notebooks 
+            WHERE user_id = $1 AND parent_id IS NOT DISTINCT FROM $2 AND lower(name) = lower($3)
+            LIMIT 1
+            "#,
+            user_id,
+            parent_id,
+            name
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(notebook_id)
+    }
+
+    pub async fn update_notebook(
+        &self,
+        notebook_id: Uuid,
+        user_id: Uuid,
+        update: UpdateNotebook
+    ) -> Result<Option<Notebook>> {
+        let notebook = sqlx::query_as!(
+            Notebook,
+            r#"
+            UPDATE notebooks
+            SET 
+                name = COALESCE($3, name),
+                parent_id = CASE WHEN $4 THEN $5 ELSE parent_id END,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING 
+                id, 
+                user_id, 
+                parent_id, 
+                name, 
+                created_at, 
+                updated_at
+            "#,
+            notebook_id,
+            user_id,
+            update.name,
+            update.parent_id.is_some(),
+            update.parent_id.flatten()
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(notebook)
+    }
+
+    pub async fn delete_notebook(&self, notebook_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM notebooks 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            notebook_id,
+            user_id
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
\ No newline at end of file
@@ -1,7 +1,9 @@
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, Transaction};
 use anyhow::Result;
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
 use uuid::Uuid;
-use crate::models::{Note, NewNote, UpdateNote};
+use crate::models::{Note, NewNote, NoteFlags, NoteLanguage, NoteSort, UpdateNote, SearchHit, SearchMode, SearchOptions};
+use crate::utils::{detect_language, prefix_tsquery, SearchField, SearchNode, SearchQuery, TextMatch};
 
 pub struct NoteRepository {
     pool: PgPool,
@@ -13,6 +15,16 @@ impl NoteRepository {
     }
 
     pub async fn get_note_by_id(&self, note_id: Uuid, user_id: Uuid) -> Result<Option<Note>> {
+        let mut conn = self.pool.acquire().await?;
+        self.get_note_by_id_in(&mut conn, note_id, user_id).await
+    }
+
+    pub async fn get_note_by_id_in(
+        &self,
+        conn: &mut PgConnection,
+        note_id: Uuid,
+        user_id: Uuid
+    ) -> Result<Option<Note>> {
         let note = sqlx::query_as!(
             Note,
             r#"
@@ -21,13 +33,55 @@ impl NoteRepository {
                 user_id, 
                 title, 
                 content, 
+                language AS "language: NoteLanguage",
                 created_at, 
-                updated_at
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
             FROM notes 
             WHERE id = $1 AND user_id = $2
             "#,
             note_id,
             user_id
+        )
+            .fetch_optional(conn)
+            .await?;
+
+        Ok(note)
+    }
+
+    // A note someone else owns, when it's shared with the user
+    pub async fn get_shared_note(&self, note_id: Uuid, user_id: Uuid) -> Result<Option<Note>> {
+        let note = sqlx::query_as!(
+            Note,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            FROM notes 
+            WHERE id = $1 AND id IN (SELECT note_id FROM note_shares WHERE user_id = $2)
+            "#,
+            note_id,
+            user_id
         )
             .fetch_optional(&self.pool)
             .await?;
@@ -35,7 +89,46 @@ impl NoteRepository {
         Ok(note)
     }
 
-    pub async fn get_user_notes(&self, user_id: Uuid, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Note>> {
+    pub async fn get_shared_notes(&self, user_id: Uuid) -> Result<Vec<Note>> {
+        let notes = sqlx::query_as!(
+            Note,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            FROM notes 
+            WHERE id IN (SELECT note_id FROM note_shares WHERE user_id = $1)
+            ORDER BY updated_at DESC
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(notes)
+    }
+
+    pub async fn get_user_notes(
+        &self,
+        user_id: Uuid,
+        sort: NoteSort,
+        include_archived: bool,
+        limit: Option<i64>,
+        offset: Option<i64>
+    ) -> Result<Vec<Note>> {
         let limit = limit.unwrap_or(50);
         let offset = offset.unwrap_or(0);
 
@@ -47,16 +140,32 @@ impl NoteRepository {
                 user_id, 
                 title, 
                 content, 
+                language AS "language: NoteLanguage",
                 created_at, 
-                updated_at
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
             FROM notes
-            WHERE user_id = $1
-            ORDER BY created_at DESC
+            WHERE user_id = $1 AND ($5 OR archived_at IS NULL)
+            ORDER BY
+                pinned DESC,
+                CASE WHEN $4 = 'oldest' THEN created_at END ASC,
+                CASE WHEN $4 = 'recently_updated' THEN updated_at END DESC,
+                CASE WHEN $4 = 'title' THEN title END ASC,
+                created_at DESC
             LIMIT $2 OFFSET $3
             "#,
             user_id,
             limit,
-            offset
+            offset,
+            sort.as_str(),
+            include_archived
         )
             .fetch_all(&self.pool)
             .await?;
@@ -64,31 +173,157 @@ impl NoteRepository {
         Ok(notes)
     }
 
+    // Lets several writes share one transaction, see the *_in methods
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>> {
+        Ok(self.pool.begin().await?)
+    }
+
     pub async fn create_note(&self, new_note: NewNote) -> Result<Note> {
+        let mut conn = self.pool.acquire().await?;
+        self.create_note_in(&mut conn, new_note).await
+    }
+
+    pub async fn create_note_in(&self, conn: &mut PgConnection, new_note: NewNote) -> Result<Note> {
         let note = sqlx::query_as!(
             Note,
             r#"
-            INSERT INTO notes (user_id, title, content)
-            VALUES ($1, $2, $3)
+            INSERT INTO notes (user_id, title, content, language, language_detected, due_at, remind_at, notebook_id, tags)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING 
                 id, 
                 user_id, 
                 title, 
                 content, 
+                language AS "language: NoteLanguage",
                 created_at, 
-                updated_at
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
             "#,
             new_note.user_id,
             new_note.title,
-            new_note.content
+            new_note.content,
+            new_note.language.as_str(),
+            new_note.language_detected,
+            new_note.due_at,
+            new_note.remind_at,
+            new_note.notebook_id,
+            &new_note.tags
         )
-            .fetch_one(&self.pool)
+            .fetch_one(conn)
+            .await?;
+
+        Ok(note)
+    }
+
+    // Imported notes keep the timestamps they had in the app they came from
+    pub async fn create_imported_note(
+        &self,
+        new_note: NewNote,
+        created_at: Option<DateTime<Utc>>,
+        updated_at: Option<DateTime<Utc>>
+    ) -> Result<Note> {
+        let mut conn = self.pool.acquire().await?;
+        self.create_imported_note_in(&mut conn, new_note, created_at, updated_at).await
+    }
+
+    pub async fn create_imported_note_in(
+        &self,
+        conn: &mut PgConnection,
+        new_note: NewNote,
+        created_at: Option<DateTime<Utc>>,
+        updated_at: Option<DateTime<Utc>>
+    ) -> Result<Note> {
+        let note = sqlx::query_as!(
+            Note,
+            r#"
+            INSERT INTO notes (user_id, title, content, language, language_detected, created_at, updated_at, notebook_id, tags)
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, NOW()), COALESCE($7, $6, NOW()), $8, $9)
+            RETURNING 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            "#,
+            new_note.user_id,
+            new_note.title,
+            new_note.content,
+            new_note.language.as_str(),
+            new_note.language_detected,
+            created_at,
+            updated_at,
+            new_note.notebook_id,
+            &new_note.tags
+        )
+            .fetch_one(conn)
             .await?;
 
         Ok(note)
     }
 
     pub async fn update_note(&self, note_id: Uuid, user_id: Uuid, update_note: UpdateNote) -> Result<Option<Note>> {
+        let mut conn = self.pool.acquire().await?;
+        self.update_note_in(&mut conn, note_id, user_id, update_note).await
+    }
+
+    // Notes whose language no client picked get it detected again from the title and
+    // content they end up with, None keeps the language as it is
+    async fn redetect_language_in(
+        &self,
+        conn: &mut PgConnection,
+        note_id: Uuid,
+        user_id: Uuid,
+        update_note: &UpdateNote
+    ) -> Result<Option<NoteLanguage>> {
+        if update_note.language.is_some() || (update_note.title.is_none() && update_note.content.is_none()) {
+            return Ok(None);
+        }
+
+        let current = sqlx::query!(
+            r#"
+            SELECT title, content 
+            FROM notes 
+            WHERE id = $1 AND user_id = $2 AND language_detected
+            "#,
+            note_id,
+            user_id
+        )
+            .fetch_optional(conn)
+            .await?;
+
+        Ok(current.map(|current| {
+            let title = update_note.title.as_deref().unwrap_or(&current.title);
+            let content = update_note.content.as_deref().unwrap_or(&current.content);
+            detect_language(&format!("{} {}", title, content))
+        }))
+    }
+
+    pub async fn update_note_in(
+        &self,
+        conn: &mut PgConnection,
+        note_id: Uuid,
+        user_id: Uuid,
+        update_note: UpdateNote
+    ) -> Result<Option<Note>> {
+        let detected = self.redetect_language_in(conn, note_id, user_id, &update_note).await?;
+
         let note = sqlx::query_as!(
             Note,
             r#"
@@ -96,6 +331,12 @@ impl NoteRepository {
             SET 
                 title = COALESCE($3, title),
                 content = COALESCE($4, content),
+                language = COALESCE($5, $13, language),
+                language_detected = language_detected AND $5::TEXT IS NULL,
+                due_at = CASE WHEN $6 THEN $7 ELSE due_at END,
+                remind_at = CASE WHEN $8 THEN $9 ELSE remind_at END,
+                notebook_id = CASE WHEN $10 THEN $11 ELSE notebook_id END,
+                tags = COALESCE($12, tags),
                 updated_at = NOW()
             WHERE id = $1 AND user_id = $2
             RETURNING 
@@ -103,38 +344,209 @@ impl NoteRepository {
                 user_id, 
                 title, 
                 content, 
+                language AS "language: NoteLanguage",
                 created_at, 
-                updated_at
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
             "#,
             note_id,
             user_id,
             update_note.title,
-            update_note.content
+            update_note.content,
+            update_note.language.map(|language| language.as_str()),
+            update_note.due_at.is_some(),
+            update_note.due_at.flatten(),
+            update_note.remind_at.is_some(),
+            update_note.remind_at.flatten(),
+            update_note.notebook_id.is_some(),
+            update_note.notebook_id.flatten(),
+            update_note.tags.as_deref(),
+            detected.map(|language| language.as_str())
         )
-            .fetch_optional(&self.pool)
+            .fetch_optional(conn)
             .await?;
 
         Ok(note)
     }
 
-    pub async fn delete_note(&self, note_id: Uuid, user_id: Uuid) -> Result<bool> {
-        let result = sqlx::query!(
+    // Creates a note under an id the client picked while offline, None when the id is taken
+    pub async fn create_synced_note(&self, note_id: Uuid, new_note: NewNote) -> Result<Option<Note>> {
+        let mut conn = self.pool.acquire().await?;
+        self.create_synced_note_in(&mut conn, note_id, new_note).await
+    }
+
+    pub async fn create_synced_note_in(
+        &self,
+        conn: &mut PgConnection,
+        note_id: Uuid,
+        new_note: NewNote
+    ) -> Result<Option<Note>> {
+        let note = sqlx::query_as!(
+            Note,
             r#"
-            DELETE FROM notes 
-            WHERE id = $1 AND user_id = $2
+            INSERT INTO notes (id, user_id, title, content, language, language_detected, notebook_id, tags)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
             "#,
             note_id,
-            user_id
+            new_note.user_id,
+            new_note.title,
+            new_note.content,
+            new_note.language.as_str(),
+            new_note.language_detected,
+            new_note.notebook_id,
+            &new_note.tags
         )
-            .execute(&self.pool)
+            .fetch_optional(conn)
             .await?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(note)
     }
 
-    pub async fn search_notes(&self, user_id: Uuid, query: &str, limit: Option<i64>) -> Result<Vec<Note>> {
-        let limit = limit.unwrap_or(50);
+    // Only updates a note still at base_version, None when it moved on or is gone
+    pub async fn update_note_if_version(
+        &self,
+        note_id: Uuid,
+        user_id: Uuid,
+        base_version: i64,
+        update_note: UpdateNote
+    ) -> Result<Option<Note>> {
+        let mut conn = self.pool.acquire().await?;
+        self.update_note_if_version_in(&mut conn, note_id, user_id, base_version, update_note).await
+    }
+
+    pub async fn update_note_if_version_in(
+        &self,
+        conn: &mut PgConnection,
+        note_id: Uuid,
+        user_id: Uuid,
+        base_version: i64,
+        update_note: UpdateNote
+    ) -> Result<Option<Note>> {
+        let detected = self.redetect_language_in(conn, note_id, user_id, &update_note).await?;
+
+        let note = sqlx::query_as!(
+            Note,
+            r#"
+            UPDATE notes
+            SET 
+                title = COALESCE($4, title),
+                content = COALESCE($5, content),
+                language = COALESCE($6, $14, language),
+                language_detected = language_detected AND $6::TEXT IS NULL,
+                due_at = CASE WHEN $7 THEN $8 ELSE due_at END,
+                remind_at = CASE WHEN $9 THEN $10 ELSE remind_at END,
+                notebook_id = CASE WHEN $11 THEN $12 ELSE notebook_id END,
+                tags = COALESCE($13, tags),
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND version = $3
+            RETURNING 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            "#,
+            note_id,
+            user_id,
+            base_version,
+            update_note.title,
+            update_note.content,
+            update_note.language.map(|language| language.as_str()),
+            update_note.due_at.is_some(),
+            update_note.due_at.flatten(),
+            update_note.remind_at.is_some(),
+            update_note.remind_at.flatten(),
+            update_note.notebook_id.is_some(),
+            update_note.notebook_id.flatten(),
+            update_note.tags.as_deref(),
+            detected.map(|language| language.as_str())
+        )
+            .fetch_optional(conn)
+            .await?;
+
+        Ok(note)
+    }
+
+    // Doesn't touch updated_at, pinning or archiving a note isn't editing it
+    pub async fn set_note_flags(&self, note_id: Uuid, user_id: Uuid, flags: NoteFlags) -> Result<Option<Note>> {
+        let note = sqlx::query_as!(
+            Note,
+            r#"
+            UPDATE notes
+            SET 
+                pinned = COALESCE($3, pinned),
+                favorite = COALESCE($4, favorite),
+                archived_at = CASE 
+                    WHEN $5::BOOLEAN IS NULL THEN archived_at 
+                    WHEN $5 THEN COALESCE(archived_at, NOW()) 
+                    ELSE NULL 
+                END
+            WHERE id = $1 AND user_id = $2
+            RETURNING 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            "#,
+            note_id,
+            user_id,
+            flags.pinned,
+            flags.favorite,
+            flags.archived
+        )
+            .fetch_optional(&self.pool)
+            .await?;
 
+        Ok(note)
+    }
+
+    // Notes due between now and `until`, soonest first
+    pub async fn get_upcoming_notes(&self, user_id: Uuid, until: DateTime<Utc>, limit: i64) -> Result<Vec<Note>> {
         let notes = sqlx::query_as!(
             Note,
             r#"
@@ -143,18 +555,59 @@ impl NoteRepository {
                 user_id, 
                 title, 
                 content, 
+                language AS "language: NoteLanguage",
                 created_at, 
-                updated_at
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
             FROM notes
-            WHERE 
-                user_id = $1 
-                AND to_tsvector('english', title || ' ' || content) @@ plainto_tsquery('english', $2)
-            ORDER BY 
-                ts_rank(to_tsvector('english', title || ' ' || content), plainto_tsquery('english', $2)) DESC
+            WHERE user_id = $1 AND due_at >= NOW() AND due_at <= $2 AND archived_at IS NULL
+            ORDER BY due_at ASC
             LIMIT $3
             "#,
             user_id,
-            query,
+            until,
+            limit
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(notes)
+    }
+
+    // Notes past their due date, the longest overdue first
+    pub async fn get_overdue_notes(&self, user_id: Uuid, limit: i64) -> Result<Vec<Note>> {
+        let notes = sqlx::query_as!(
+            Note,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            FROM notes
+            WHERE user_id = $1 AND due_at < NOW() AND archived_at IS NULL
+            ORDER BY due_at ASC
+            LIMIT $2
+            "#,
+            user_id,
             limit
         )
             .fetch_all(&self.pool)
@@ -162,4 +615,426 @@ impl NoteRepository {
 
         Ok(notes)
     }
-}
\ No newline at end of file
+
+    // Everything that shows up in the calendar feed
+    pub async fn get_scheduled_notes(&self, user_id: Uuid) -> Result<Vec<Note>> {
+        let notes = sqlx::query_as!(
+            Note,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            FROM notes
+            WHERE user_id = $1 AND (due_at IS NOT NULL OR remind_at IS NOT NULL) AND archived_at IS NULL
+            ORDER BY COALESCE(due_at, remind_at) ASC
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(notes)
+    }
+
+    // Marks every due reminder as sent and announces it, in one statement so each
+    // reminder goes out once even with several workers. Returns how many were sent.
+    pub async fn send_due_reminders(&self) -> Result<u64> {
+        let sent = sqlx::query_scalar!(
+            r#"
+            WITH due AS (
+                INSERT INTO sent_reminders (note_id, remind_at)
+                SELECT id, remind_at
+                FROM notes
+                WHERE remind_at <= NOW() AND archived_at IS NULL
+                ON CONFLICT (note_id, remind_at) DO NOTHING
+                RETURNING note_id
+            ),
+            reminders AS (
+                SELECT 
+                    n.id, 
+                    n.user_id, 
+                    n.version,
+                    note_webhook_payload(n) AS data
+                FROM notes n
+                JOIN due ON due.note_id = n.id
+            ),
+            deliveries AS (
+                INSERT INTO webhook_deliveries (webhook_id, event, payload)
+                SELECT 
+                    w.id, 
+                    'note.reminder', 
+                    json_build_object('type', 'note.reminder', 'at', NOW(), 'user_id', r.user_id, 'note', r.data)
+                FROM reminders r
+                JOIN webhooks w ON w.user_id = r.user_id
+                WHERE w.active AND 'note.reminder' = ANY(w.events)
+            )
+            SELECT COUNT(pg_notify('note_events', json_build_object(
+                'type', 'note.reminder',
+                'user_id', user_id,
+                'note_id', id,
+                'version', version,
+                'at', NOW()
+            )::text)) AS "sent!"
+            FROM reminders
+            "#
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(sent as u64)
+    }
+
+    pub async fn delete_note_if_version(&self, note_id: Uuid, user_id: Uuid, base_version: i64) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM notes 
+            WHERE id = $1 AND user_id = $2 AND version = $3
+            "#,
+            note_id,
+            user_id,
+            base_version
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_note(&self, note_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let mut conn = self.pool.acquire().await?;
+        self.delete_note_in(&mut conn, note_id, user_id).await
+    }
+
+    pub async fn delete_note_in(&self, conn: &mut PgConnection, note_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM notes 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            note_id,
+            user_id
+        )
+            .execute(conn)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn search_notes(
+        &self,
+        user_id: Uuid,
+        query: &SearchQuery,
+        options: &SearchOptions,
+        limit: Option<i64>,
+        offset: Option<i64>
+    ) -> Result<Vec<SearchHit>> {
+        let limit = limit.unwrap_or(50);
+        let offset = offset.unwrap_or(0);
+
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, user_id, title, content, language, created_at, updated_at, version, due_at, remind_at, pinned, favorite, archived_at, notebook_id, tags, "
+        );
+        push_score(&mut builder, query, options.mode);
+        builder.push(" AS score, ts_headline(language::regconfig, ").push(html_escaped("title")).push(", ");
+        push_rank_tsquery(&mut builder, &query.nodes);
+        builder.push(", ").push_bind(options.markers.title_options());
+        builder.push(") AS title_snippet, ts_headline(language::regconfig, ").push(html_escaped("content")).push(", ");
+        push_rank_tsquery(&mut builder, &query.nodes);
+        builder.push(", ").push_bind(options.markers.content_options());
+        builder.push(") AS content_snippet FROM notes WHERE ");
+        push_search_filter(&mut builder, user_id, query, options.mode, options.include_archived);
+        builder
+            .push(" ORDER BY ")
+            .push(order_by(options.sort))
+            .push(" LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let mut tx = self.pool.begin().await?;
+        set_fuzzy_threshold(&mut tx, options.mode).await?;
+
+        let hits = builder
+            .build_query_as::<SearchHit>()
+            .fetch_all(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(hits)
+    }
+
+    pub async fn count_search_results(&self, user_id: Uuid, query: &SearchQuery, options: &SearchOptions) -> Result<i64> {
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM notes WHERE ");
+        push_search_filter(&mut builder, user_id, query, options.mode, options.include_archived);
+
+        let mut tx = self.pool.begin().await?;
+        set_fuzzy_threshold(&mut tx, options.mode).await?;
+
+        let total = builder
+            .build_query_scalar::<i64>()
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(total)
+    }
+}
+
+// ===== SEARCH QUERY COMPILATION =====
+
+// Stored vector with the title weighted A and the content weighted B
+const ALL_FIELDS_DOCUMENT: &str = "search_vector";
+const TITLE_DOCUMENT: &str = "ts_filter(search_vector, '{a}')";
+
+// Every note language's configuration, query terms are stemmed with each of them
+// so a single tsquery matches notes in any language and can use the GIN index
+const SEARCH_CONFIGS: [NoteLanguage; 4] = [
+    NoteLanguage::English,
+    NoteLanguage::German,
+    NoteLanguage::Spanish,
+    NoteLanguage::Simple,
+];
+
+// pg_trgm's default of 0.6 misses most single typos in short words
+const FUZZY_WORD_SIMILARITY_THRESHOLD: &str = "0.3";
+// Weights of the full-text rank and the trigram similarity in the fuzzy score
+const FULLTEXT_WEIGHT: f32 = 0.6;
+const TRIGRAM_WEIGHT: f32 = 0.4;
+
+// ts_headline copies the text around the matches verbatim, snippets are escaped so
+// they can be rendered as HTML between the <mark> markers
+fn html_escaped(column: &str) -> String {
+    format!(
+        "replace(replace(replace(replace(replace({}, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), '\"', '&quot;'), '''', '&#39;')",
+        column
+    )
+}
+
+fn order_by(sort: NoteSort) -> &'static str {
+    match sort {
+        NoteSort::Relevance => "score DESC, created_at DESC",
+        NoteSort::Newest => "created_at DESC",
+        NoteSort::Oldest => "created_at ASC",
+        NoteSort::RecentlyUpdated => "updated_at DESC",
+        NoteSort::Title => "title ASC, created_at DESC",
+    }
+}
+
+// Lower the threshold used by the <% operator for the rest of the transaction
+async fn set_fuzzy_threshold(tx: &mut Transaction<'_, Postgres>, mode: SearchMode) -> Result<()> {
+    if mode == SearchMode::Fuzzy {
+        sqlx::query!(
+            "SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)",
+            FUZZY_WORD_SIMILARITY_THRESHOLD
+        )
+            .fetch_one(&mut **tx)
+            .await?;
+    }
+    Ok(())
+}
+
+fn push_tsquery(builder: &mut QueryBuilder<Postgres>, text: &TextMatch) {
+    builder.push("(");
+    for (index, config) in SEARCH_CONFIGS.iter().enumerate() {
+        if index > 0 {
+            builder.push(" || ");
+        }
+        match text {
+            TextMatch::Word(word) => {
+                builder
+                    .push(format!("plainto_tsquery('{}', ", config.as_str()))
+                    .push_bind(word.clone())
+                    .push(")");
+            }
+            TextMatch::Phrase(phrase) => {
+                builder
+                    .push(format!("phraseto_tsquery('{}', ", config.as_str()))
+                    .push_bind(phrase.clone())
+                    .push(")");
+            }
+            TextMatch::Prefix(prefix) => {
+                builder
+                    .push(format!("to_tsquery('{}', ", config.as_str()))
+                    .push_bind(prefix_tsquery(prefix))
+                    .push(")");
+            }
+        }
+    }
+    builder.push(")");
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    date.and_time(NaiveTime::MIN).and_utc()
+}
+
+fn push_condition(builder: &mut QueryBuilder<Postgres>, user_id: Uuid, node: &SearchNode, fuzzy: bool) {
+    match node {
+        SearchNode::Text { field, text } => {
+            let document = match field {
+                SearchField::All => ALL_FIELDS_DOCUMENT,
+                SearchField::Title => TITLE_DOCUMENT,
+            };
+            builder.push("(").push(document).push(" @@ ");
+            push_tsquery(builder, text);
+
+            // in fuzzy mode single words also match on trigram similarity
+            match text {
+                TextMatch::Word(word) | TextMatch::Prefix(word) if fuzzy => {
+                    builder.push(" OR ").push_bind(word.clone()).push(" <% title");
+                    if *field == SearchField::All {
+                        builder.push(" OR ").push_bind(word.clone()).push(" <% content");
+                    }
+                }
+                _ => {}
+            }
+            builder.push(")");
+        }
+        SearchNode::Before(date) => {
+            builder.push("created_at < ").push_bind(start_of_day(*date));
+        }
+        SearchNode::After(date) => {
+            builder.push("created_at >= ").push_bind(start_of_day(*date + Days::new(1)));
+        }
+        SearchNode::Not(inner) => {
+            // exclusions stay exact, otherwise -rust would also drop notes about trust
+            builder.push("NOT (");
+            push_condition(builder, user_id, inner, false);
+            builder.push(")");
+        }
+        SearchNode::Or(alternatives) => {
+            builder.push("(");
+            for (index, alternative) in alternatives.iter().enumerate() {
+                if index > 0 {
+                    builder.push(" OR ");
+                }
+                push_condition(builder, user_id, alternative, fuzzy);
+            }
+            builder.push(")");
+        }
+        SearchNode::Tag(tag) => {
+            // containment so the GIN index on tags can be used
+            builder.push("tags @> ARRAY[").push_bind(tag.clone()).push("]::TEXT[]");
+        }
+        SearchNode::Notebook(name) => {
+            // every notebook with that name plus the ones nested inside them
+            builder
+                .push("notebook_id IN (WITH RECURSIVE matched AS (SELECT id FROM notebooks WHERE user_id = ")
+                .push_bind(user_id)
+                .push(" AND lower(name) = lower(")
+                .push_bind(name.clone())
+                .push(") UNION SELECT child.id FROM notebooks child JOIN matched ON child.parent_id = matched.id) SELECT id FROM matched)");
+        }
+    }
+}
+
+fn push_search_filter(
+    builder: &mut QueryBuilder<Postgres>,
+    user_id: Uuid,
+    query: &SearchQuery,
+    mode: SearchMode,
+    include_archived: bool
+) {
+    builder.push("user_id = ").push_bind(user_id);
+    if !include_archived {
+        builder.push(" AND archived_at IS NULL");
+    }
+    for node in &query.nodes {
+        builder.push(" AND ");
+        push_condition(builder, user_id, node, mode == SearchMode::Fuzzy);
+    }
+}
+
+// Collect the positive text terms into a single tsquery used for ranking and headlines
+fn collect_rank_terms<'a>(nodes: &'a [SearchNode], terms: &mut Vec<Vec<&'a TextMatch>>) {
+    for node in nodes {
+        match node {
+            SearchNode::Text { text, .. } => terms.push(vec![text]),
+            SearchNode::Or(alternatives) => {
+                let texts: Vec<&TextMatch> = alternatives
+                    .iter()
+                    .filter_map(|alternative| match alternative {
+                        SearchNode::Text { text, .. } => Some(text),
+                        _ => None,
+                    })
+                    .collect();
+                if !texts.is_empty() {
+                    terms.push(texts);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn push_rank_tsquery(builder: &mut QueryBuilder<Postgres>, nodes: &[SearchNode]) {
+    let mut terms = Vec::new();
+    collect_rank_terms(nodes, &mut terms);
+
+    if terms.is_empty() {
+        builder.push("''::tsquery");
+        return;
+    }
+
+    builder.push("(");
+    for (index, alternatives) in terms.iter().enumerate() {
+        if index > 0 {
+            builder.push(" && ");
+        }
+        builder.push("(");
+        for (alt_index, text) in alternatives.iter().enumerate() {
+            if alt_index > 0 {
+                builder.push(" || ");
+            }
+            push_tsquery(builder, text);
+        }
+        builder.push(")");
+    }
+    builder.push(")");
+}
+
+// Full-text rank, blended with the best trigram similarity of title or content in fuzzy mode
+fn push_score(builder: &mut QueryBuilder<Postgres>, query: &SearchQuery, mode: SearchMode) {
+    builder.push("(ts_rank(").push(ALL_FIELDS_DOCUMENT).push(", ");
+    push_rank_tsquery(builder, &query.nodes);
+    builder.push(")");
+
+    if mode == SearchMode::Fuzzy {
+        let mut terms = Vec::new();
+        collect_rank_terms(&query.nodes, &mut terms);
+        let text = terms
+            .iter()
+            .flatten()
+            .map(|text| match text {
+                TextMatch::Word(value) | TextMatch::Phrase(value) | TextMatch::Prefix(value) => value.as_str(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        builder
+            .push(" * ")
+            .push_bind(FULLTEXT_WEIGHT)
+            .push(" + GREATEST(word_similarity(")
+            .push_bind(text.clone())
+            .push(", title), word_similarity(")
+            .push_bind(text)
+            .push(", content)) * ")
+            .push_bind(TRIGRAM_WEIGHT);
+    }
+
+    builder.push(")::real");
+}
@@ -0,0 +1,172 @@
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{NewSavedSearch, NoteSort, SavedSearch, SearchMode, UpdateSavedSearch};
+
+pub struct SavedSearchRepository {
+    pool: PgPool,
+}
+
+impl SavedSearchRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_saved_search_by_id(&self, saved_search_id: Uuid, user_id: Uuid) -> Result<Option<SavedSearch>> {
+        let saved_search = sqlx::query_as!(
+            SavedSearch,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                name, 
+                query, 
+                mode AS "mode: SearchMode",
+                sort AS "sort: NoteSort",
+                created_at, 
+                updated_at
+            FROM saved_searches 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            saved_search_id,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(saved_search)
+    }
+
+    pub async fn get_user_saved_searches(&self, user_id: Uuid) -> Result<Vec<SavedSearch>> {
+        let saved_searches = sqlx::query_as!(
+            SavedSearch,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                name, 
+                query, 
+                mode AS "mode: SearchMode",
+                sort AS "sort: NoteSort",
+                created_at, 
+                updated_at
+            FROM saved_searches 
+            WHERE user_id = $1
+            ORDER BY name ASC
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(saved_searches)
+    }
+
+    pub async fn find_by_name(&self, user_id: Uuid, name: &str) -> Result<Option<SavedSearch>> {
+        let saved_search = sqlx::query_as!(
+            SavedSearch,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                name, 
+                query, 
+                mode AS "mode: SearchMode",
+                sort AS "sort: NoteSort",
+                created_at, 
+                updated_at
+            FROM saved_searches 
+            WHERE user_id = $1 AND name = $2
+            "#,
+            user_id,
+            name
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(saved_search)
+    }
+
+    pub async fn create_saved_search(&self, new_saved_search: NewSavedSearch) -> Result<SavedSearch> {
+        let saved_search = sqlx::query_as!(
+            SavedSearch,
+            r#"
+            INSERT INTO saved_searches (user_id, name, query, mode, sort)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING 
+                id, 
+                user_id, 
+                name, 
+                query, 
+                mode AS "mode: SearchMode",
+                sort AS "sort: NoteSort",
+                created_at, 
+                updated_at
+            "#,
+            new_saved_search.user_id,
+            new_saved_search.name,
+            new_saved_search.query,
+            new_saved_search.mode.as_str(),
+            new_saved_search.sort.as_str()
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(saved_search)
+    }
+
+    pub async fn update_saved_search(
+        &self,
+        saved_search_id: Uuid,
+        user_id: Uuid,
+        update: UpdateSavedSearch
+    ) -> Result<Option<SavedSearch>> {
+        let saved_search = sqlx::query_as!(
+            SavedSearch,
+            r#"
+            UPDATE saved_searches
+            SET 
+                name = COALESCE($3, name),
+                query = COALESCE($4, query),
+                mode = COALESCE($5, mode),
+                sort = COALESCE($6, sort),
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING 
+                id, 
+                user_id, 
+                name, 
+                query, 
+                mode AS "mode: SearchMode",
+                sort AS "sort: NoteSort",
+                created_at, 
+                updated_at
+            "#,
+            saved_search_id,
+            user_id,
+            update.name,
+            update.query,
+            update.mode.map(|mode| mode.as_str()),
+            update.sort.map(|sort| sort.as_str())
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(saved_search)
+    }
+
+    pub async fn delete_saved_search(&self, saved_search_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM saved_searches 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            saved_search_id,
+            user_id
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{NoteShare, SharePermission};
+
+pub struct ShareRepository {
+    pool: PgPool,
+}
+
+impl ShareRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_note_shares(&self, note_id: Uuid) -> Result<Vec<NoteShare>> {
+        let shares = sqlx::query_as!(
+            NoteShare,
+            r#"
+            SELECT 
+                note_shares.note_id, 
+                note_shares.user_id, 
+                users.username, 
+                note_shares.permission AS "permission: SharePermission", 
+                note_shares.created_at, 
+                note_shares.updated_at
+            FROM note_shares 
+            JOIN users ON users.id = note_shares.user_id
+            WHERE note_shares.note_id = $1
+            ORDER BY users.username
+            "#,
+            note_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(shares)
+    }
+
+    // None when the note isn't shared with the user
+    pub async fn get_permission(&self, note_id: Uuid, user_id: Uuid) -> Result<Option<SharePermission>> {
+        let permission = sqlx::query_scalar!(
+            r#"
+            SELECT permission AS "permission: SharePermission"
+            FROM note_shares 
+            WHERE note_id = $1 AND user_id = $2
+            "#,
+            note_id,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(permission)
+    }
+
+    // What the user may do with each note shared with them
+    pub async fn get_user_permissions(&self, user_id: Uuid) -> Result<HashMap<Uuid, SharePermission>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT 
+                note_id, 
+                permission AS "permission: SharePermission"
+            FROM note_shares 
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| (row.note_id, row.permission)).collect())
+    }
+
+    // Sharing a note again with the same user changes the permission
+    pub async fn share_note(&self, note_id: Uuid, user_id: Uuid, permission: SharePermission) -> Result<NoteShare> {
+        let share = sqlx::query_as!(
+            NoteShare,
+            r#"
+            WITH share AS (
+                INSERT INTO note_shares (note_id, user_id, permission)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (note_id, user_id) DO UPDATE
+                    SET permission = EXCLUDED.permission, updated_at = NOW()
+                RETURNING *
+            )
+            SELECT 
+                share.note_id, 
+                share.user_id, 
+                users.username, 
+                share.permission AS "permission: SharePermission", 
+                share.created_at, 
+                share.updated_at
+            FROM share 
+            JOIN users ON users.id = share.user_id
+            "#,
+            note_id,
+            user_id,
+            permission.as_str()
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(share)
+    }
+
+    pub async fn delete_share(&self, note_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM note_shares 
+            WHERE note_id = $1 AND user_id = $2
+            "#,
+            note_id,
+            user_id
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
\ No newline at end of file
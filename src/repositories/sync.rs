@@ -0,0 +1,139 @@
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{ChangeWindow, Note, NoteLanguage, Tombstone};
+
+pub struct SyncRepository {
+    pool: PgPool,
+}
+
+impl SyncRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Notes written and deleted after `since`, at most `limit` of them in change order
+    pub async fn get_changes(
+        &self,
+        user_id: Uuid,
+        since: i64,
+        limit: i64
+    ) -> Result<(Vec<Note>, Vec<Tombstone>, ChangeWindow)> {
+        let mut tx = self.pool.begin().await?;
+
+        // waits for writers of this user that already took a change_seq to commit
+        sqlx::query!(
+            "SELECT pg_advisory_xact_lock_shared(note_sync_lock($1))",
+            user_id
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        let window = sqlx::query!(
+            r#"
+            WITH changes AS (
+                SELECT change_seq FROM notes WHERE user_id = $1 AND change_seq > $2
+                UNION ALL
+                SELECT change_seq FROM note_tombstones WHERE user_id = $1 AND change_seq > $2
+            )
+            SELECT
+                (SELECT change_seq FROM changes ORDER BY change_seq OFFSET $3::BIGINT - 1 LIMIT 1) AS last_in_page,
+                (SELECT change_seq FROM changes ORDER BY change_seq OFFSET $3 LIMIT 1) AS first_after_page,
+                (SELECT MAX(change_seq) FROM changes) AS latest
+            "#,
+            user_id,
+            since,
+            limit
+        )
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let window = match (window.first_after_page, window.last_in_page) {
+            (Some(_), Some(until)) => ChangeWindow { until, has_more: true },
+            _ => ChangeWindow { until: window.latest.unwrap_or(since), has_more: false },
+        };
+
+        let notes = sqlx::query_as!(
+            Note,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                created_at, 
+                updated_at,
+                version,
+                due_at,
+                remind_at,
+                pinned,
+                favorite,
+                archived_at,
+                notebook_id,
+                tags
+            FROM notes
+            WHERE user_id = $1 AND change_seq > $2 AND change_seq <= $3
+            ORDER BY change_seq
+            "#,
+            user_id,
+            since,
+            window.until
+        )
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let tombstones = sqlx::query_as!(
+            Tombstone,
+            r#"
+            SELECT 
+                note_id AS id, 
+                deleted_at
+            FROM note_tombstones
+            WHERE user_id = $1 AND change_seq > $2 AND change_seq <= $3
+            ORDER BY change_seq
+            "#,
+            user_id,
+            since,
+            window.until
+        )
+            .fetch_all(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok((notes, tombstones, window))
+    }
+
+    // Tokens below this may have missed deletes whose tombstones are gone
+    pub async fn get_purged_through(&self) -> Result<i64> {
+        let purged_through = sqlx::query_scalar!(
+            "SELECT purged_through FROM sync_horizon"
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(purged_through)
+    }
+
+    pub async fn purge_tombstones(&self, retention_days: u32) -> Result<i64> {
+        let purged = sqlx::query_scalar!(
+            r#"
+            WITH purged AS (
+                DELETE FROM note_tombstones
+                WHERE deleted_at < NOW() - make_interval(days => $1)
+                RETURNING change_seq
+            ), horizon AS (
+                UPDATE sync_horizon
+                SET purged_through = GREATEST(purged_through, (SELECT COALESCE(MAX(change_seq), 0) FROM purged))
+            )
+            SELECT COUNT(*) AS "purged!" FROM purged
+            "#,
+            retention_days as i32
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(purged)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{NoteTask, TaskLine};
+
+pub struct TaskRepository {
+    pool: PgPool,
+}
+
+impl TaskRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Tasks across all of the user's notes, recently edited notes first
+    pub async fn get_user_tasks(
+        &self,
+        user_id: Uuid,
+        done: Option<bool>,
+        limit: i64,
+        offset: i64
+    ) -> Result<Vec<NoteTask>> {
+        let tasks = sqlx::query_as!(
+            NoteTask,
+            r#"
+            SELECT 
+                t.note_id AS "note_id!", 
+                n.title AS note_title, 
+                t.task_index AS "task_index!", 
+                t.line_number AS "line_number!", 
+                t.done AS "done!", 
+                t.text AS "text!",
+                n.updated_at AS note_updated_at
+            FROM note_tasks t
+            JOIN notes n ON n.id = t.note_id
+            WHERE t.user_id = $1 AND ($2::BOOLEAN IS NULL OR t.done = $2)
+            ORDER BY n.updated_at DESC, t.note_id, t.task_index
+            LIMIT $3 OFFSET $4
+            "#,
+            user_id,
+            done,
+            limit,
+            offset
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(tasks)
+    }
+
+    // The task's line and the note content it was parsed from, in one snapshot
+    pub async fn get_task_line(&self, note_id: Uuid, user_id: Uuid, task_index: i32) -> Result<Option<TaskLine>> {
+        let line = sqlx::query_as!(
+            TaskLine,
+            r#"
+            SELECT 
+                t.line_number AS "line_number!", 
+                t.done AS "done!", 
+                n.version, 
+                n.content
+            FROM note_tasks t
+            JOIN notes n ON n.id = t.note_id
+            WHERE t.user_id = $2 AND t.note_id = $1 AND t.task_index = $3
+            "#,
+            note_id,
+            user_id,
+            task_index
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(line)
+    }
+}
\ No newline at end of file
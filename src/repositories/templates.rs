@@ -0,0 +1,189 @@
+use sqlx::PgPool;
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::{NewNoteTemplate, NoteLanguage, NoteTemplate, UpdateNoteTemplate};
+
+pub struct NoteTemplateRepository {
+    pool: PgPool,
+}
+
+impl NoteTemplateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_template_by_id(&self, template_id: Uuid, user_id: Uuid) -> Result<Option<NoteTemplate>> {
+        let template = sqlx::query_as!(
+            NoteTemplate,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                name, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                notebook_id, 
+                tags, 
+                created_at, 
+                updated_at
+            FROM note_templates 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            template_id,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(template)
+    }
+
+    pub async fn get_user_templates(&self, user_id: Uuid) -> Result<Vec<NoteTemplate>> {
+        let templates = sqlx::query_as!(
+            NoteTemplate,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                name, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                notebook_id, 
+                tags, 
+                created_at, 
+                updated_at
+            FROM note_templates 
+            WHERE user_id = $1
+            ORDER BY name ASC
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(templates)
+    }
+
+    pub async fn find_by_name(&self, user_id: Uuid, name: &str) -> Result<Option<NoteTemplate>> {
+        let template = sqlx::query_as!(
+            NoteTemplate,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                name, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                notebook_id, 
+                tags, 
+                created_at, 
+                updated_at
+            FROM note_templates 
+            WHERE user_id = $1 AND name = $2
+            "#,
+            user_id,
+            name
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(template)
+    }
+
+    pub async fn create_template(&self, new_template: NewNoteTemplate) -> Result<NoteTemplate> {
+        let template = sqlx::query_as!(
+            NoteTemplate,
+            r#"
+            INSERT INTO note_templates (user_id, name, title, content, language, notebook_id, tags)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING 
+                id, 
+                user_id, 
+                name, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                notebook_id, 
+                tags, 
+                created_at, 
+                updated_at
+            "#,
+            new_template.user_id,
+            new_template.name,
+            new_template.title,
+            new_template.content,
+            new_template.language.map(|language| language.as_str()),
+            new_template.notebook_id,
+            &new_template.tags
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(template)
+    }
+
+    pub async fn update_template(
+        &self,
+        template_id: Uuid,
+        user_id: Uuid,
+        update: UpdateNoteTemplate
+    ) -> Result<Option<NoteTemplate>> {
+        let template = sqlx::query_as!(
+            NoteTemplate,
+            r#"
+            UPDATE note_templates
+            SET 
+                name = COALESCE($3, name),
+                title = COALESCE($4, title),
+                content = COALESCE($5, content),
+                language = COALESCE($6, language),
+                notebook_id = CASE WHEN $7 THEN $8 ELSE notebook_id END,
+                tags = COALESCE($9, tags),
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING 
+                id, 
+                user_id, 
+                name, 
+                title, 
+                content, 
+                language AS "language: NoteLanguage",
+                notebook_id, 
+                tags, 
+                created_at, 
+                updated_at
+            "#,
+            template_id,
+            user_id,
+            update.name,
+            update.title,
+            update.content,
+            update.language.map(|language| language.as_str()),
+            update.notebook_id.is_some(),
+            update.notebook_id.flatten(),
+            update.tags.as_deref()
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(template)
+    }
+
+    pub async fn delete_template(&self, template_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM note_templates 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            template_id,
+            user_id
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use sqlx::{PgConnection, PgPool};
+use anyhow::Result;
+use uuid::Uuid;
+use crate::models::NoteUsage;
+
+pub struct UsageRepository {
+    pool: PgPool,
+}
+
+impl UsageRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_note_usage(&self, user_id: Uuid) -> Result<NoteUsage> {
+        let mut conn = self.pool.acquire().await?;
+        self.get_note_usage_in(&mut conn, user_id).await
+    }
+
+    // Users without notes yet have no row
+    pub async fn get_note_usage_in(&self, conn: &mut PgConnection, user_id: Uuid) -> Result<NoteUsage> {
+        let usage = sqlx::query_as!(
+            NoteUsage,
+            r#"
+            SELECT 
+                note_count, 
+                content_bytes
+            FROM user_usage 
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+            .fetch_optional(conn)
+            .await?;
+
+        Ok(usage.unwrap_or_default())
+    }
+
+    // Locks the user's row until the transaction ends, creating it first so even a
+    // user's first notes are counted one after the other
+    pub async fn lock_note_usage_in(&self, conn: &mut PgConnection, user_id: Uuid) -> Result<NoteUsage> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_usage (user_id)
+            VALUES ($1)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+            user_id
+        )
+            .execute(&mut *conn)
+            .await?;
+
+        let usage = sqlx::query_as!(
+            NoteUsage,
+            r#"
+            SELECT 
+                note_count, 
+                content_bytes
+            FROM user_usage 
+            WHERE user_id = $1
+            FOR UPDATE
+            "#,
+            user_id
+        )
+            .fetch_one(conn)
+            .await?;
+
+        Ok(usage)
+    }
+}
\ No newline at end of file
@@ -145,4 +145,36 @@ impl UserRepository {
 
         Ok(result.rows_affected() > 0)
     }
+
+    // None revokes the user's calendar feed
+    pub async fn set_calendar_token_hash(&self, user_id: Uuid, token_hash: Option<String>) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE users
+            SET calendar_token_hash = $2
+            WHERE id = $1
+            "#,
+            user_id,
+            token_hash
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn find_id_by_calendar_token_hash(&self, token_hash: &str) -> Result<Option<Uuid>> {
+        let user_id = sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM users 
+            WHERE calendar_token_hash = $1
+            "#,
+            token_hash
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(user_id)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,343 @@
+use sqlx::PgPool;
+use sqlx::types::Json;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+use crate::models::{DeliveryStatus, DueDelivery, NewWebhook, UpdateWebhook, Webhook, WebhookDelivery};
+
+pub struct WebhookRepository {
+    pool: PgPool,
+}
+
+impl WebhookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_webhook_by_id(&self, webhook_id: Uuid, user_id: Uuid) -> Result<Option<Webhook>> {
+        let webhook = sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                url, 
+                secret, 
+                events, 
+                active, 
+                consecutive_failures, 
+                disabled_at, 
+                created_at, 
+                updated_at
+            FROM webhooks 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            webhook_id,
+            user_id
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(webhook)
+    }
+
+    pub async fn get_user_webhooks(&self, user_id: Uuid) -> Result<Vec<Webhook>> {
+        let webhooks = sqlx::query_as!(
+            Webhook,
+            r#"
+            SELECT 
+                id, 
+                user_id, 
+                url, 
+                secret, 
+                events, 
+                active, 
+                consecutive_failures, 
+                disabled_at, 
+                created_at, 
+                updated_at
+            FROM webhooks 
+            WHERE user_id = $1
+            ORDER BY created_at ASC
+            "#,
+            user_id
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(webhooks)
+    }
+
+    pub async fn create_webhook(&self, new_webhook: NewWebhook) -> Result<Webhook> {
+        let webhook = sqlx::query_as!(
+            Webhook,
+            r#"
+            INSERT INTO webhooks (user_id, url, secret, events)
+            VALUES ($1, $2, $3, $4)
+            RETURNING 
+                id, 
+                user_id, 
+                url, 
+                secret, 
+                events, 
+                active, 
+                consecutive_failures, 
+                disabled_at, 
+                created_at, 
+                updated_at
+            "#,
+            new_webhook.user_id,
+            new_webhook.url,
+            new_webhook.secret,
+            &new_webhook.events
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(webhook)
+    }
+
+    pub async fn update_webhook(&self, webhook_id: Uuid, user_id: Uuid, update: UpdateWebhook) -> Result<Option<Webhook>> {
+        let webhook = sqlx::query_as!(
+            Webhook,
+            r#"
+            UPDATE webhooks
+            SET 
+                url = COALESCE($3, url),
+                events = COALESCE($4, events),
+                active = COALESCE($5, active),
+                consecutive_failures = CASE WHEN $5 THEN 0 ELSE consecutive_failures END,
+                disabled_at = CASE WHEN $5 IS NULL THEN disabled_at WHEN $5 THEN NULL ELSE NOW() END,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING 
+                id, 
+                user_id, 
+                url, 
+                secret, 
+                events, 
+                active, 
+                consecutive_failures, 
+                disabled_at, 
+                created_at, 
+                updated_at
+            "#,
+            webhook_id,
+            user_id,
+            update.url,
+            update.events.as_deref(),
+            update.active
+        )
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(webhook)
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM webhooks 
+            WHERE id = $1 AND user_id = $2
+            "#,
+            webhook_id,
+            user_id
+        )
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Newest first, callers check the webhook belongs to the user
+    pub async fn get_deliveries(
+        &self,
+        webhook_id: Uuid,
+        status: Option<DeliveryStatus>,
+        limit: i64,
+        offset: i64
+    ) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            SELECT 
+                id, 
+                webhook_id, 
+                event, 
+                payload AS "payload: Json<Value>", 
+                status AS "status: DeliveryStatus", 
+                attempts, 
+                next_attempt_at, 
+                last_status_code, 
+                last_error, 
+                created_at, 
+                delivered_at
+            FROM webhook_deliveries 
+            WHERE webhook_id = $1 AND ($2::TEXT IS NULL OR status = $2)
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            webhook_id,
+            status.map(|status| status.as_str()),
+            limit,
+            offset
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(deliveries)
+    }
+
+    // Note events are queued by the notes table trigger, this is for everything else
+    pub async fn queue_delivery(&self, webhook_id: Uuid, event: &str, payload: Value) -> Result<WebhookDelivery> {
+        let delivery = sqlx::query_as!(
+            WebhookDelivery,
+            r#"
+            INSERT INTO webhook_deliveries (webhook_id, event, payload)
+            VALUES ($1, $2, $3)
+            RETURNING 
+                id, 
+                webhook_id, 
+                event, 
+                payload AS "payload: Json<Value>", 
+                status AS "status: DeliveryStatus", 
+                attempts, 
+                next_attempt_at, 
+                last_status_code, 
+                last_error, 
+                created_at, 
+                delivered_at
+            "#,
+            webhook_id,
+            event,
+            payload
+        )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(delivery)
+    }
+
+    // Due deliveries of active webhooks, pushed back by `lease_secs` so another dispatcher
+    // skips them. If this one dies mid-delivery they're retried once the lease runs out
+    pub async fn claim_due_deliveries(&self, limit: i64, lease_secs: f64) -> Result<Vec<DueDelivery>> {
+        let deliveries = sqlx::query_as!(
+            DueDelivery,
+            r#"
+            UPDATE webhook_deliveries AS d
+            SET next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM webhooks AS w
+            WHERE w.id = d.webhook_id AND d.id IN (
+                SELECT due.id
+                FROM webhook_deliveries AS due
+                JOIN webhooks ON webhooks.id = due.webhook_id
+                WHERE due.status = 'pending' AND due.next_attempt_at <= NOW() AND webhooks.active
+                ORDER BY due.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF due SKIP LOCKED
+            )
+            RETURNING 
+                d.id, 
+                d.webhook_id, 
+                w.url, 
+                w.secret, 
+                d.event, 
+                d.payload AS "payload: Json<Value>", 
+                d.attempts
+            "#,
+            limit,
+            lease_secs
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn record_success(&self, delivery: &DueDelivery, status_code: i32) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET 
+                status = 'delivered',
+                attempts = attempts + 1,
+                last_status_code = $2,
+                last_error = NULL,
+                delivered_at = NOW()
+            WHERE id = $1
+            "#,
+            delivery.id,
+            status_code
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE webhooks 
+            SET consecutive_failures = 0
+            WHERE id = $1
+            "#,
+            delivery.webhook_id
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Schedules the next attempt, or gives up when `retry_at` is None. Returns true when
+    // this failure disabled the webhook
+    pub async fn record_failure(
+        &self,
+        delivery: &DueDelivery,
+        status_code: Option<i32>,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+        disable_after: i32
+    ) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET 
+                status = CASE WHEN $4::TIMESTAMPTZ IS NULL THEN 'failed' ELSE 'pending' END,
+                attempts = attempts + 1,
+                last_status_code = $2,
+                last_error = $3,
+                next_attempt_at = COALESCE($4, next_attempt_at)
+            WHERE id = $1
+            "#,
+            delivery.id,
+            status_code,
+            error,
+            retry_at
+        )
+            .execute(&mut *tx)
+            .await?;
+
+        let disabled = sqlx::query_scalar!(
+            r#"
+            UPDATE webhooks 
+            SET 
+                consecutive_failures = consecutive_failures + 1,
+                active = active AND consecutive_failures + 1 < $2,
+                disabled_at = CASE WHEN active AND consecutive_failures + 1 >= $2 THEN NOW() ELSE disabled_at END
+            WHERE id = $1
+            RETURNING consecutive_failures = $2 AS "disabled!"
+            "#,
+            delivery.webhook_id,
+            disable_after
+        )
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(disabled.unwrap_or(false))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,281 @@
+use std::sync::Arc;
+use actix_multipart::Multipart;
+use actix_web::http::header::{
+    self, Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue
+};
+use actix_web::{HttpResponse, Error};
+use bytes::Bytes;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{Attachment, NewAttachment, NoteAttachments};
+use crate::repositories::{AttachmentRepository, NoteRepository};
+use crate::storage::{BlobStore, ByteRange};
+use crate::utils::{read_file_upload, UploadError};
+
+pub struct AttachmentService {
+    pub repo: AttachmentRepository,
+    pub notes: NoteRepository,
+    store: Arc<dyn BlobStore>,
+    max_file_size: u64,
+    user_quota: u64,
+}
+
+// Keep only the last path segment of a client supplied filename
+fn sanitize_filename(filename: &str) -> String {
+    let name = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(255)
+        .collect::<String>();
+
+    if name.is_empty() || name == "." || name == ".." {
+        "attachment".to_string()
+    } else {
+        name
+    }
+}
+
+fn content_disposition(filename: &str) -> ContentDisposition {
+    let ascii_fallback: String = filename
+        .chars()
+        .map(|c| if c.is_ascii() && c != '"' { c } else { '_' })
+        .collect();
+
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![
+            DispositionParam::Filename(ascii_fallback),
+            DispositionParam::FilenameExt(ExtendedValue {
+                charset: Charset::Ext("UTF-8".to_string()),
+                language_tag: None,
+                value: filename.as_bytes().to_vec(),
+            }),
+        ],
+    }
+}
+
+impl AttachmentService {
+    pub fn new(pool: PgPool, store: Arc<dyn BlobStore>, max_file_size: u64, user_quota: u64) -> Self {
+        Self {
+            repo: AttachmentRepository::new(pool.clone()),
+            notes: NoteRepository::new(pool),
+            store,
+            max_file_size,
+            user_quota,
+        }
+    }
+
+    async fn note_exists(&self, user_id: Uuid, note_id: Uuid) -> Result<bool, Error> {
+        let note = self.notes
+            .get_note_by_id(note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(note.is_some())
+    }
+
+    pub async fn upload_attachment(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid,
+        payload: Multipart
+    ) -> Result<HttpResponse, Error> {
+        if !self.note_exists(user_id, note_id).await? {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        }
+
+        let upload = match read_file_upload(payload, self.max_file_size).await {
+            Ok(upload) => upload,
+            Err(UploadError::Missing) => {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": "Missing multipart field 'file'" })));
+            }
+            Err(UploadError::TooLarge) => {
+                return Ok(HttpResponse::PayloadTooLarge().json(json!({
+                    "message": format!("Attachments must not exceed {} bytes", self.max_file_size)
+                })));
+            }
+            Err(UploadError::Malformed(message)) => {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": message })));
+            }
+        };
+
+        let size_bytes = upload.data.len() as i64;
+
+        let usage = self.repo
+            .get_user_usage(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if (usage + size_bytes) as u64 > self.user_quota {
+            return Ok(HttpResponse::InsufficientStorage().json(json!({
+                "message": "Attachment storage quota exceeded",
+                "quota_bytes": self.user_quota,
+                "used_bytes": usage
+            })));
+        }
+
+        let sha256 = hex::encode(Sha256::digest(&upload.data));
+        let attachment = self
+            .store_attachment(NewAttachment::new(
+                note_id,
+                user_id,
+                sanitize_filename(&upload.filename),
+                upload.content_type,
+                size_bytes,
+                sha256
+            ), upload.data)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Created().json(attachment))
+    }
+
+    // Store the blob unless identical content is already stored, then record the attachment
+    async fn store_attachment(&self, new_attachment: NewAttachment, data: Bytes) -> anyhow::Result<Attachment> {
+        let mut tx = self.repo.begin().await?;
+        self.repo.lock_blob(&mut tx, &new_attachment.sha256).await?;
+
+        if !self.repo.blob_exists(&mut tx, &new_attachment.sha256).await? {
+            self.store.put(&new_attachment.sha256, data).await?;
+            self.repo.create_blob(&mut tx, &new_attachment.sha256, new_attachment.size_bytes).await?;
+        } else if !self.store.exists(&new_attachment.sha256).await? {
+            // the row outlived the stored object, take the chance to restore it
+            self.store.put(&new_attachment.sha256, data).await?;
+        }
+
+        let attachment = self.repo.create_attachment(&mut tx, new_attachment).await?;
+        tx.commit().await?;
+
+        Ok(attachment)
+    }
+
+    pub async fn get_note_attachments(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        if !self.note_exists(user_id, note_id).await? {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        }
+
+        let attachments = self.repo
+            .get_note_attachments(note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(NoteAttachments { attachments }))
+    }
+
+    pub async fn download_attachment(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid,
+        attachment_id: Uuid,
+        range_header: Option<&str>
+    ) -> Result<HttpResponse, Error> {
+        let attachment = self.repo
+            .get_attachment(attachment_id, note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(attachment) = attachment else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Attachment not found" })));
+        };
+
+        let size = attachment.size_bytes as u64;
+        let range = match range_header.map(|header| ByteRange::parse(header, size)) {
+            Some(Ok(range)) => range,
+            None => None,
+            Some(Err(message)) => {
+                return Ok(HttpResponse::RangeNotSatisfiable()
+                    .insert_header((header::CONTENT_RANGE, format!("bytes */{}", size)))
+                    .json(json!({ "message": message })));
+            }
+        };
+
+        let data = self.store
+            .get(&attachment.sha256, range)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let mut response = match range {
+            Some(range) => {
+                let mut response = HttpResponse::PartialContent();
+                response.insert_header((
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", range.start, range.end, size)
+                ));
+                response
+            }
+            None => HttpResponse::Ok(),
+        };
+
+        Ok(response
+            .insert_header((header::CONTENT_TYPE, attachment.content_type.as_str()))
+            .insert_header(content_disposition(&attachment.filename))
+            .insert_header((header::ACCEPT_RANGES, "bytes"))
+            .insert_header((header::ETAG, format!("\"{}\"", attachment.sha256)))
+            .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+            .body(data))
+    }
+
+    pub async fn delete_attachment(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid,
+        attachment_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        let attachment = self.repo
+            .get_attachment(attachment_id, note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(attachment) = attachment else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Attachment not found" })));
+        };
+
+        self.remove_attachment(&attachment)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::NoContent().json(json!({ "message": "Attachment deleted" })))
+    }
+
+    async fn remove_attachment(&self, attachment: &Attachment) -> anyhow::Result<()> {
+        let mut tx = self.repo.begin().await?;
+        self.repo.lock_blob(&mut tx, &attachment.sha256).await?;
+        self.repo.delete_attachment(&mut tx, attachment.id, attachment.user_id).await?;
+
+        if self.repo.delete_blob_if_orphaned(&mut tx, &attachment.sha256).await? {
+            self.store.delete(&attachment.sha256).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Delete blobs whose attachments went away with their notes
+    pub async fn purge_orphaned_blobs(&self) -> anyhow::Result<usize> {
+        let mut purged = 0;
+
+        for sha256 in self.repo.find_orphaned_blobs().await? {
+            let mut tx = self.repo.begin().await?;
+            self.repo.lock_blob(&mut tx, &sha256).await?;
+
+            if self.repo.delete_blob_if_orphaned(&mut tx, &sha256).await? {
+                self.store.delete(&sha256).await?;
+                purged += 1;
+            }
+
+            tx.commit().await?;
+        }
+
+        Ok(purged)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,493 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use actix_web::{web, HttpRequest, HttpResponse, Error};
+use actix_ws::{AggregatedMessage, AggregatedMessageStream, Closed, Session};
+use bytes::Bytes;
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+use yrs::sync::{Message, MessageReader, SyncMessage};
+use yrs::updates::decoder::{Decode, DecoderV1};
+use yrs::updates::encoder::Encode;
+use yrs::{Doc, ReadTxn, Transact, Update};
+use crate::models::{Note, NoteEvent, NoteEventKind, PresenceUser, RoomError, RoomPresence, SharePermission, UpdateNote};
+use crate::repositories::{NoteDocumentRepository, NoteLinkRepository, NoteRepository, ShareRepository, UserRepository};
+use crate::services::{note_size, QuotaService};
+use crate::utils::{document_text, encode_document, load_document, merge_document_text, new_document, parse_wiki_links, replace_document_text};
+
+// How often edits are written back to notes.content
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+// Room messages buffered per connection, a client that falls further behind is dropped and resyncs
+const ROOM_BUFFER: usize = 256;
+// A first sync sends the whole document in one message
+const MAX_MESSAGE_SIZE: usize = 8 * 1024 * 1024;
+// Connection ids start at 1, updates made by the server itself come from 0
+const SERVER_CONNECTION: u64 = 0;
+
+#[derive(Clone, Debug)]
+enum RoomMessage {
+    // encoded y-sync message, relayed to everyone but the sender
+    Sync { from: u64, data: Bytes },
+    Presence(String),
+    // a snapshot was refused
+    Error(String),
+    // the user lost access to the note or had their permission changed, their
+    // connections close and have to join again
+    Revoked(Uuid),
+    // the note is gone
+    Closed,
+}
+
+// What notes.content looked like after the last snapshot
+struct SnapshotState {
+    content: String,
+    version: i64,
+    // the document as of `content`, REST edits are merged in relative to it
+    state: Vec<u8>,
+    dirty: bool,
+    // the last snapshot went over quota, the editors were told
+    refused: bool,
+}
+
+// Everyone editing one note, the owner and whoever it's shared with
+struct Room {
+    note_id: Uuid,
+    // the owner, snapshots are saved on their behalf
+    user_id: Uuid,
+    doc: Mutex<Doc>,
+    snapshot: Mutex<SnapshotState>,
+    members: Mutex<HashMap<u64, PresenceUser>>,
+    sender: broadcast::Sender<RoomMessage>,
+}
+
+pub struct CollabService {
+    notes: NoteRepository,
+    links: NoteLinkRepository,
+    documents: NoteDocumentRepository,
+    shares: ShareRepository,
+    users: UserRepository,
+    quotas: Arc<QuotaService>,
+    rooms: Mutex<HashMap<Uuid, Arc<Room>>>,
+    next_connection: AtomicU64,
+}
+
+impl Room {
+    fn broadcast_presence(&self) {
+        let members = self.members.lock().unwrap().values().cloned().collect();
+        let presence = RoomPresence::new(self.note_id, members);
+        if let Ok(presence) = serde_json::to_string(&presence) {
+            let _ = self.sender.send(RoomMessage::Presence(presence));
+        }
+    }
+
+    fn is_member(&self, connection_id: u64, user_id: Uuid) -> bool {
+        self.members.lock().unwrap().get(&connection_id).is_some_and(|member| member.user_id == user_id)
+    }
+
+    fn leave(&self, connection_id: u64) {
+        self.members.lock().unwrap().remove(&connection_id);
+        self.broadcast_presence();
+    }
+
+    // Answers sync requests and applies updates from one connection, returns the replies for it.
+    // Updates from read-only connections are dropped
+    fn handle_sync(&self, connection_id: u64, can_edit: bool, data: &[u8]) -> Vec<Vec<u8>> {
+        let mut replies = Vec::new();
+        let mut decoder = DecoderV1::from(data);
+
+        for message in MessageReader::new(&mut decoder) {
+            let Ok(message) = message else {
+                break;
+            };
+
+            match message {
+                Message::Sync(SyncMessage::SyncStep1(state_vector)) => {
+                    let diff = self.doc.lock().unwrap().transact().encode_state_as_update_v1(&state_vector);
+                    replies.push(Message::Sync(SyncMessage::SyncStep2(diff)).encode_v1());
+                }
+                Message::Sync(SyncMessage::SyncStep2(_)) | Message::Sync(SyncMessage::Update(_)) if !can_edit => {}
+                Message::Sync(SyncMessage::SyncStep2(update)) | Message::Sync(SyncMessage::Update(update)) => {
+                    let Ok(decoded) = Update::decode_v1(&update) else {
+                        continue;
+                    };
+                    if self.doc.lock().unwrap().transact_mut().apply_update(decoded).is_err() {
+                        continue;
+                    }
+                    self.snapshot.lock().unwrap().dirty = true;
+                    let data = Message::Sync(SyncMessage::Update(update)).encode_v1();
+                    let _ = self.sender.send(RoomMessage::Sync { from: connection_id, data: data.into() });
+                }
+                // cursors and selections, only the clients care about those
+                Message::Awareness(update) => {
+                    let data = Message::Awareness(update).encode_v1();
+                    let _ = self.sender.send(RoomMessage::Sync { from: connection_id, data: data.into() });
+                }
+                _ => {}
+            }
+        }
+
+        replies
+    }
+}
+
+impl CollabService {
+    pub fn new(pool: PgPool, quotas: Arc<QuotaService>) -> Self {
+        Self {
+            notes: NoteRepository::new(pool.clone()),
+            links: NoteLinkRepository::new(pool.clone()),
+            documents: NoteDocumentRepository::new(pool.clone()),
+            shares: ShareRepository::new(pool.clone()),
+            users: UserRepository::new(pool),
+            quotas,
+            rooms: Mutex::new(HashMap::new()),
+            next_connection: AtomicU64::new(SERVER_CONNECTION + 1),
+        }
+    }
+
+    // The note and whether the user may edit it, None when it's neither theirs nor shared with them
+    async fn accessible_note(&self, user_id: Uuid, note_id: Uuid) -> anyhow::Result<Option<(Note, bool)>> {
+        if let Some(note) = self.notes.get_note_by_id(note_id, user_id).await? {
+            return Ok(Some((note, true)));
+        }
+
+        let Some(permission) = self.shares.get_permission(note_id, user_id).await? else {
+            return Ok(None);
+        };
+        let note = self.notes.get_shared_note(note_id, user_id).await?;
+
+        Ok(note.map(|note| (note, permission == SharePermission::Write)))
+    }
+
+    // Access is only checked on joining, so connections of users whose share was deleted
+    // or changed are closed here for as long as the server runs
+    pub async fn close_revoked_connections(&self, mut events: broadcast::Receiver<NoteEvent>) {
+        loop {
+            match events.recv().await {
+                Ok(NoteEvent {
+                    kind: NoteEventKind::Shared | NoteEventKind::Unshared,
+                    user_id: Some(user_id),
+                    note_id: Some(note_id),
+                    ..
+                }) => {
+                    let room = self.rooms.lock().unwrap().get(&note_id).cloned();
+                    if let Some(room) = room {
+                        let _ = room.sender.send(RoomMessage::Revoked(user_id));
+                    }
+                }
+                Ok(NoteEvent { kind: NoteEventKind::Resync, .. }) | Err(RecvError::Lagged(_)) => self.recheck_shares().await,
+                Ok(_) => {}
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+
+    // Some events were missed, looks up again whether everyone besides the owners may
+    // still open the notes they're in
+    async fn recheck_shares(&self) {
+        let rooms: Vec<Arc<Room>> = self.rooms.lock().unwrap().values().cloned().collect();
+
+        for room in rooms {
+            let users: HashSet<Uuid> = room.members
+                .lock()
+                .unwrap()
+                .values()
+                .map(|member| member.user_id)
+                .filter(|user_id| *user_id != room.user_id)
+                .collect();
+
+            for user_id in users {
+                match self.shares.get_permission(room.note_id, user_id).await {
+                    Ok(Some(_)) => {}
+                    Ok(None) => {
+                        let _ = room.sender.send(RoomMessage::Revoked(user_id));
+                    }
+                    Err(e) => eprintln!("Checking access of user {} to note {} failed: {}", user_id, room.note_id, e),
+                }
+            }
+        }
+    }
+
+    // Upgrades to a WebSocket speaking the y-websocket protocol for the note's content,
+    // plus JSON text frames announcing presence and refused snapshots
+    pub async fn join_room(
+        self: Arc<Self>,
+        user_id: Uuid,
+        note_id: Uuid,
+        req: &HttpRequest,
+        body: web::Payload
+    ) -> Result<HttpResponse, Error> {
+        let note = self
+            .accessible_note(user_id, note_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some((note, can_edit)) = note else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        };
+
+        let username = self.users
+            .find_by_id(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .map(|user| user.username)
+            .unwrap_or_default();
+
+        let (response, session, messages) = actix_ws::handle(req, body)?;
+        let messages = messages
+            .max_frame_size(MAX_MESSAGE_SIZE)
+            .aggregate_continuations()
+            .max_continuation_size(MAX_MESSAGE_SIZE);
+
+        let connection_id = self.next_connection.fetch_add(1, Ordering::Relaxed);
+        let (room, receiver) = self.clone()
+            .open_room(note, connection_id, PresenceUser { user_id, username })
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        actix_web::rt::spawn(Self::run_connection(room, connection_id, can_edit, session, messages, receiver));
+
+        Ok(response)
+    }
+
+    // Joins the note's room, loading it first if nobody has the note open yet
+    async fn open_room(
+        self: Arc<Self>,
+        note: Note,
+        connection_id: u64,
+        member: PresenceUser
+    ) -> anyhow::Result<(Arc<Room>, broadcast::Receiver<RoomMessage>)> {
+        let mut loaded = None;
+
+        // membership changes under the rooms lock, so a room can't be closed while someone joins
+        let (room, created) = loop {
+            {
+                let mut rooms = self.rooms.lock().unwrap();
+                let existing = rooms.get(&note.id).cloned();
+                let joined = match (existing, loaded.take()) {
+                    (Some(room), _) => Some((room, false)),
+                    (None, Some(room)) => {
+                        rooms.insert(note.id, Arc::clone(&room));
+                        Some((room, true))
+                    }
+                    (None, None) => None,
+                };
+                if let Some((room, created)) = joined {
+                    room.members.lock().unwrap().insert(connection_id, member);
+                    break (room, created);
+                }
+            }
+            loaded = Some(Arc::new(self.load_room(&note).await?));
+        };
+
+        let receiver = room.sender.subscribe();
+        room.broadcast_presence();
+
+        if created {
+            actix_web::rt::spawn(self.clone().keep_snapshotting(room.clone()));
+        }
+
+        Ok((room, receiver))
+    }
+
+    async fn load_room(&self, note: &Note) -> anyhow::Result<Room> {
+        let state = self.documents.get_document_state(note.id).await?;
+        let mut dirty = state.is_none();
+
+        let doc = match state.and_then(|state| load_document(&state).ok()) {
+            Some(doc) => doc,
+            None => {
+                dirty = true;
+                new_document(&note.content)
+            }
+        };
+
+        // edited through the REST API while nobody had it open
+        if document_text(&doc) != note.content {
+            replace_document_text(&doc, &note.content);
+            dirty = true;
+        }
+
+        let (sender, _) = broadcast::channel(ROOM_BUFFER);
+        let state = encode_document(&doc);
+
+        Ok(Room {
+            note_id: note.id,
+            user_id: note.user_id,
+            doc: Mutex::new(doc),
+            snapshot: Mutex::new(SnapshotState {
+                content: note.content.clone(),
+                version: note.version,
+                state,
+                dirty,
+                refused: false,
+            }),
+            members: Mutex::new(HashMap::new()),
+            sender,
+        })
+    }
+
+    async fn run_connection(
+        room: Arc<Room>,
+        connection_id: u64,
+        can_edit: bool,
+        mut session: Session,
+        mut messages: AggregatedMessageStream,
+        mut receiver: broadcast::Receiver<RoomMessage>
+    ) {
+        // the client answers with the updates we're missing and asks for ours
+        let step1 = Message::Sync(SyncMessage::SyncStep1(room.doc.lock().unwrap().transact().state_vector())).encode_v1();
+
+        if session.binary(step1).await.is_ok() {
+            let _ = Self::relay(&room, connection_id, can_edit, &mut session, &mut messages, &mut receiver).await;
+        }
+
+        room.leave(connection_id);
+        let _ = session.close(None).await;
+    }
+
+    async fn relay(
+        room: &Room,
+        connection_id: u64,
+        can_edit: bool,
+        session: &mut Session,
+        messages: &mut AggregatedMessageStream,
+        receiver: &mut broadcast::Receiver<RoomMessage>
+    ) -> Result<(), Closed> {
+        loop {
+            tokio::select! {
+                message = receiver.recv() => match message {
+                    Ok(RoomMessage::Sync { from, data }) if from != connection_id => session.binary(data).await?,
+                    Ok(RoomMessage::Sync { .. }) => {}
+                    Ok(RoomMessage::Revoked(user_id)) if room.is_member(connection_id, user_id) => return Ok(()),
+                    Ok(RoomMessage::Revoked(_)) => {}
+                    Ok(RoomMessage::Presence(text)) | Ok(RoomMessage::Error(text)) => session.text(text).await?,
+                    // a lagging client has missed updates, it syncs again when it reconnects
+                    Ok(RoomMessage::Closed) | Err(RecvError::Closed) | Err(RecvError::Lagged(_)) => return Ok(()),
+                },
+                message = messages.recv() => match message {
+                    Some(Ok(AggregatedMessage::Binary(data))) => {
+                        for reply in room.handle_sync(connection_id, can_edit, &data) {
+                            session.binary(reply).await?;
+                        }
+                    }
+                    Some(Ok(AggregatedMessage::Ping(bytes))) => session.pong(&bytes).await?,
+                    Some(Ok(AggregatedMessage::Text(_))) | Some(Ok(AggregatedMessage::Pong(_))) => {}
+                    Some(Ok(AggregatedMessage::Close(_))) | Some(Err(_)) | None => return Ok(()),
+                },
+            }
+        }
+    }
+
+    // Writes the document back to notes.content every few seconds while the room is open.
+    // After the last editor left the room stays until its edits are saved
+    async fn keep_snapshotting(self: Arc<Self>, room: Arc<Room>) {
+        let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let note_exists = match self.snapshot(&room).await {
+                Ok(exists) => exists,
+                Err(e) => {
+                    eprintln!("Snapshot of note {} failed: {}", room.note_id, e);
+                    room.snapshot.lock().unwrap().dirty = true;
+                    true
+                }
+            };
+
+            let mut rooms = self.rooms.lock().unwrap();
+            if !note_exists {
+                rooms.remove(&room.note_id);
+                let _ = room.sender.send(RoomMessage::Closed);
+                return;
+            }
+            if room.members.lock().unwrap().is_empty() && !room.snapshot.lock().unwrap().dirty {
+                rooms.remove(&room.note_id);
+                return;
+            }
+        }
+    }
+
+    // false once the note was deleted
+    async fn snapshot(&self, room: &Room) -> anyhow::Result<bool> {
+        let Some(note) = self.notes.get_note_by_id(room.note_id, room.user_id).await? else {
+            return Ok(false);
+        };
+
+        let (known_version, known_content, known_state) = {
+            let snapshot = room.snapshot.lock().unwrap();
+            (snapshot.version, snapshot.content.clone(), snapshot.state.clone())
+        };
+
+        // edited through the REST API since the last snapshot, merge that edit into the
+        // document alongside whatever was typed in the room meanwhile
+        if note.version != known_version && note.content != known_content {
+            let (update, state) = merge_document_text(&room.doc.lock().unwrap(), &known_state, &note.content)?;
+            let data = Message::Sync(SyncMessage::Update(update)).encode_v1();
+            let _ = room.sender.send(RoomMessage::Sync { from: SERVER_CONNECTION, data: data.into() });
+
+            // the next REST edit is merged relative to this one
+            let mut snapshot = room.snapshot.lock().unwrap();
+            snapshot.content = note.content.clone();
+            snapshot.version = note.version;
+            snapshot.state = state;
+            snapshot.dirty = true;
+        }
+
+        // edits arriving from here on set it again for the next round
+        let dirty = std::mem::take(&mut room.snapshot.lock().unwrap().dirty);
+        if !dirty && note.version == known_version {
+            return Ok(true);
+        }
+
+        let (content, state) = {
+            let doc = room.doc.lock().unwrap();
+            (document_text(&doc), encode_document(&doc))
+        };
+
+        let version = if content == note.content {
+            note.version
+        } else {
+            // refused like a REST edit over quota. The room stays dirty and open, retrying
+            // until the edits fit, and the editors hear about it once
+            let mut tx = self.notes.begin().await?;
+            let old_size = note_size(&note.title, &note.content);
+            let new_size = note_size(&note.title, &content);
+            if let Err(exceeded) = self.quotas.check_note_write_in(&mut tx, note.user_id, Some(old_size), new_size).await? {
+                let mut snapshot = room.snapshot.lock().unwrap();
+                snapshot.dirty = true;
+                if !std::mem::replace(&mut snapshot.refused, true) {
+                    let error = RoomError::new(note.id, exceeded.status().as_u16(), exceeded.message());
+                    if let Ok(error) = serde_json::to_string(&error) {
+                        let _ = room.sender.send(RoomMessage::Error(error));
+                    }
+                }
+                return Ok(true);
+            }
+
+            let update = UpdateNote::new().with_content(content.clone());
+            // someone saved through the REST API in the meantime, merge that in next round
+            let Some(updated) = self.notes.update_note_if_version_in(&mut tx, note.id, note.user_id, note.version, update).await? else {
+                room.snapshot.lock().unwrap().dirty = true;
+                return Ok(true);
+            };
+            tx.commit().await?;
+            self.links.replace_note_links(updated.id, updated.user_id, &parse_wiki_links(&updated.content)).await?;
+            updated.version
+        };
+
+        self.documents.save_document_state(note.id, &state).await?;
+
+        let mut snapshot = room.snapshot.lock().unwrap();
+        snapshot.content = content;
+        snapshot.version = version;
+        snapshot.state = state;
+        snapshot.refused = false;
+
+        Ok(true)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+use std::time::Duration;
+use actix_web::{web, HttpRequest, HttpResponse, Error};
+use actix_ws::Message;
+use bytes::Bytes;
+use futures_util::stream;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+use crate::models::NoteEvent;
+
+const NOTE_EVENTS_CHANNEL: &str = "note_events";
+// Events buffered per connection before a slow client misses some and gets a resync
+const EVENT_BUFFER: usize = 1024;
+// Proxies tend to drop connections that stay quiet for a minute
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+pub struct EventService {
+    sender: broadcast::Sender<NoteEvent>,
+}
+
+impl Default for EventService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventService {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    // Every event received from Postgres, for services reacting to them on this instance
+    pub fn subscribe(&self) -> broadcast::Receiver<NoteEvent> {
+        self.sender.subscribe()
+    }
+
+    // Relays note_events notifications from Postgres to the connected clients of this
+    // instance, runs for as long as the server does
+    pub async fn listen(&self, pool: PgPool) {
+        loop {
+            if let Err(e) = self.relay_notifications(&pool).await {
+                eprintln!("Note event listener failed: {}", e);
+            }
+            // whatever happened while we weren't listening is gone
+            let _ = self.sender.send(NoteEvent::resync());
+            tokio::time::sleep(LISTENER_RETRY_DELAY).await;
+        }
+    }
+
+    async fn relay_notifications(&self, pool: &PgPool) -> anyhow::Result<()> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(NOTE_EVENTS_CHANNEL).await?;
+
+        loop {
+            // None means the connection dropped, the next call reconnects
+            let Some(notification) = listener.try_recv().await? else {
+                let _ = self.sender.send(NoteEvent::resync());
+                continue;
+            };
+
+            match serde_json::from_str::<NoteEvent>(notification.payload()) {
+                // only fails when nobody is connected
+                Ok(event) => {
+                    let _ = self.sender.send(event);
+                }
+                Err(e) => eprintln!("Ignoring malformed note event: {}", e),
+            }
+        }
+    }
+
+    // Next event for this user, a resync after falling behind, None once the service is gone
+    async fn next_event(receiver: &mut broadcast::Receiver<NoteEvent>, user_id: Uuid) -> Option<NoteEvent> {
+        loop {
+            match receiver.recv().await {
+                Ok(event) if event.is_for(user_id) => return Some(event),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => return Some(NoteEvent::resync()),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    pub fn stream_events(&self, user_id: Uuid) -> HttpResponse {
+        let receiver = self.sender.subscribe();
+
+        let events = stream::unfold((receiver, true), move |(mut receiver, first)| async move {
+            // tell EventSource how long to wait before reconnecting
+            if first {
+                return Some((Ok::<_, Error>(Bytes::from_static(b"retry: 3000\n\n")), (receiver, false)));
+            }
+
+            let chunk = match tokio::time::timeout(KEEPALIVE_INTERVAL, Self::next_event(&mut receiver, user_id)).await {
+                Ok(Some(event)) => Bytes::from(event.to_sse()),
+                Ok(None) => return None,
+                Err(_) => Bytes::from_static(b": keepalive\n\n"),
+            };
+
+            Some((Ok(chunk), (receiver, false)))
+        });
+
+        HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header(("Cache-Control", "no-cache"))
+            // keeps nginx from buffering the stream
+            .insert_header(("X-Accel-Buffering", "no"))
+            .streaming(events)
+    }
+
+    // Same events as JSON text frames, anything the client sends besides pings is ignored
+    pub fn open_websocket(
+        &self,
+        user_id: Uuid,
+        req: &HttpRequest,
+        body: web::Payload
+    ) -> Result<HttpResponse, Error> {
+        let (response, mut session, mut messages) = actix_ws::handle(req, body)?;
+        let mut receiver = self.sender.subscribe();
+
+        actix_web::rt::spawn(async move {
+            let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    event = Self::next_event(&mut receiver, user_id) => {
+                        let Some(event) = event else {
+                            break;
+                        };
+                        let Ok(text) = serde_json::to_string(&event) else {
+                            continue;
+                        };
+                        if session.text(text).await.is_err() {
+                            return;
+                        }
+                    }
+                    message = messages.recv() => match message {
+                        Some(Ok(Message::Ping(bytes))) => {
+                            if session.pong(&bytes).await.is_err() {
+                                return;
+                            }
+                        }
+                        Some(Ok(Message::Close(reason))) => {
+                            let _ = session.close(reason).await;
+                            return;
+                        }
+                        Some(Ok(_)) => {}
+                        _ => break,
+                    },
+                    _ = keepalive.tick() => {
+                        if session.ping(b"").await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            let _ = session.close(None).await;
+        });
+
+        Ok(response)
+    }
+}
\ No newline at end of file
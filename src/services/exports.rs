@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{HttpResponse, Error};
+use bytes::Bytes;
+use chrono::Utc;
+use futures_util::stream;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{ExportFormat, NoteFilter, QueryParams};
+use crate::repositories::NotebookRepository;
+use crate::services::NoteService;
+use crate::utils::ExportEncoder;
+
+// Notes loaded from the database per chunk of the response
+const EXPORT_PAGE_SIZE: i64 = 100;
+
+pub struct ExportService {
+    notes: Arc<NoteService>,
+    notebooks: NotebookRepository,
+}
+
+// Where an export left off between two chunks of the response
+struct ExportCursor {
+    notes: Arc<NoteService>,
+    user_id: Uuid,
+    filter: NoteFilter,
+    encoder: ExportEncoder,
+    offset: i64,
+    // set when the caller passed a limit
+    remaining: Option<i64>,
+}
+
+enum ExportStep {
+    Header(ExportCursor),
+    Notes(ExportCursor),
+    Footer(ExportCursor),
+    Done,
+}
+
+impl ExportCursor {
+    // The next page of notes encoded as one chunk, None once every note went out
+    async fn next_chunk(&mut self) -> anyhow::Result<Option<Bytes>> {
+        let page_size = self.remaining.map_or(EXPORT_PAGE_SIZE, |remaining| remaining.min(EXPORT_PAGE_SIZE));
+        if page_size <= 0 {
+            return Ok(None);
+        }
+
+        let notes = self.notes
+            .get_notes_page(self.user_id, &self.filter, page_size, self.offset)
+            .await?;
+        if notes.is_empty() {
+            return Ok(None);
+        }
+
+        let mut chunk = Vec::new();
+        for note in &notes {
+            chunk.extend(self.encoder.encode(note)?);
+        }
+
+        self.offset += notes.len() as i64;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= notes.len() as i64;
+        }
+
+        Ok(Some(Bytes::from(chunk)))
+    }
+}
+
+impl ExportService {
+    pub fn new(pool: PgPool, notes: Arc<NoteService>) -> Self {
+        Self {
+            notes,
+            notebooks: NotebookRepository::new(pool),
+        }
+    }
+
+    // Streams every note matching the GET /notes filters, a page at a time
+    pub async fn export_notes(
+        &self,
+        user_id: Uuid,
+        format: ExportFormat,
+        params: &QueryParams
+    ) -> Result<HttpResponse, Error> {
+        let filter = match NoteService::note_filter(params) {
+            Ok(filter) => filter,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        };
+
+        // notes only carry the id of their notebook, folders need the whole tree
+        let notebooks = self.notebooks
+            .get_user_notebooks(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let cursor = ExportCursor {
+            notes: self.notes.clone(),
+            user_id,
+            filter,
+            encoder: ExportEncoder::new(format, notebooks),
+            offset: params.offset.unwrap_or(0).max(0),
+            remaining: params.limit,
+        };
+
+        let body = stream::unfold(ExportStep::Header(cursor), move |step| async move {
+            match step {
+                ExportStep::Header(cursor) => match cursor.encoder.header() {
+                    Ok(header) => Some((Ok(Bytes::from(header)), ExportStep::Notes(cursor))),
+                    Err(e) => {
+                        eprintln!("Export for user {} failed: {}", user_id, e);
+                        Some((Err(actix_web::error::ErrorInternalServerError(e)), ExportStep::Done))
+                    }
+                },
+                ExportStep::Notes(mut cursor) => match cursor.next_chunk().await {
+                    Ok(Some(chunk)) => Some((Ok(chunk), ExportStep::Notes(cursor))),
+                    Ok(None) => Some((Ok(Bytes::new()), ExportStep::Footer(cursor))),
+                    Err(e) => {
+                        // headers are long gone, all we can do is cut the response short
+                        eprintln!("Export for user {} failed: {}", user_id, e);
+                        Some((Err(actix_web::error::ErrorInternalServerError(e)), ExportStep::Done))
+                    }
+                },
+                ExportStep::Footer(cursor) => match cursor.encoder.finish() {
+                    Ok(footer) => Some((Ok(Bytes::from(footer)), ExportStep::Done)),
+                    Err(e) => {
+                        eprintln!("Export for user {} failed: {}", user_id, e);
+                        Some((Err(actix_web::error::ErrorInternalServerError(e)), ExportStep::Done))
+                    }
+                },
+                ExportStep::Done => None,
+            }
+        });
+
+        let filename = format!("notes-export-{}.{}", Utc::now().format("%Y%m%d"), format.extension());
+
+        Ok(HttpResponse::Ok()
+            .content_type(format.content_type())
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(filename)],
+            })
+            .streaming(body))
+    }
+}
\ No newline at end of file
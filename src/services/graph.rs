@@ -0,0 +1,70 @@
+use actix_web::{HttpResponse, Error};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{GraphFormat, GraphParams, NoteGraph};
+use crate::repositories::NoteLinkRepository;
+use crate::utils::normalize_tag;
+
+const DEFAULT_GRAPH_DEPTH: u32 = 1;
+const MAX_GRAPH_DEPTH: u32 = 10;
+
+pub struct GraphService {
+    links: NoteLinkRepository
+}
+
+impl GraphService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            links: NoteLinkRepository::new(pool)
+        }
+    }
+
+    pub async fn get_graph(
+        &self,
+        user_id: Uuid,
+        params: &GraphParams
+    ) -> Result<HttpResponse, Error> {
+        let tag = match params.tag.as_deref().map(normalize_tag).transpose() {
+            Ok(tag) => tag,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        };
+        let notebook = params.notebook.as_deref().map(str::trim);
+
+        let depth = params.depth.unwrap_or(DEFAULT_GRAPH_DEPTH);
+        if depth > MAX_GRAPH_DEPTH {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": format!("depth must be at most {}", MAX_GRAPH_DEPTH)
+            })));
+        }
+
+        let nodes = self.links
+            .get_graph_nodes(user_id, tag.as_deref(), notebook)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let edges = self.links
+            .get_graph_edges(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let mut graph = NoteGraph::new(nodes, edges);
+
+        if let Some(note_id) = params.note_id {
+            if !graph.nodes.iter().any(|node| node.id == note_id) {
+                return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+            }
+            graph = graph.neighborhood(note_id, depth);
+        }
+
+        match params.format.unwrap_or_default() {
+            GraphFormat::Json => Ok(HttpResponse::Ok().json(graph)),
+            GraphFormat::Dot => Ok(HttpResponse::Ok()
+                .content_type("text/vnd.graphviz; charset=utf-8")
+                .body(graph.to_dot())),
+            GraphFormat::Graphml => Ok(HttpResponse::Ok()
+                .content_type("application/graphml+xml; charset=utf-8")
+                .body(graph.to_graphml())),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use actix_multipart::Multipart;
+use actix_web::{HttpResponse, Error};
+use bytes::Bytes;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::jobs::{JobQueue, RunImport};
+use crate::models::{Import, ImportFileError, ImportFormat, ImportStatus, NewNote, UserImports};
+use crate::repositories::{ImportRepository, NotebookRepository, NoteLinkRepository, NoteRepository};
+use crate::services::{note_size, QuotaService};
+use crate::storage::BlobStore;
+use crate::utils::{detect_import_format, parse_import, parse_wiki_links, read_file_upload, ImportedFile, UploadError};
+
+pub struct ImportService {
+    pub repo: ImportRepository,
+    notes: NoteRepository,
+    notebooks: NotebookRepository,
+    links: NoteLinkRepository,
+    jobs: JobQueue,
+    store: Arc<dyn BlobStore>,
+    quotas: Arc<QuotaService>,
+    max_import_size: u64,
+}
+
+impl ImportService {
+    pub fn new(pool: PgPool, max_import_size: u64, store: Arc<dyn BlobStore>, quotas: Arc<QuotaService>) -> Self {
+        Self {
+            repo: ImportRepository::new(pool.clone()),
+            notes: NoteRepository::new(pool.clone()),
+            notebooks: NotebookRepository::new(pool.clone()),
+            links: NoteLinkRepository::new(pool.clone()),
+            jobs: JobQueue::new(pool),
+            store,
+            quotas,
+            max_import_size,
+        }
+    }
+
+    // Where an upload waits for its import job
+    fn upload_key(import_id: Uuid) -> String {
+        format!("import{}", import_id.simple())
+    }
+
+    // Accept the upload and queue an import job, the client polls GET /import/{id}
+    pub async fn start_import(
+        &self,
+        user_id: Uuid,
+        format: Option<ImportFormat>,
+        payload: Multipart
+    ) -> Result<HttpResponse, Error> {
+        let upload = match read_file_upload(payload, self.max_import_size).await {
+            Ok(upload) => upload,
+            Err(UploadError::Missing) => {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": "Missing multipart field 'file'" })));
+            }
+            Err(UploadError::TooLarge) => {
+                return Ok(HttpResponse::PayloadTooLarge().json(json!({
+                    "message": format!("Imports must not exceed {} bytes", self.max_import_size)
+                })));
+            }
+            Err(UploadError::Malformed(message)) => {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": message })));
+            }
+        };
+
+        let format = format.unwrap_or_else(|| detect_import_format(&upload.filename, &upload.data));
+        let filename = upload.filename.rsplit(['/', '\\']).next().unwrap_or_default().to_string();
+
+        let import = self.repo
+            .create_import(user_id, format, &filename)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let queued = async {
+            self.store.put(&Self::upload_key(import.id), upload.data).await?;
+            self.jobs.enqueue(&RunImport { import_id: import.id, user_id, format }).await
+        };
+
+        if let Err(e) = queued.await {
+            eprintln!("Could not queue import {}: {}", import.id, e);
+            let errors = vec![ImportFileError { file: filename, message: "Could not queue the import".to_string() }];
+            let _ = self.repo.finish_import(import.id, ImportStatus::Failed, &errors).await;
+            return Err(actix_web::error::ErrorInternalServerError(e));
+        }
+
+        Ok(HttpResponse::Accepted().json(import))
+    }
+
+    // Job handler, picks up where an earlier attempt stopped
+    pub async fn run_queued_import(&self, import_id: Uuid, user_id: Uuid, format: ImportFormat) -> anyhow::Result<()> {
+        // gone along with its user
+        let Some(import) = self.repo.get_import(import_id, user_id).await? else {
+            return Ok(());
+        };
+
+        if !matches!(import.status, ImportStatus::Completed | ImportStatus::Failed) {
+            let data = self.store.get(&Self::upload_key(import_id), None).await?;
+            self.run_import(&import, format, data).await?;
+        }
+
+        self.store.delete(&Self::upload_key(import_id)).await
+    }
+
+    // Job handler for an import that failed its last attempt
+    pub async fn fail_queued_import(&self, import_id: Uuid, user_id: Uuid) -> anyhow::Result<()> {
+        let Some(import) = self.repo.get_import(import_id, user_id).await? else {
+            return Ok(());
+        };
+        let mut errors = import.errors.0;
+        errors.push(ImportFileError { file: import.filename, message: "Import failed unexpectedly".to_string() });
+        self.repo.finish_import(import_id, ImportStatus::Failed, &errors).await?;
+        self.store.delete(&Self::upload_key(import_id)).await
+    }
+
+    async fn run_import(
+        &self,
+        import: &Import,
+        format: ImportFormat,
+        data: Bytes
+    ) -> anyhow::Result<()> {
+        // unzipping and converting is CPU bound, keep it off the async workers
+        let parsed = actix_web::rt::task::spawn_blocking(move || parse_import(format, &data)).await?;
+
+        let files = match parsed {
+            Ok(files) => files,
+            Err(message) => {
+                let file = import.filename.clone();
+                self.repo.finish_import(import.id, ImportStatus::Failed, &[ImportFileError { file, message }]).await?;
+                return Ok(());
+            }
+        };
+
+        self.repo.start_import(import.id, files.len() as i32).await?;
+
+        // an earlier attempt may have got part of the way
+        let mut errors = import.errors.0.clone();
+        let mut imported = import.imported_notes;
+        let resume_from = import.processed_files as usize;
+        let mut notebooks = HashMap::new();
+
+        for (index, file) in files.into_iter().enumerate().skip(resume_from) {
+            match self.import_file(import.user_id, file, &mut notebooks).await {
+                Ok(skipped) => {
+                    imported += 1;
+                    errors.extend(skipped);
+                }
+                Err(error) => errors.push(error),
+            }
+            self.repo.record_progress(import.id, index as i32 + 1, imported, &errors).await?;
+        }
+
+        self.repo.finish_import(import.id, ImportStatus::Completed, &errors).await?;
+
+        Ok(())
+    }
+
+    // Folders become notebooks, created once per import and looked up by path afterwards
+    async fn notebook_for_path(
+        &self,
+        user_id: Uuid,
+        path: &[String],
+        notebooks: &mut HashMap<Vec<String>, Uuid>
+    ) -> anyhow::Result<Option<Uuid>> {
+        let mut parent_id = None;
+
+        for depth in 1..=path.len() {
+            let key = path[..depth].iter().map(|name| name.to_lowercase()).collect::<Vec<_>>();
+            let notebook_id = match notebooks.get(&key) {
+                Some(notebook_id) => *notebook_id,
+                None => {
+                    let notebook_id = self.notebooks.find_or_create(user_id, parent_id, &path[depth - 1]).await?;
+                    notebooks.insert(key, notebook_id);
+                    notebook_id
+                }
+            };
+            parent_id = Some(notebook_id);
+        }
+
+        Ok(parent_id)
+    }
+
+    // Returns what was left out of an imported note, for the error report
+    async fn import_file(
+        &self,
+        user_id: Uuid,
+        file: ImportedFile,
+        notebooks: &mut HashMap<Vec<String>, Uuid>
+    ) -> Result<Vec<ImportFileError>, ImportFileError> {
+        let imported = file?;
+
+        let notebook_id = self
+            .notebook_for_path(user_id, &imported.notebook, notebooks)
+            .await
+            .map_err(|e| ImportFileError {
+                file: imported.file.clone(),
+                message: format!("Could not create notebook: {}", e),
+            })?;
+
+        let new_note = NewNote::new(user_id, imported.title, imported.content)
+            .with_notebook(notebook_id)
+            .with_tags(imported.tags);
+
+        let size = note_size(&new_note.title, &new_note.content);
+        let stored = async {
+            let mut tx = self.notes.begin().await?;
+            if let Err(exceeded) = self.quotas.check_note_write_in(&mut tx, user_id, None, size).await? {
+                return Ok(Err(exceeded));
+            }
+
+            let note = self.notes
+                .create_imported_note_in(&mut tx, new_note, imported.created_at, imported.updated_at)
+                .await?;
+            self.links
+                .replace_note_links_in(&mut tx, note.id, user_id, &parse_wiki_links(&note.content))
+                .await?;
+            tx.commit().await?;
+
+            anyhow::Ok(Ok(()))
+        };
+
+        let stored = stored.await.map_err(|e| ImportFileError {
+            file: imported.file.clone(),
+            message: format!("Could not save note: {}", e),
+        })?;
+
+        if let Err(exceeded) = stored {
+            return Err(ImportFileError { file: imported.file, message: exceeded.message() });
+        }
+
+        Ok(imported.skipped
+            .into_iter()
+            .map(|message| ImportFileError { file: imported.file.clone(), message })
+            .collect())
+    }
+
+    pub async fn get_import(
+        &self,
+        user_id: Uuid,
+        import_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        let import = self.repo
+            .get_import(import_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match import {
+            Some(import) => Ok(HttpResponse::Ok().json(import)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Import not found" })))
+        }
+    }
+
+    pub async fn get_imports(
+        &self,
+        user_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        let imports = self.repo
+            .get_user_imports(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserImports { imports }))
+    }
+}
\ No newline at end of file
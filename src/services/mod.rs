@@ -1,5 +1,35 @@
+pub mod attachments;
+pub mod collab;
+pub mod events;
+pub mod exports;
+pub mod graph;
+pub mod imports;
+pub mod notebooks;
 pub mod notes;
+pub mod quotas;
+pub mod reminders;
+pub mod saved_searches;
+pub mod shares;
+pub mod sync;
+pub mod tasks;
+pub mod templates;
 pub mod users;
+pub mod webhooks;
 
+pub use attachments::*;
+pub use collab::*;
+pub use events::*;
+pub use exports::*;
+pub use graph::*;
+pub use imports::*;
+pub use notebooks::*;
 pub use notes::*;
-pub use users::*;
\ No newline at end of file
+pub use quotas::*;
+pub use reminders::*;
+pub use saved_searches::*;
+pub use shares::*;
+pub use sync::*;
+pub use tasks::*;
+pub use templates::*;
+pub use users::*;
+pub use webhooks::*;
\ No newline at end of file
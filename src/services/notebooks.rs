@@ -0,0 +1,158 @@
+use actix_web::{HttpResponse, Error};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{CreateNotebookDto, NewNotebook, UpdateNotebook, UserNotebooks};
+use crate::repositories::NotebookRepository;
+use crate::utils::normalize_notebook_name;
+
+pub struct NotebookService {
+    pub repo: NotebookRepository,
+}
+
+impl NotebookService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: NotebookRepository::new(pool),
+        }
+    }
+
+    async fn name_taken(
+        &self,
+        user_id: Uuid,
+        parent_id: Option<Uuid>,
+        name: &str,
+        except: Option<Uuid>
+    ) -> Result<bool, Error> {
+        let existing = self.repo
+            .find_by_name(user_id, parent_id, name)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(existing.is_some_and(|notebook| Some(notebook.id) != except))
+    }
+
+    async fn parent_exists(&self, user_id: Uuid, parent_id: Option<Uuid>) -> Result<bool, Error> {
+        let Some(parent_id) = parent_id else {
+            return Ok(true);
+        };
+
+        let parent = self.repo
+            .get_notebook_by_id(parent_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(parent.is_some())
+    }
+
+    pub async fn get_notebooks(&self, user_id: Uuid) -> Result<HttpResponse, Error> {
+        let notebooks = self.repo
+            .get_user_notebooks(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserNotebooks { notebooks }))
+    }
+
+    pub async fn get_notebook_by_id(&self, user_id: Uuid, notebook_id: Uuid) -> Result<HttpResponse, Error> {
+        let notebook = self.repo
+            .get_notebook_by_id(notebook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match notebook {
+            Some(notebook) => Ok(HttpResponse::Ok().json(notebook)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Notebook not found" })))
+        }
+    }
+
+    pub async fn create_notebook(&self, user_id: Uuid, dto: CreateNotebookDto) -> Result<HttpResponse, Error> {
+        let name = match normalize_notebook_name(&dto.name) {
+            Ok(name) => name,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        };
+
+        if !self.parent_exists(user_id, dto.parent_id).await? {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Parent notebook not found" })));
+        }
+
+        if self.name_taken(user_id, dto.parent_id, &name, None).await? {
+            return Ok(HttpResponse::Conflict().json(json!({ "message": "A notebook with this name already exists here" })));
+        }
+
+        let notebook = self.repo
+            .create_notebook(NewNotebook { user_id, parent_id: dto.parent_id, name })
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Created().json(notebook))
+    }
+
+    pub async fn update_notebook(
+        &self,
+        user_id: Uuid,
+        notebook_id: Uuid,
+        mut update: UpdateNotebook
+    ) -> Result<HttpResponse, Error> {
+        let existing = self.repo
+            .get_notebook_by_id(notebook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(existing) = existing else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Notebook not found" })));
+        };
+
+        if let Some(name) = &update.name {
+            match normalize_notebook_name(name) {
+                Ok(name) => update.name = Some(name),
+                Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+            }
+        }
+
+        if let Some(Some(parent_id)) = update.parent_id {
+            if !self.parent_exists(user_id, Some(parent_id)).await? {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": "Parent notebook not found" })));
+            }
+
+            let cycle = self.repo
+                .is_within(notebook_id, parent_id, user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+
+            if cycle {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": "A notebook can't be moved into itself" })));
+            }
+        }
+
+        let parent_id = update.parent_id.unwrap_or(existing.parent_id);
+        let name = update.name.as_deref().unwrap_or(&existing.name);
+        if self.name_taken(user_id, parent_id, name, Some(notebook_id)).await? {
+            return Ok(HttpResponse::Conflict().json(json!({ "message": "A notebook with this name already exists here" })));
+        }
+
+        let notebook = self.repo
+            .update_notebook(notebook_id, user_id, update)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match notebook {
+            Some(notebook) => Ok(HttpResponse::Ok().json(notebook)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Notebook not found" })))
+        }
+    }
+
+    // Nested notebooks go with it, the notes inside are kept without a notebook
+    pub async fn delete_notebook(&self, user_id: Uuid, notebook_id: Uuid) -> Result<HttpResponse, Error> {
+        let deleted = self.repo
+            .delete_notebook(notebook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if deleted {
+            Ok(HttpResponse::NoContent().json(json!({ "message": "Notebook deleted" })))
+        } else {
+            Ok(HttpResponse::NotFound().json(json!({ "message": "Notebook not found" })))
+        }
+    }
+}
\ No newline at end of file
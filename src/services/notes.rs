@@ -1,45 +1,146 @@
 use actix_web::{HttpResponse, Error};
+use actix_web::http::StatusCode;
 use serde_json::json;
-use sqlx::PgPool;
+use sqlx::{Acquire, PgConnection, PgPool};
+use std::sync::Arc;
 use uuid::Uuid;
-use crate::models::{NewNote, UpdateNote, UserNotes};
-use crate::repositories::NoteRepository;
+use crate::models::{BatchMode, BatchOperation, BatchRequest, BatchResponse, BatchResult, NewNote, Note, NoteFlags, QuotaExceeded, UpdateNote, UserNotes, HighlightMarkers, NoteBacklinks, NoteFilter, NoteFormat, NoteLinks, NoteSort, QueryParams, RenderedMarkdown, RenderedNote, SearchOptions, SearchResults};
+use crate::repositories::{NoteLinkRepository, NoteRepository, NotebookRepository};
+use crate::services::{note_size, quota_exceeded_response, QuotaService};
+use crate::utils::{normalize_tags, parse_search_query, parse_wiki_links, render_markdown, rewrite_wiki_links, RenderCache, SearchQuery};
+
+// Rendered notes kept in memory before the cache starts over
+const RENDER_CACHE_CAPACITY: usize = 1000;
+const MAX_BATCH_OPERATIONS: usize = 1000;
 
 pub struct NoteService {
-    pub repo: NoteRepository
+    pub repo: NoteRepository,
+    links: NoteLinkRepository,
+    notebooks: NotebookRepository,
+    renders: RenderCache,
+    quotas: Arc<QuotaService>
 }
 
 impl NoteService {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: PgPool, quotas: Arc<QuotaService>) -> Self {
         Self {
-            repo: NoteRepository::new(pool)
+            repo: NoteRepository::new(pool.clone()),
+            links: NoteLinkRepository::new(pool.clone()),
+            notebooks: NotebookRepository::new(pool),
+            renders: RenderCache::new(RENDER_CACHE_CAPACITY),
+            quotas
         }
     }
 
     pub async fn get_note_by_id(
         &self,
         user_id: Uuid,
-        note_id: Uuid
+        note_id: Uuid,
+        format: NoteFormat
     ) -> Result<HttpResponse, Error> {
-        let note = self.repo
+        let mut note = self.repo
             .get_note_by_id(note_id, user_id)
             .await
             .map_err(actix_web::error::ErrorInternalServerError)?;
 
-        match note {
-            Some(extracted_note) => Ok(HttpResponse::Ok().json(extracted_note)),
-            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })))
+        // someone else's note shared with this user
+        if note.is_none() {
+            note = self.repo
+                .get_shared_note(note_id, user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+        }
+
+        match (note, format) {
+            (Some(extracted_note), NoteFormat::Json) => Ok(HttpResponse::Ok().json(extracted_note)),
+            (Some(extracted_note), NoteFormat::Html) => {
+                let html = self.renders
+                    .get_or_render(extracted_note.id, extracted_note.updated_at, &extracted_note.content)
+                    .to_string();
+                Ok(HttpResponse::Ok().json(RenderedNote { note: extracted_note, html }))
+            }
+            (None, _) => Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })))
+        }
+    }
+
+    // Ad-hoc rendering for previews, nothing to cache against
+    pub fn render_markdown(&self, markdown: &str) -> HttpResponse {
+        HttpResponse::Ok().json(RenderedMarkdown { html: render_markdown(markdown) })
+    }
+
+    // Shared by GET /notes and saved searches: search when a term is given, list otherwise
+    pub async fn list_notes(
+        &self,
+        user_id: Uuid,
+        params: &QueryParams
+    ) -> Result<HttpResponse, Error> {
+        let filter = match Self::note_filter(params) {
+            Ok(filter) => filter,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        };
+
+        match filter {
+            NoteFilter::All { sort, include_archived } => {
+                self.get_users_notes(user_id, sort, include_archived, params.limit, params.offset).await
+            }
+            NoteFilter::Search { query, options } => {
+                self.search_notes(user_id, query, options, params.limit, params.offset).await
+            }
+        }
+    }
+
+    // Validates the filtering part of GET /notes params, also used by exports
+    pub fn note_filter(params: &QueryParams) -> Result<NoteFilter, String> {
+        let sort = params.sort.unwrap_or_default();
+        let include_archived = params.include_archived.unwrap_or(false);
+
+        let Some(search_term) = &params.search else {
+            return Ok(NoteFilter::All { sort, include_archived });
+        };
+
+        let markers = HighlightMarkers::new(params.highlight_start.clone(), params.highlight_end.clone())?;
+        let query = parse_search_query(search_term)?;
+
+        let options = SearchOptions {
+            mode: params.mode.unwrap_or_default(),
+            sort,
+            markers,
+            include_archived,
+        };
+
+        Ok(NoteFilter::Search { query, options })
+    }
+
+    // One page of the notes matched by a filter, without search snippets
+    pub async fn get_notes_page(
+        &self,
+        user_id: Uuid,
+        filter: &NoteFilter,
+        limit: i64,
+        offset: i64
+    ) -> anyhow::Result<Vec<Note>> {
+        match filter {
+            NoteFilter::All { sort, include_archived } => {
+                self.repo.get_user_notes(user_id, *sort, *include_archived, Some(limit), Some(offset)).await
+            }
+            NoteFilter::Search { query, .. } if query.is_empty() => Ok(Vec::new()),
+            NoteFilter::Search { query, options } => {
+                let hits = self.repo.search_notes(user_id, query, options, Some(limit), Some(offset)).await?;
+                Ok(hits.into_iter().map(|hit| hit.note).collect())
+            }
         }
     }
 
     pub async fn get_users_notes(
         &self,
         user_id: Uuid,
+        sort: NoteSort,
+        include_archived: bool,
         limit: Option<i64>,
         offset: Option<i64>
     ) -> Result<HttpResponse, Error> {
         let user_notes = self.repo
-            .get_user_notes(user_id, limit, offset)
+            .get_user_notes(user_id, sort, include_archived, limit, offset)
             .await
             .map_err(actix_web::error::ErrorInternalServerError)?;
         
@@ -49,23 +150,89 @@ impl NoteService {
     pub async fn search_notes(
         &self,
         user_id: Uuid,
-        search_term: String,
-        limit: Option<i64>
+        query: SearchQuery,
+        options: SearchOptions,
+        limit: Option<i64>,
+        offset: Option<i64>
     ) -> Result<HttpResponse, Error> {
-        let search_results = self.repo
-            .search_notes(user_id, &search_term, limit)
+        // an empty query matches nothing, same as plainto_tsquery('')
+        if query.is_empty() {
+            return Ok(HttpResponse::Ok().json(SearchResults {
+                hits: Vec::new(),
+                total: 0,
+                limit: limit.unwrap_or(50),
+                offset: offset.unwrap_or(0),
+            }));
+        }
+
+        let hits = self.repo
+            .search_notes(user_id, &query, &options, limit, offset)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let total = self.repo
+            .count_search_results(user_id, &query, &options)
             .await
             .map_err(actix_web::error::ErrorInternalServerError)?;
         
-        Ok(HttpResponse::Ok().json(search_results))
+        Ok(HttpResponse::Ok().json(SearchResults {
+            hits,
+            total,
+            limit: limit.unwrap_or(50),
+            offset: offset.unwrap_or(0),
+        }))
+    }
+
+    async fn notebook_exists(&self, user_id: Uuid, notebook_id: Option<Uuid>) -> Result<bool, Error> {
+        let Some(notebook_id) = notebook_id else {
+            return Ok(true);
+        };
+
+        let notebook = self.notebooks
+            .get_notebook_by_id(notebook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(notebook.is_some())
     }
 
     pub async fn create_note(
         &self,
-        new_note: NewNote
+        mut new_note: NewNote
     ) -> Result<HttpResponse, Error> {
+        new_note.tags = match normalize_tags(&new_note.tags) {
+            Ok(tags) => tags,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        };
+
+        if !self.notebook_exists(new_note.user_id, new_note.notebook_id).await? {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Notebook not found" })));
+        }
+
+        let mut tx = self.repo
+            .begin()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let quota = self.quotas
+            .check_note_write_in(&mut tx, new_note.user_id, None, note_size(&new_note.title, &new_note.content))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if let Err(exceeded) = quota {
+            return Ok(quota_exceeded_response(exceeded));
+        }
+
         let new_note = self.repo
-            .create_note(new_note)
+            .create_note_in(&mut tx, new_note)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        tx.commit()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        self.sync_links(&new_note)
             .await
             .map_err(actix_web::error::ErrorInternalServerError)?;
         
@@ -76,30 +243,363 @@ impl NoteService {
         &self,
         user_id: Uuid,
         note_id: Uuid,
-        updated_note: UpdateNote
+        mut updated_note: UpdateNote,
+        rewrite_links: bool
     ) -> Result<HttpResponse, Error> {
+        if let Some(tags) = &updated_note.tags {
+            match normalize_tags(tags) {
+                Ok(tags) => updated_note.tags = Some(tags),
+                Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+            }
+        }
+
+        if !self.notebook_exists(user_id, updated_note.notebook_id.flatten()).await? {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Notebook not found" })));
+        }
+
+        let mut tx = self.repo
+            .begin()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        // the note's size can't change between reading it and the write from here on
+        self.quotas
+            .lock_note_usage_in(&mut tx, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let note = self.repo
+            .get_note_by_id_in(&mut tx, note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(existing_note) = note else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        };
+
+        let new_size = note_size(
+            updated_note.title.as_deref().unwrap_or(&existing_note.title),
+            updated_note.content.as_deref().unwrap_or(&existing_note.content)
+        );
+        let quota = self.quotas
+            .check_note_write_in(&mut tx, user_id, Some(note_size(&existing_note.title, &existing_note.content)), new_size)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if let Err(exceeded) = quota {
+            return Ok(quota_exceeded_response(exceeded));
+        }
+
+        // has to be looked up before the rename, afterwards the links no longer resolve
+        let renamed = updated_note.title.as_ref().filter(|title| **title != existing_note.title).cloned();
+        let referrers = match &renamed {
+            Some(_) if rewrite_links => self.links
+                .get_title_referrers(note_id, user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?,
+            _ => Vec::new()
+        };
+
+        let updated_note = self.repo
+            .update_note_in(&mut tx, note_id, user_id, updated_note)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(note) = updated_note else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        };
+
+        // a rename that would push a referrer over quota is refused as a whole
+        let mut rewritten = Vec::new();
+        if let Some(new_title) = renamed {
+            for referrer_id in referrers {
+                let referrer = self
+                    .rewrite_referrer_in(&mut tx, referrer_id, user_id, &existing_note.title, &new_title)
+                    .await
+                    .map_err(actix_web::error::ErrorInternalServerError)?;
+
+                match referrer {
+                    Ok(Some(referrer)) => rewritten.push(referrer),
+                    Ok(None) => {}
+                    Err(exceeded) => return Ok(quota_exceeded_response(exceeded)),
+                }
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        for written in std::iter::once(&note).chain(&rewritten) {
+            self.sync_links(written)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+        }
+
+        // a self-referencing note was just rewritten, return the fresh copy
         let note = self.repo
             .get_note_by_id(note_id, user_id)
             .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .unwrap_or(note);
+
+        Ok(HttpResponse::Ok().json(note))
+    }
+
+    // Re-parse the [[links]] in a note after its content was written
+    async fn sync_links(&self, note: &Note) -> anyhow::Result<()> {
+        let links = parse_wiki_links(&note.content);
+        self.links.replace_note_links(note.id, note.user_id, &links).await
+    }
+
+    // The rewritten referrer, None when it's gone or had nothing to rewrite
+    async fn rewrite_referrer_in(
+        &self,
+        conn: &mut PgConnection,
+        referrer_id: Uuid,
+        user_id: Uuid,
+        old_title: &str,
+        new_title: &str
+    ) -> anyhow::Result<Result<Option<Note>, QuotaExceeded>> {
+        let Some(referrer) = self.repo.get_note_by_id_in(conn, referrer_id, user_id).await? else {
+            return Ok(Ok(None));
+        };
+
+        let content = rewrite_wiki_links(&referrer.content, old_title, new_title);
+        if content == referrer.content {
+            return Ok(Ok(None));
+        }
+
+        let old_size = note_size(&referrer.title, &referrer.content);
+        let new_size = note_size(&referrer.title, &content);
+        if let Err(exceeded) = self.quotas.check_note_write_in(conn, user_id, Some(old_size), new_size).await? {
+            return Ok(Err(exceeded));
+        }
+
+        let update = UpdateNote::new().with_content(content);
+        Ok(Ok(self.repo.update_note_in(conn, referrer_id, user_id, update).await?))
+    }
+
+    // Runs every operation in one transaction, each behind its own savepoint so a
+    // failure can be undone on its own in per_item mode
+    pub async fn run_batch(
+        &self,
+        user_id: Uuid,
+        batch: BatchRequest
+    ) -> Result<HttpResponse, Error> {
+        if batch.operations.len() > MAX_BATCH_OPERATIONS {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": format!("A batch can hold at most {} operations", MAX_BATCH_OPERATIONS)
+            })));
+        }
+
+        let mode = batch.mode.unwrap_or_default();
+        let total = batch.operations.len();
+        let mut results = Vec::with_capacity(total);
+
+        let mut tx = self.repo
+            .begin()
+            .await
             .map_err(actix_web::error::ErrorInternalServerError)?;
 
-        if let Some(_) = note {
-            let updated_note = self.repo
-                .update_note(note_id, user_id, updated_note)
+        for (index, operation) in batch.operations.into_iter().enumerate() {
+            let mut savepoint = tx
+                .begin()
                 .await
                 .map_err(actix_web::error::ErrorInternalServerError)?;
 
-            match updated_note {
-                Some(note) => {
-                    Ok(HttpResponse::Ok().json(note))
+            let result = match self.apply_operation(&mut savepoint, user_id, index, operation).await {
+                Ok(result) => result,
+                Err(e) => {
+                    eprintln!("Batch operation {} failed: {}", index, e);
+                    BatchResult::failed(index, 500, "Database error")
+                }
+            };
+
+            if result.succeeded() {
+                savepoint.commit().await.map_err(actix_web::error::ErrorInternalServerError)?;
+            } else {
+                savepoint.rollback().await.map_err(actix_web::error::ErrorInternalServerError)?;
+            }
+
+            let status = result.status;
+            let failed = !result.succeeded();
+            results.push(result);
+
+            if failed && mode == BatchMode::Atomic {
+                tx.rollback().await.map_err(actix_web::error::ErrorInternalServerError)?;
+
+                let mut results: Vec<BatchResult> = results
+                    .into_iter()
+                    .map(|result| match result.succeeded() {
+                        true => BatchResult::failed(result.index, 424, "Rolled back, another operation failed"),
+                        false => result,
+                    })
+                    .collect();
+                results.extend(
+                    (index + 1..total).map(|index| BatchResult::failed(index, 424, "Not run, another operation failed"))
+                );
+
+                let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                return Ok(HttpResponse::build(status).json(BatchResponse { committed: false, results }));
+            }
+        }
+
+        tx.commit().await.map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(BatchResponse { committed: true, results }))
+    }
+
+    async fn apply_operation(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        index: usize,
+        operation: BatchOperation
+    ) -> anyhow::Result<BatchResult> {
+        match operation {
+            BatchOperation::Create(dto) => {
+                let tags = match normalize_tags(&dto.tags) {
+                    Ok(tags) => tags,
+                    Err(message) => return Ok(BatchResult::failed(index, 400, message)),
+                };
+
+                if let Some(notebook_id) = dto.notebook_id
+                    && self.notebooks.get_notebook_by_id_in(conn, notebook_id, user_id).await?.is_none()
+                {
+                    return Ok(BatchResult::failed(index, 400, "Notebook not found"));
+                }
+
+                let mut new_note = NewNote::new(user_id, dto.title, dto.content);
+                if let Some(language) = dto.language {
+                    new_note = new_note.with_language(language);
                 }
-                None => {
-                    Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })))
+                new_note = new_note
+                    .with_schedule(dto.due_at, dto.remind_at)
+                    .with_notebook(dto.notebook_id)
+                    .with_tags(tags);
+
+                let size = note_size(&new_note.title, &new_note.content);
+                if let Err(exceeded) = self.quotas.check_note_write_in(conn, user_id, None, size).await? {
+                    return Ok(BatchResult::failed(index, exceeded.status().as_u16(), exceeded.message()));
                 }
+
+                let note = self.repo.create_note_in(conn, new_note).await?;
+                self.links.replace_note_links_in(conn, note.id, user_id, &parse_wiki_links(&note.content)).await?;
+
+                Ok(BatchResult::ok(index, 201, Some(note)))
             }
-        } else {
-            Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })))
+            BatchOperation::Update { id, title, content, language, due_at, remind_at } => {
+                let Some(existing) = self.repo.get_note_by_id_in(conn, id, user_id).await? else {
+                    return Ok(BatchResult::failed(index, 404, "Note not found"));
+                };
+
+                let new_size = note_size(
+                    title.as_deref().unwrap_or(&existing.title),
+                    content.as_deref().unwrap_or(&existing.content)
+                );
+                let old_size = note_size(&existing.title, &existing.content);
+                if let Err(exceeded) = self.quotas.check_note_write_in(conn, user_id, Some(old_size), new_size).await? {
+                    return Ok(BatchResult::failed(index, exceeded.status().as_u16(), exceeded.message()));
+                }
+
+                let update = UpdateNote { title, content, language, due_at, remind_at, ..UpdateNote::new() };
+                let Some(note) = self.repo.update_note_in(conn, id, user_id, update).await? else {
+                    return Ok(BatchResult::failed(index, 404, "Note not found"));
+                };
+
+                self.links.replace_note_links_in(conn, note.id, user_id, &parse_wiki_links(&note.content)).await?;
+
+                Ok(BatchResult::ok(index, 200, Some(note)))
+            }
+            BatchOperation::Delete { id } => match self.repo.delete_note_in(conn, id, user_id).await? {
+                true => Ok(BatchResult::ok(index, 204, None)),
+                false => Ok(BatchResult::failed(index, 404, "Note not found")),
+            },
+            BatchOperation::Move { id, notebook_id } => {
+                if let Some(notebook_id) = notebook_id
+                    && self.notebooks.get_notebook_by_id_in(conn, notebook_id, user_id).await?.is_none()
+                {
+                    return Ok(BatchResult::failed(index, 400, "Notebook not found"));
+                }
+
+                let update = UpdateNote { notebook_id: Some(notebook_id), ..UpdateNote::new() };
+                match self.repo.update_note_in(conn, id, user_id, update).await? {
+                    Some(note) => Ok(BatchResult::ok(index, 200, Some(note))),
+                    None => Ok(BatchResult::failed(index, 404, "Note not found")),
+                }
+            }
+            BatchOperation::Tag { id, add, remove } => {
+                let (add, remove) = match (normalize_tags(&add), normalize_tags(&remove)) {
+                    (Ok(add), Ok(remove)) => (add, remove),
+                    (Err(message), _) | (_, Err(message)) => return Ok(BatchResult::failed(index, 400, message)),
+                };
+
+                let Some(existing) = self.repo.get_note_by_id_in(conn, id, user_id).await? else {
+                    return Ok(BatchResult::failed(index, 404, "Note not found"));
+                };
+
+                let tags: Vec<String> = existing.tags
+                    .into_iter()
+                    .chain(add)
+                    .filter(|tag| !remove.contains(tag))
+                    .collect();
+                let tags = match normalize_tags(&tags) {
+                    Ok(tags) => tags,
+                    Err(message) => return Ok(BatchResult::failed(index, 400, message)),
+                };
+
+                let update = UpdateNote { tags: Some(tags), ..UpdateNote::new() };
+                match self.repo.update_note_in(conn, id, user_id, update).await? {
+                    Some(note) => Ok(BatchResult::ok(index, 200, Some(note))),
+                    None => Ok(BatchResult::failed(index, 404, "Note not found")),
+                }
+            }
+        }
+    }
+
+    pub async fn get_note_links(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        let note = self.repo
+            .get_note_by_id(note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if note.is_none() {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        }
+
+        let links = self.links
+            .get_note_links(note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(NoteLinks { links }))
+    }
+
+    pub async fn get_note_backlinks(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        let note = self.repo
+            .get_note_by_id(note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if note.is_none() {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
         }
+
+        let backlinks = self.links
+            .get_note_backlinks(note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(NoteBacklinks { backlinks }))
     }
 
     pub async fn delete_note(
@@ -118,4 +618,21 @@ impl NoteService {
             Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })))
         }
     }
+
+    pub async fn set_note_flags(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid,
+        flags: NoteFlags
+    ) -> Result<HttpResponse, Error> {
+        let note = self.repo
+            .set_note_flags(note_id, user_id, flags)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match note {
+            Some(note) => Ok(HttpResponse::Ok().json(note)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })))
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use actix_web::{HttpResponse, Error};
+use serde_json::json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use crate::config::QuotaSettings;
+use crate::models::{NoteUsage, QuotaExceeded, UsageLimit, UserUsage};
+use crate::repositories::{AttachmentRepository, UsageRepository};
+
+pub struct QuotaService {
+    usage: UsageRepository,
+    attachments: AttachmentRepository,
+    settings: QuotaSettings,
+    attachment_quota: u64,
+}
+
+// What a note counts against the quotas
+pub fn note_size(title: &str, content: &str) -> i64 {
+    (title.len() + content.len()) as i64
+}
+
+pub fn quota_exceeded_response(exceeded: QuotaExceeded) -> HttpResponse {
+    HttpResponse::build(exceeded.status()).json(json!({ "message": exceeded.message() }))
+}
+
+impl QuotaService {
+    pub fn new(pool: PgPool, settings: QuotaSettings, attachment_quota: u64) -> Self {
+        Self {
+            usage: UsageRepository::new(pool.clone()),
+            attachments: AttachmentRepository::new(pool),
+            settings,
+            attachment_quota,
+        }
+    }
+
+    // Concurrent writes of one user wait for each other from here until the transaction
+    // ends. Taken before reading a note whose size the check depends on.
+    pub async fn lock_note_usage_in(&self, conn: &mut PgConnection, user_id: Uuid) -> anyhow::Result<()> {
+        self.usage.lock_note_usage_in(conn, user_id).await?;
+        Ok(())
+    }
+
+    // Has to run in the transaction that writes the note, the usage stays locked until
+    // it commits. Sees the usage changed by earlier writes of the same transaction.
+    pub async fn check_note_write_in(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        old_size: Option<i64>,
+        new_size: i64
+    ) -> anyhow::Result<Result<(), QuotaExceeded>> {
+        let usage = self.usage.lock_note_usage_in(conn, user_id).await?;
+        Ok(self.check(usage, old_size, new_size))
+    }
+
+    // old_size is None for a new note. Notes already over a lowered limit can still
+    // be edited as long as they don't grow.
+    fn check(&self, usage: NoteUsage, old_size: Option<i64>, new_size: i64) -> Result<(), QuotaExceeded> {
+        let growth = new_size - old_size.unwrap_or(0);
+
+        if new_size as u64 > self.settings.max_note_size && growth > 0 {
+            return Err(QuotaExceeded::NoteTooLarge { limit: self.settings.max_note_size });
+        }
+
+        if old_size.is_none() && usage.note_count as u64 >= self.settings.max_notes {
+            return Err(QuotaExceeded::TooManyNotes { limit: self.settings.max_notes });
+        }
+
+        if growth > 0 && (usage.content_bytes + growth) as u64 > self.settings.max_content_bytes {
+            return Err(QuotaExceeded::ContentStorage {
+                limit: self.settings.max_content_bytes,
+                used: usage.content_bytes,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_usage(&self, user_id: Uuid) -> Result<HttpResponse, Error> {
+        let notes = self.usage
+            .get_note_usage(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let attachment_bytes = self.attachments
+            .get_user_usage(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserUsage {
+            notes: UsageLimit { used: notes.note_count, limit: self.settings.max_notes },
+            content_bytes: UsageLimit { used: notes.content_bytes, limit: self.settings.max_content_bytes },
+            attachment_bytes: UsageLimit { used: attachment_bytes, limit: self.attachment_quota },
+            max_note_size: self.settings.max_note_size,
+        }))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+use actix_web::{HttpRequest, HttpResponse, Error};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{CalendarFeed, DueNotesParams, UserNotes};
+use crate::repositories::{NoteRepository, UserRepository};
+use crate::utils::{generate_calendar_token, hash_calendar_token, render_calendar};
+
+const DEFAULT_UPCOMING_DAYS: i64 = 7;
+const MAX_UPCOMING_DAYS: i64 = 366;
+const DEFAULT_DUE_NOTES_LIMIT: i64 = 50;
+const MAX_DUE_NOTES_LIMIT: i64 = 500;
+
+pub struct ReminderService {
+    notes: NoteRepository,
+    users: UserRepository,
+}
+
+impl ReminderService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            notes: NoteRepository::new(pool.clone()),
+            users: UserRepository::new(pool),
+        }
+    }
+
+    fn due_notes_limit(params: &DueNotesParams) -> i64 {
+        params.limit.unwrap_or(DEFAULT_DUE_NOTES_LIMIT).clamp(1, MAX_DUE_NOTES_LIMIT)
+    }
+
+    pub async fn get_upcoming_notes(&self, user_id: Uuid, params: &DueNotesParams) -> Result<HttpResponse, Error> {
+        let days = params.days.unwrap_or(DEFAULT_UPCOMING_DAYS);
+        if !(1..=MAX_UPCOMING_DAYS).contains(&days) {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": format!("days must be between 1 and {}", MAX_UPCOMING_DAYS)
+            })));
+        }
+
+        let until = Utc::now() + Duration::days(days);
+        let notes = self.notes
+            .get_upcoming_notes(user_id, until, Self::due_notes_limit(params))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserNotes { notes }))
+    }
+
+    pub async fn get_overdue_notes(&self, user_id: Uuid, params: &DueNotesParams) -> Result<HttpResponse, Error> {
+        let notes = self.notes
+            .get_overdue_notes(user_id, Self::due_notes_limit(params))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserNotes { notes }))
+    }
+
+    // Creates the feed URL, or replaces it so the old one stops working
+    pub async fn rotate_calendar_token(&self, user_id: Uuid, req: &HttpRequest) -> Result<HttpResponse, Error> {
+        let token = generate_calendar_token();
+        self.users
+            .set_calendar_token_hash(user_id, Some(hash_calendar_token(&token)))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        // the feed sits next to this route, under the same API prefix and version
+        let connection = req.connection_info();
+        let calendar_path = req.path().trim_end_matches("/token");
+        let url = format!("{}://{}{}/{}.ics", connection.scheme(), connection.host(), calendar_path, token);
+
+        Ok(HttpResponse::Created().json(CalendarFeed { token, url }))
+    }
+
+    pub async fn revoke_calendar_token(&self, user_id: Uuid) -> Result<HttpResponse, Error> {
+        self.users
+            .set_calendar_token_hash(user_id, None)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::NoContent().json(json!({"message": "Calendar feed revoked"})))
+    }
+
+    // The token is the only credential, calendar apps can't log in
+    pub async fn get_calendar_feed(&self, token: &str) -> Result<HttpResponse, Error> {
+        let user_id = self.users
+            .find_id_by_calendar_token_hash(&hash_calendar_token(token))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(user_id) = user_id else {
+            return Ok(HttpResponse::NotFound().json(json!({"message": "Calendar not found"})));
+        };
+
+        let notes = self.notes
+            .get_scheduled_notes(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok()
+            .content_type("text/calendar; charset=utf-8")
+            .insert_header(("Content-Disposition", "inline; filename=\"notes.ics\""))
+            .insert_header(("Cache-Control", "private, no-cache"))
+            .body(render_calendar(&notes)))
+    }
+
+    // There's no mailer yet, so reminders go out as note.reminder webhooks and as
+    // live events to the owner's SSE and WebSocket connections
+    pub async fn send_due_reminders(&self) -> anyhow::Result<u64> {
+        self.notes.send_due_reminders().await
+    }
+}
\ No newline at end of file
@@ -0,0 +1,156 @@
+use actix_web::{HttpResponse, Error};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{
+    CreateSavedSearchDto, NewSavedSearch, SavedSearchRunParams, UpdateSavedSearch, UserSavedSearches
+};
+use crate::repositories::SavedSearchRepository;
+use crate::services::NoteService;
+use crate::utils::parse_search_query;
+
+pub struct SavedSearchService {
+    pub repo: SavedSearchRepository
+}
+
+impl SavedSearchService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: SavedSearchRepository::new(pool)
+        }
+    }
+
+    // Reject queries GET /notes would reject, so a saved search can always be run
+    fn validate_query(&self, query: &str) -> Result<(), String> {
+        parse_search_query(query).map(|_| ())
+    }
+
+    async fn name_taken(&self, user_id: Uuid, name: &str, except: Option<Uuid>) -> Result<bool, Error> {
+        let existing = self.repo
+            .find_by_name(user_id, name)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(existing.is_some_and(|saved_search| Some(saved_search.id) != except))
+    }
+
+    pub async fn get_saved_searches(&self, user_id: Uuid) -> Result<HttpResponse, Error> {
+        let saved_searches = self.repo
+            .get_user_saved_searches(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserSavedSearches { saved_searches }))
+    }
+
+    pub async fn get_saved_search_by_id(
+        &self,
+        user_id: Uuid,
+        saved_search_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        let saved_search = self.repo
+            .get_saved_search_by_id(saved_search_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match saved_search {
+            Some(saved_search) => Ok(HttpResponse::Ok().json(saved_search)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Saved search not found" })))
+        }
+    }
+
+    pub async fn create_saved_search(
+        &self,
+        user_id: Uuid,
+        dto: CreateSavedSearchDto
+    ) -> Result<HttpResponse, Error> {
+        let new_saved_search = NewSavedSearch::new(user_id, dto);
+
+        if new_saved_search.name.is_empty() {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Name must not be empty" })));
+        }
+
+        if let Err(message) = self.validate_query(&new_saved_search.query) {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": message })));
+        }
+
+        if self.name_taken(user_id, &new_saved_search.name, None).await? {
+            return Ok(HttpResponse::Conflict().json(json!({ "message": "A saved search with this name already exists" })));
+        }
+
+        let saved_search = self.repo
+            .create_saved_search(new_saved_search)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Created().json(saved_search))
+    }
+
+    pub async fn update_saved_search(
+        &self,
+        user_id: Uuid,
+        saved_search_id: Uuid,
+        mut update: UpdateSavedSearch
+    ) -> Result<HttpResponse, Error> {
+        if let Some(name) = &update.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": "Name must not be empty" })));
+            }
+            if self.name_taken(user_id, &name, Some(saved_search_id)).await? {
+                return Ok(HttpResponse::Conflict().json(json!({ "message": "A saved search with this name already exists" })));
+            }
+            update.name = Some(name);
+        }
+
+        if let Some(Err(message)) = update.query.as_deref().map(|query| self.validate_query(query)) {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": message })));
+        }
+
+        let saved_search = self.repo
+            .update_saved_search(saved_search_id, user_id, update)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match saved_search {
+            Some(saved_search) => Ok(HttpResponse::Ok().json(saved_search)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Saved search not found" })))
+        }
+    }
+
+    pub async fn delete_saved_search(
+        &self,
+        user_id: Uuid,
+        saved_search_id: Uuid
+    ) -> Result<HttpResponse, Error> {
+        let deleted = self.repo
+            .delete_saved_search(saved_search_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if deleted {
+            Ok(HttpResponse::NoContent().json(json!({ "message": "Saved search deleted" })))
+        } else {
+            Ok(HttpResponse::NotFound().json(json!({ "message": "Saved search not found" })))
+        }
+    }
+
+    // Run the saved search through the same code path as GET /notes
+    pub async fn get_saved_search_notes(
+        &self,
+        user_id: Uuid,
+        saved_search_id: Uuid,
+        run: SavedSearchRunParams,
+        notes: &NoteService
+    ) -> Result<HttpResponse, Error> {
+        let saved_search = self.repo
+            .get_saved_search_by_id(saved_search_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match saved_search {
+            Some(saved_search) => notes.list_notes(user_id, &saved_search.to_query_params(run)).await,
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Saved search not found" })))
+        }
+    }
+}
\ No newline at end of file
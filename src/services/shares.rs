@@ -0,0 +1,122 @@
+use actix_web::{HttpResponse, Error};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{CreateShareDto, NoteShares, SharedNote, SharedNotes};
+use crate::repositories::{NoteRepository, ShareRepository, UserRepository};
+
+pub struct ShareService {
+    pub repo: ShareRepository,
+    notes: NoteRepository,
+    users: UserRepository,
+}
+
+impl ShareService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: ShareRepository::new(pool.clone()),
+            notes: NoteRepository::new(pool.clone()),
+            users: UserRepository::new(pool),
+        }
+    }
+
+    // Only the owner sees and changes who a note is shared with
+    async fn owns_note(&self, user_id: Uuid, note_id: Uuid) -> Result<bool, Error> {
+        let note = self.notes
+            .get_note_by_id(note_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(note.is_some())
+    }
+
+    pub async fn share_note(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid,
+        dto: CreateShareDto
+    ) -> Result<HttpResponse, Error> {
+        if !self.owns_note(user_id, note_id).await? {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        }
+
+        let recipient = self.users
+            .find_by_email(dto.email.trim())
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(recipient) = recipient else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "User not found" })));
+        };
+
+        if recipient.id == user_id {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "You already own this note" })));
+        }
+
+        let share = self.repo
+            .share_note(note_id, recipient.id, dto.permission.unwrap_or_default())
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Created().json(share))
+    }
+
+    pub async fn get_note_shares(&self, user_id: Uuid, note_id: Uuid) -> Result<HttpResponse, Error> {
+        if !self.owns_note(user_id, note_id).await? {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        }
+
+        let shares = self.repo
+            .get_note_shares(note_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(NoteShares { shares }))
+    }
+
+    // The owner revokes a share, or the user it's shared with leaves it
+    pub async fn delete_share(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid,
+        shared_with: Uuid
+    ) -> Result<HttpResponse, Error> {
+        if shared_with != user_id && !self.owns_note(user_id, note_id).await? {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Note not found" })));
+        }
+
+        let deleted = self.repo
+            .delete_share(note_id, shared_with)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if deleted {
+            Ok(HttpResponse::NoContent().json(json!({ "message": "Share deleted" })))
+        } else {
+            Ok(HttpResponse::NotFound().json(json!({ "message": "Share not found" })))
+        }
+    }
+
+    pub async fn get_shared_notes(&self, user_id: Uuid) -> Result<HttpResponse, Error> {
+        let notes = self.notes
+            .get_shared_notes(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let permissions = self.repo
+            .get_user_permissions(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        // a share revoked between the two queries leaves its note out
+        let notes = notes
+            .into_iter()
+            .filter_map(|note| {
+                let permission = *permissions.get(&note.id)?;
+                Some(SharedNote { note, permission })
+            })
+            .collect();
+
+        Ok(HttpResponse::Ok().json(SharedNotes { notes }))
+    }
+}
\ No newline at end of file
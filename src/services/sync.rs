@@ -0,0 +1,236 @@
+use actix_web::{HttpResponse, Error};
+use serde_json::json;
+use std::sync::Arc;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{ConflictReason, NewNote, Note, SyncChange, SyncChanges, SyncConflict, SyncParams, SyncPush, SyncPushResult, UpdateNote};
+use crate::repositories::{NoteLinkRepository, NoteRepository, SyncRepository};
+use crate::services::{note_size, QuotaService};
+use crate::utils::parse_wiki_links;
+
+const DEFAULT_SYNC_LIMIT: i64 = 500;
+const MAX_SYNC_LIMIT: i64 = 1000;
+const MAX_SYNC_CHANGES: usize = 1000;
+
+pub struct SyncService {
+    pub repo: SyncRepository,
+    notes: NoteRepository,
+    links: NoteLinkRepository,
+    quotas: Arc<QuotaService>,
+    tombstone_retention_days: u32,
+}
+
+// What happened to a single pushed change
+enum SyncOutcome {
+    Applied(Note),
+    Deleted(Uuid),
+    Conflict(SyncConflict),
+}
+
+impl SyncService {
+    pub fn new(pool: PgPool, tombstone_retention_days: u32, quotas: Arc<QuotaService>) -> Self {
+        Self {
+            repo: SyncRepository::new(pool.clone()),
+            notes: NoteRepository::new(pool.clone()),
+            links: NoteLinkRepository::new(pool),
+            quotas,
+            tombstone_retention_days,
+        }
+    }
+
+    pub async fn get_changes(
+        &self,
+        user_id: Uuid,
+        params: &SyncParams
+    ) -> Result<HttpResponse, Error> {
+        let since = match params.since.as_deref().map(str::parse::<i64>) {
+            None => 0,
+            Some(Ok(since)) if since >= 0 => since,
+            Some(_) => return Ok(HttpResponse::BadRequest().json(json!({ "message": "Invalid sync token" })))
+        };
+
+        let limit = params.limit.unwrap_or(DEFAULT_SYNC_LIMIT);
+        if !(1..=MAX_SYNC_LIMIT).contains(&limit) {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": format!("limit must be between 1 and {}", MAX_SYNC_LIMIT)
+            })));
+        }
+
+        // deletes older than the retention period are forgotten, the client has to start over
+        let purged_through = self.repo
+            .get_purged_through()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if since > 0 && since < purged_through {
+            return Ok(HttpResponse::Gone().json(json!({
+                "message": "Sync token expired, sync again without since to fetch every note"
+            })));
+        }
+
+        let (notes, deleted, window) = self.repo
+            .get_changes(user_id, since, limit)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(SyncChanges {
+            notes,
+            deleted,
+            token: window.until.to_string(),
+            has_more: window.has_more,
+        }))
+    }
+
+    // Changes are applied one by one, a conflict only affects its own note
+    pub async fn push_changes(
+        &self,
+        user_id: Uuid,
+        push: SyncPush
+    ) -> Result<HttpResponse, Error> {
+        if push.changes.len() > MAX_SYNC_CHANGES {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": format!("A sync can push at most {} changes", MAX_SYNC_CHANGES)
+            })));
+        }
+
+        let mut result = SyncPushResult::default();
+
+        for change in push.changes {
+            let outcome = self.apply_change(user_id, change)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+
+            match outcome {
+                SyncOutcome::Applied(note) => result.applied.push(note),
+                SyncOutcome::Deleted(note_id) => result.deleted.push(note_id),
+                SyncOutcome::Conflict(conflict) => result.conflicts.push(conflict),
+            }
+        }
+
+        Ok(HttpResponse::Ok().json(result))
+    }
+
+    async fn apply_change(&self, user_id: Uuid, change: SyncChange) -> anyhow::Result<SyncOutcome> {
+        let id = change.id;
+
+        match (change.deleted, change.base_version) {
+            (true, None) => Ok(SyncOutcome::Conflict(SyncConflict::new(
+                id,
+                ConflictReason::Invalid,
+                "Deletes need the base_version the client last saw",
+                None
+            ))),
+            (true, Some(base_version)) => {
+                if self.notes.delete_note_if_version(id, user_id, base_version).await? {
+                    return Ok(SyncOutcome::Deleted(id));
+                }
+
+                match self.notes.get_note_by_id(id, user_id).await? {
+                    Some(note) => Ok(SyncOutcome::Conflict(SyncConflict::new(
+                        id,
+                        ConflictReason::VersionMismatch,
+                        format!("Note is at version {}, not {}", note.version, base_version),
+                        Some(note)
+                    ))),
+                    // already gone, deleting it again is a no-op
+                    None => Ok(SyncOutcome::Deleted(id)),
+                }
+            }
+            (false, None) => {
+                let Some(title) = change.title else {
+                    return Ok(SyncOutcome::Conflict(SyncConflict::new(
+                        id,
+                        ConflictReason::Invalid,
+                        "New notes need a title",
+                        None
+                    )));
+                };
+
+                let mut new_note = NewNote::new(user_id, title, change.content.unwrap_or_default());
+                if let Some(language) = change.language {
+                    new_note = new_note.with_language(language);
+                }
+
+                let mut tx = self.notes.begin().await?;
+
+                let size = note_size(&new_note.title, &new_note.content);
+                if let Err(exceeded) = self.quotas.check_note_write_in(&mut tx, user_id, None, size).await? {
+                    return Ok(SyncOutcome::Conflict(SyncConflict::quota_exceeded(id, exceeded, None)));
+                }
+
+                let created = self.notes.create_synced_note_in(&mut tx, id, new_note).await?;
+                tx.commit().await?;
+
+                match created {
+                    Some(note) => {
+                        self.sync_links(&note).await?;
+                        Ok(SyncOutcome::Applied(note))
+                    }
+                    None => Ok(SyncOutcome::Conflict(SyncConflict::new(
+                        id,
+                        ConflictReason::AlreadyExists,
+                        "A note with this id already exists",
+                        self.notes.get_note_by_id(id, user_id).await?
+                    ))),
+                }
+            }
+            (false, Some(base_version)) => {
+                let update = UpdateNote {
+                    title: change.title,
+                    content: change.content,
+                    language: change.language,
+                    ..UpdateNote::new()
+                };
+
+                let mut tx = self.notes.begin().await?;
+                self.quotas.lock_note_usage_in(&mut tx, user_id).await?;
+
+                // a stale change is reported as a version conflict below, quotas don't matter for it
+                if let Some(note) = self.notes.get_note_by_id_in(&mut tx, id, user_id).await?
+                    && note.version == base_version
+                {
+                    let new_size = note_size(
+                        update.title.as_deref().unwrap_or(&note.title),
+                        update.content.as_deref().unwrap_or(&note.content)
+                    );
+                    let old_size = note_size(&note.title, &note.content);
+                    if let Err(exceeded) = self.quotas.check_note_write_in(&mut tx, user_id, Some(old_size), new_size).await? {
+                        return Ok(SyncOutcome::Conflict(SyncConflict::quota_exceeded(id, exceeded, Some(note))));
+                    }
+                }
+
+                let updated = self.notes.update_note_if_version_in(&mut tx, id, user_id, base_version, update).await?;
+                tx.commit().await?;
+
+                if let Some(note) = updated {
+                    self.sync_links(&note).await?;
+                    return Ok(SyncOutcome::Applied(note));
+                }
+
+                match self.notes.get_note_by_id(id, user_id).await? {
+                    Some(note) => Ok(SyncOutcome::Conflict(SyncConflict::new(
+                        id,
+                        ConflictReason::VersionMismatch,
+                        format!("Note is at version {}, not {}", note.version, base_version),
+                        Some(note)
+                    ))),
+                    None => Ok(SyncOutcome::Conflict(SyncConflict::new(
+                        id,
+                        ConflictReason::Deleted,
+                        "Note was deleted on the server",
+                        None
+                    ))),
+                }
+            }
+        }
+    }
+
+    async fn sync_links(&self, note: &Note) -> anyhow::Result<()> {
+        let links = parse_wiki_links(&note.content);
+        self.links.replace_note_links(note.id, note.user_id, &links).await
+    }
+
+    pub async fn purge_tombstones(&self) -> anyhow::Result<i64> {
+        self.repo.purge_tombstones(self.tombstone_retention_days).await
+    }
+}
\ No newline at end of file
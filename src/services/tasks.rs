@@ -0,0 +1,98 @@
+use actix_web::{HttpResponse, Error};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{TaskParams, UpdateNote, UpdateTaskDto, UserTasks};
+use crate::repositories::{NoteRepository, TaskRepository};
+use crate::utils::set_task_done;
+
+const DEFAULT_TASKS_LIMIT: i64 = 100;
+const MAX_TASKS_LIMIT: i64 = 500;
+
+pub struct TaskService {
+    repo: TaskRepository,
+    notes: NoteRepository,
+}
+
+impl TaskService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: TaskRepository::new(pool.clone()),
+            notes: NoteRepository::new(pool),
+        }
+    }
+
+    pub async fn get_tasks(&self, user_id: Uuid, params: &TaskParams) -> Result<HttpResponse, Error> {
+        let limit = params.limit.unwrap_or(DEFAULT_TASKS_LIMIT).clamp(1, MAX_TASKS_LIMIT);
+        let offset = params.offset.unwrap_or(0).max(0);
+
+        let tasks = self.repo
+            .get_user_tasks(user_id, params.status.unwrap_or_default().done(), limit, offset)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserTasks { tasks }))
+    }
+
+    // Rewrites the task's line in the note content, only if the note is still at the
+    // version the line was read from (and the one the client saw, when it sent one)
+    pub async fn update_task(
+        &self,
+        user_id: Uuid,
+        note_id: Uuid,
+        task_index: i32,
+        update: UpdateTaskDto
+    ) -> Result<HttpResponse, Error> {
+        let line = self.repo
+            .get_task_line(note_id, user_id, task_index)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(line) = line else {
+            let note = self.notes
+                .get_note_by_id(note_id, user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+
+            let message = if note.is_some() { "Task not found" } else { "Note not found" };
+            return Ok(HttpResponse::NotFound().json(json!({"message": message})));
+        };
+
+        if update.version.is_some_and(|version| version != line.version) {
+            return Ok(HttpResponse::Conflict().json(json!({
+                "message": "The note has changed, reload it and try again",
+                "version": line.version
+            })));
+        }
+
+        let done = update.done.unwrap_or(!line.done);
+        if done == line.done {
+            let note = self.notes
+                .get_note_by_id(note_id, user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?;
+
+            return match note {
+                Some(note) => Ok(HttpResponse::Ok().json(note)),
+                None => Ok(HttpResponse::NotFound().json(json!({"message": "Note not found"}))),
+            };
+        }
+
+        let Some(content) = set_task_done(&line.content, line.line_number as usize, done) else {
+            return Err(actix_web::error::ErrorInternalServerError("task line has no checkbox"));
+        };
+
+        // only the checkbox changes, so the note's [[links]] stay as they are
+        let note = self.notes
+            .update_note_if_version(note_id, user_id, line.version, UpdateNote::new().with_content(content))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match note {
+            Some(note) => Ok(HttpResponse::Ok().json(note)),
+            None => Ok(HttpResponse::Conflict().json(json!({
+                "message": "The note changed while the task was being updated, reload it and try again"
+            }))),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use actix_web::{HttpResponse, Error};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::models::{
+    CreateNoteTemplateDto, InstantiateTemplateDto, NewNote, NewNoteTemplate, UpdateNoteTemplate, UserNoteTemplates
+};
+use crate::repositories::{NotebookRepository, NoteTemplateRepository, UserRepository};
+use crate::services::NoteService;
+use crate::utils::{fill_template, is_valid_template_variable, normalize_tags};
+
+pub struct NoteTemplateService {
+    pub repo: NoteTemplateRepository,
+    notebooks: NotebookRepository,
+    users: UserRepository,
+}
+
+impl NoteTemplateService {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            repo: NoteTemplateRepository::new(pool.clone()),
+            notebooks: NotebookRepository::new(pool.clone()),
+            users: UserRepository::new(pool),
+        }
+    }
+
+    async fn notebook_exists(&self, user_id: Uuid, notebook_id: Option<Uuid>) -> Result<bool, Error> {
+        let Some(notebook_id) = notebook_id else {
+            return Ok(true);
+        };
+
+        let notebook = self.notebooks
+            .get_notebook_by_id(notebook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(notebook.is_some())
+    }
+
+    async fn name_taken(&self, user_id: Uuid, name: &str, except: Option<Uuid>) -> Result<bool, Error> {
+        let existing = self.repo
+            .find_by_name(user_id, name)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(existing.is_some_and(|template| Some(template.id) != except))
+    }
+
+    pub async fn get_templates(&self, user_id: Uuid) -> Result<HttpResponse, Error> {
+        let templates = self.repo
+            .get_user_templates(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserNoteTemplates { templates }))
+    }
+
+    pub async fn get_template_by_id(&self, user_id: Uuid, template_id: Uuid) -> Result<HttpResponse, Error> {
+        let template = self.repo
+            .get_template_by_id(template_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match template {
+            Some(template) => Ok(HttpResponse::Ok().json(template)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Template not found" })))
+        }
+    }
+
+    pub async fn create_template(&self, user_id: Uuid, dto: CreateNoteTemplateDto) -> Result<HttpResponse, Error> {
+        let mut new_template = NewNoteTemplate::new(user_id, dto);
+
+        match normalize_tags(&new_template.tags) {
+            Ok(tags) => new_template.tags = tags,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        }
+
+        if !self.notebook_exists(user_id, new_template.notebook_id).await? {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Notebook not found" })));
+        }
+
+        if new_template.name.is_empty() {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Name must not be empty" })));
+        }
+
+        if new_template.title.trim().is_empty() {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Title must not be empty" })));
+        }
+
+        if self.name_taken(user_id, &new_template.name, None).await? {
+            return Ok(HttpResponse::Conflict().json(json!({ "message": "A template with this name already exists" })));
+        }
+
+        let template = self.repo
+            .create_template(new_template)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Created().json(template))
+    }
+
+    pub async fn update_template(
+        &self,
+        user_id: Uuid,
+        template_id: Uuid,
+        mut update: UpdateNoteTemplate
+    ) -> Result<HttpResponse, Error> {
+        if let Some(tags) = &update.tags {
+            match normalize_tags(tags) {
+                Ok(tags) => update.tags = Some(tags),
+                Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+            }
+        }
+
+        if !self.notebook_exists(user_id, update.notebook_id.flatten()).await? {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Notebook not found" })));
+        }
+
+        if let Some(name) = &update.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Ok(HttpResponse::BadRequest().json(json!({ "message": "Name must not be empty" })));
+            }
+            if self.name_taken(user_id, &name, Some(template_id)).await? {
+                return Ok(HttpResponse::Conflict().json(json!({ "message": "A template with this name already exists" })));
+            }
+            update.name = Some(name);
+        }
+
+        if update.title.as_deref().is_some_and(|title| title.trim().is_empty()) {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "Title must not be empty" })));
+        }
+
+        let template = self.repo
+            .update_template(template_id, user_id, update)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match template {
+            Some(template) => Ok(HttpResponse::Ok().json(template)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Template not found" })))
+        }
+    }
+
+    pub async fn delete_template(&self, user_id: Uuid, template_id: Uuid) -> Result<HttpResponse, Error> {
+        let deleted = self.repo
+            .delete_template(template_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if deleted {
+            Ok(HttpResponse::NoContent().json(json!({ "message": "Template deleted" })))
+        } else {
+            Ok(HttpResponse::NotFound().json(json!({ "message": "Template not found" })))
+        }
+    }
+
+    // Fills in the template and creates the note through the same path as POST /notes.
+    // {{date}} and {{time}} are in UTC, clients in other timezones can pass their own.
+    pub async fn create_note_from_template(
+        &self,
+        user_id: Uuid,
+        template_id: Uuid,
+        dto: InstantiateTemplateDto,
+        notes: &NoteService
+    ) -> Result<HttpResponse, Error> {
+        if let Some(name) = dto.variables.keys().find(|name| !is_valid_template_variable(name)) {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": format!("Invalid variable name '{}', use letters, digits, '_', '.' and '-'", name)
+            })));
+        }
+
+        let template = self.repo
+            .get_template_by_id(template_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(template) = template else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Template not found" })));
+        };
+
+        let user = self.users
+            .find_by_id(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+
+        let now = Utc::now();
+        let mut values = HashMap::from([
+            ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+            ("time".to_string(), now.format("%H:%M").to_string()),
+            ("datetime".to_string(), now.format("%Y-%m-%d %H:%M").to_string()),
+            ("weekday".to_string(), now.format("%A").to_string()),
+            ("user.username".to_string(), user.username),
+            ("user.email".to_string(), user.email),
+        ]);
+        values.extend(dto.variables);
+
+        let title = fill_template(&template.title, &values).trim().to_string();
+        if title.is_empty() {
+            return Ok(HttpResponse::BadRequest().json(json!({ "message": "The note title is empty once the template is filled in" })));
+        }
+
+        let mut new_note = NewNote::new(user_id, title, fill_template(&template.content, &values));
+        if let Some(language) = template.language {
+            new_note = new_note.with_language(language);
+        }
+        new_note = new_note
+            .with_schedule(dto.due_at, dto.remind_at)
+            .with_notebook(template.notebook_id)
+            .with_tags(template.tags);
+
+        notes.create_note(new_note).await
+    }
+}
\ No newline at end of file
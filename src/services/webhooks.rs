@@ -0,0 +1,341 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use actix_web::{HttpResponse, Error};
+use chrono::Utc;
+use futures_util::future::join_all;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::config::WebhookSettings;
+use crate::models::{
+    CreateWebhookDto, CreatedWebhook, DeliveryParams, DueDelivery, NewWebhook, UpdateWebhook, UserWebhooks,
+    WebhookDeliveries, PING_EVENT, WEBHOOK_EVENTS
+};
+use crate::repositories::WebhookRepository;
+use crate::utils::{generate_webhook_secret, is_public_address, resolve_public_host, sign_webhook_payload, PublicResolver};
+
+// Deliveries sent at once by one dispatcher round
+const DISPATCH_BATCH_SIZE: i64 = 20;
+// How often the dispatcher looks for due deliveries when the queue is empty
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY_SECS: u64 = 6 * 3600;
+const MIN_SECRET_LENGTH: usize = 16;
+
+pub struct WebhookService {
+    pub repo: WebhookRepository,
+    client: reqwest::Client,
+    settings: WebhookSettings,
+}
+
+enum DeliveryResult {
+    Delivered(i32),
+    Failed(Option<i32>, String),
+}
+
+impl WebhookService {
+    pub fn new(pool: PgPool, settings: WebhookSettings) -> Self {
+        let mut client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(settings.timeout))
+            // a redirect would send the signed payload somewhere the user didn't register
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(concat!("rust_notes_api-webhooks/", env!("CARGO_PKG_VERSION")));
+        if !settings.allow_private_networks {
+            client = client.dns_resolver(Arc::new(PublicResolver));
+        }
+        let client = client.build().expect("Webhook HTTP client settings are valid");
+
+        Self {
+            repo: WebhookRepository::new(pool),
+            client,
+            settings,
+        }
+    }
+
+    // The host of a URL written as an IP address, which skips the resolver
+    fn literal_address(url: &reqwest::Url) -> Option<IpAddr> {
+        url.host_str()?.trim_start_matches('[').trim_end_matches(']').parse().ok()
+    }
+
+    async fn validate_url(&self, url: &str) -> Result<String, String> {
+        let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("Webhook URLs must use http or https".to_string());
+        }
+        let Some(host) = parsed.host_str() else {
+            return Err("Webhook URLs must have a host".to_string());
+        };
+
+        if !self.settings.allow_private_networks {
+            match Self::literal_address(&parsed) {
+                Some(ip) if !is_public_address(ip) => {
+                    return Err(format!("{} is not a public address", ip));
+                }
+                Some(_) => {}
+                None => {
+                    resolve_public_host(host, parsed.port_or_known_default().unwrap_or(0)).await?;
+                }
+            }
+        }
+
+        Ok(parsed.to_string())
+    }
+
+    fn validate_events(events: Vec<String>) -> Result<Vec<String>, String> {
+        let mut valid: Vec<String> = Vec::new();
+        for event in events {
+            let event = event.trim().to_lowercase();
+            if !WEBHOOK_EVENTS.contains(&event.as_str()) {
+                return Err(format!("Unknown event '{}', expected one of {}", event, WEBHOOK_EVENTS.join(", ")));
+            }
+            if !valid.contains(&event) {
+                valid.push(event);
+            }
+        }
+        if valid.is_empty() {
+            return Err("Subscribe to at least one event".to_string());
+        }
+        Ok(valid)
+    }
+
+    pub async fn get_webhooks(&self, user_id: Uuid) -> Result<HttpResponse, Error> {
+        let webhooks = self.repo
+            .get_user_webhooks(user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(UserWebhooks { webhooks }))
+    }
+
+    pub async fn get_webhook_by_id(&self, user_id: Uuid, webhook_id: Uuid) -> Result<HttpResponse, Error> {
+        let webhook = self.repo
+            .get_webhook_by_id(webhook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match webhook {
+            Some(webhook) => Ok(HttpResponse::Ok().json(webhook)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Webhook not found" })))
+        }
+    }
+
+    pub async fn create_webhook(&self, user_id: Uuid, dto: CreateWebhookDto) -> Result<HttpResponse, Error> {
+        let url = match self.validate_url(&dto.url).await {
+            Ok(url) => url,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        };
+
+        let events = match Self::validate_events(dto.events) {
+            Ok(events) => events,
+            Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+        };
+
+        let secret = dto.secret.unwrap_or_else(generate_webhook_secret);
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "message": format!("Secrets must be at least {} characters long", MIN_SECRET_LENGTH)
+            })));
+        }
+
+        let webhook = self.repo
+            .create_webhook(NewWebhook::new(user_id, url, secret, events))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let secret = webhook.secret.clone();
+        Ok(HttpResponse::Created().json(CreatedWebhook { webhook, secret }))
+    }
+
+    pub async fn update_webhook(
+        &self,
+        user_id: Uuid,
+        webhook_id: Uuid,
+        mut update: UpdateWebhook
+    ) -> Result<HttpResponse, Error> {
+        if let Some(url) = &update.url {
+            match self.validate_url(url).await {
+                Ok(url) => update.url = Some(url),
+                Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+            }
+        }
+
+        if let Some(events) = update.events.take() {
+            match Self::validate_events(events) {
+                Ok(events) => update.events = Some(events),
+                Err(message) => return Ok(HttpResponse::BadRequest().json(json!({ "message": message })))
+            }
+        }
+
+        let webhook = self.repo
+            .update_webhook(webhook_id, user_id, update)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        match webhook {
+            Some(webhook) => Ok(HttpResponse::Ok().json(webhook)),
+            None => Ok(HttpResponse::NotFound().json(json!({ "message": "Webhook not found" })))
+        }
+    }
+
+    pub async fn delete_webhook(&self, user_id: Uuid, webhook_id: Uuid) -> Result<HttpResponse, Error> {
+        let deleted = self.repo
+            .delete_webhook(webhook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if deleted {
+            Ok(HttpResponse::NoContent().json(json!({ "message": "Webhook deleted" })))
+        } else {
+            Ok(HttpResponse::NotFound().json(json!({ "message": "Webhook not found" })))
+        }
+    }
+
+    // Queue a ping to check the receiver and the signature, it shows up in the delivery log
+    pub async fn ping_webhook(&self, user_id: Uuid, webhook_id: Uuid) -> Result<HttpResponse, Error> {
+        let webhook = self.repo
+            .get_webhook_by_id(webhook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        let Some(webhook) = webhook else {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Webhook not found" })));
+        };
+
+        let payload = json!({
+            "type": PING_EVENT,
+            "at": Utc::now(),
+            "user_id": user_id,
+            "webhook_id": webhook.id,
+        });
+
+        let delivery = self.repo
+            .queue_delivery(webhook.id, PING_EVENT, payload)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Accepted().json(delivery))
+    }
+
+    pub async fn get_deliveries(
+        &self,
+        user_id: Uuid,
+        webhook_id: Uuid,
+        params: DeliveryParams
+    ) -> Result<HttpResponse, Error> {
+        let webhook = self.repo
+            .get_webhook_by_id(webhook_id, user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        if webhook.is_none() {
+            return Ok(HttpResponse::NotFound().json(json!({ "message": "Webhook not found" })));
+        }
+
+        let limit = params.limit.unwrap_or(50).clamp(1, 100);
+        let offset = params.offset.unwrap_or(0).max(0);
+
+        let deliveries = self.repo
+            .get_deliveries(webhook_id, params.status, limit, offset)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+        Ok(HttpResponse::Ok().json(WebhookDeliveries { deliveries }))
+    }
+
+    // Send due deliveries until the server stops. Several instances can run this at once,
+    // each claims different deliveries
+    pub async fn dispatch(&self) {
+        loop {
+            match self.dispatch_due().await {
+                Ok(sent) if sent as i64 == DISPATCH_BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to dispatch webhook deliveries: {}", e),
+            }
+            tokio::time::sleep(DISPATCH_INTERVAL).await;
+        }
+    }
+
+    async fn dispatch_due(&self) -> anyhow::Result<usize> {
+        // long enough for the request to time out before anyone else picks the delivery up
+        let lease = (self.settings.timeout + 30) as f64;
+        let deliveries = self.repo.claim_due_deliveries(DISPATCH_BATCH_SIZE, lease).await?;
+
+        let results = join_all(deliveries.iter().map(|delivery| self.send(delivery))).await;
+
+        for (delivery, result) in deliveries.iter().zip(results) {
+            match result {
+                DeliveryResult::Delivered(status_code) => {
+                    self.repo.record_success(delivery, status_code).await?;
+                }
+                DeliveryResult::Failed(status_code, error) => {
+                    let attempts = delivery.attempts as u32 + 1;
+                    let retry_at = (attempts < self.settings.max_attempts).then(|| {
+                        let delay = self.settings.retry_base_delay
+                            .saturating_mul(2u64.saturating_pow(attempts - 1))
+                            .min(MAX_RETRY_DELAY_SECS);
+                        Utc::now() + chrono::Duration::seconds(delay as i64)
+                    });
+
+                    let disable_after = self.settings.disable_after_failures as i32;
+                    let disabled = self.repo
+                        .record_failure(delivery, status_code, &error, retry_at, disable_after)
+                        .await?;
+                    if disabled {
+                        println!("⚠️  Disabled webhook {} after {} consecutive failures", delivery.webhook_id, disable_after);
+                    }
+                }
+            }
+        }
+
+        Ok(deliveries.len())
+    }
+
+    async fn send(&self, delivery: &DueDelivery) -> DeliveryResult {
+        let body = match serde_json::to_vec(&delivery.payload.0) {
+            Ok(body) => body,
+            Err(e) => return DeliveryResult::Failed(None, e.to_string()),
+        };
+        let signature = sign_webhook_payload(&delivery.secret, Utc::now().timestamp(), &body);
+
+        // hosts go through PublicResolver, addresses have to be checked here
+        if !self.settings.allow_private_networks
+            && let Ok(url) = reqwest::Url::parse(&delivery.url)
+            && let Some(ip) = Self::literal_address(&url)
+            && !is_public_address(ip)
+        {
+            return DeliveryResult::Failed(None, format!("{} is not a public address", ip));
+        }
+
+        let response = self.client
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header("X-Webhook-Id", delivery.webhook_id.to_string())
+            .header("X-Webhook-Delivery", delivery.id.to_string())
+            .header("X-Webhook-Event", &delivery.event)
+            .header("X-Webhook-Signature", signature)
+            .body(body)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => {
+                DeliveryResult::Delivered(response.status().as_u16() as i32)
+            }
+            // the body stays out of the delivery log, it's whatever the receiver chose to answer
+            Ok(response) => {
+                let status = response.status();
+                DeliveryResult::Failed(Some(status.as_u16() as i32), format!("HTTP {}", status))
+            }
+            Err(e) => {
+                // the reason, like a host resolving to a private address, is further down the chain
+                let mut error = e.to_string();
+                let mut source = std::error::Error::source(&e);
+                while let Some(cause) = source {
+                    error = format!("{}: {}", error, cause);
+                    source = cause.source();
+                }
+                DeliveryResult::Failed(None, error)
+            }
+        }
+    }
+}
\ No newline at end of file
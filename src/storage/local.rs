@@ -0,0 +1,70 @@
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use crate::storage::{BlobStore, ByteRange};
+
+// Stores blobs as files under a root directory, sharded by the first two key characters
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        if key.len() < 2 || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(anyhow!("Invalid blob key: {}", key));
+        }
+        Ok(self.root.join(&key[..2]).join(key))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, data: Bytes) -> Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // write to a temporary file first so readers never see a partial blob
+        let tmp_path = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+        fs::write(&tmp_path, &data).await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> Result<Bytes> {
+        let path = self.path_for(key)?;
+
+        match range {
+            Some(range) => {
+                let mut file = fs::File::open(&path).await?;
+                file.seek(SeekFrom::Start(range.start)).await?;
+                let mut buffer = vec![0; range.len() as usize];
+                file.read_exact(&mut buffer).await?;
+                Ok(Bytes::from(buffer))
+            }
+            None => Ok(Bytes::from(fs::read(&path).await?)),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(fs::try_exists(self.path_for(key)?).await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path_for(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+pub mod local;
+pub mod s3;
+
+pub use local::*;
+pub use s3::*;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+
+// Inclusive byte range of a blob, as in an HTTP Range header
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+// Where attachment contents live, keyed by their SHA-256
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, data: Bytes) -> Result<()>;
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> Result<Bytes>;
+    async fn exists(&self, key: &str) -> Result<bool>;
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+// ranges are inclusive, so there's no empty one
+#[allow(clippy::len_without_is_empty)]
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    // Parse a single `bytes=` range against a blob of `size` bytes
+    //
+    // Ok(None) means the header should be ignored and the whole blob served,
+    // Err means the range can't be satisfied and the response should be a 416
+    pub fn parse(header: &str, size: u64) -> Result<Option<Self>, String> {
+        let Some(spec) = header.trim().strip_prefix("bytes=") else {
+            return Ok(None);
+        };
+
+        // multiple ranges would need a multipart response, serve the whole blob instead
+        if spec.contains(',') {
+            return Ok(None);
+        }
+
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Ok(None);
+        };
+
+        let range = match (start.trim(), end.trim()) {
+            // bytes=-500, the last 500 bytes
+            ("", suffix) => {
+                let Ok(suffix) = suffix.parse::<u64>() else {
+                    return Ok(None);
+                };
+                if suffix == 0 || size == 0 {
+                    return Err(format!("Range not satisfiable for {} bytes", size));
+                }
+                Self { start: size.saturating_sub(suffix), end: size - 1 }
+            }
+            // bytes=500- or bytes=500-999
+            (start, end) => {
+                let Ok(start) = start.parse::<u64>() else {
+                    return Ok(None);
+                };
+                let end = if end.is_empty() {
+                    size.saturating_sub(1)
+                } else {
+                    match end.parse::<u64>() {
+                        Ok(end) => end.min(size.saturating_sub(1)),
+                        Err(_) => return Ok(None),
+                    }
+                };
+                if start >= size {
+                    return Err(format!("Range not satisfiable for {} bytes", size));
+                }
+                if end < start {
+                    return Ok(None);
+                }
+                Self { start, end }
+            }
+        };
+
+        Ok(Some(range))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u64, end: u64) -> Option<ByteRange> {
+        Some(ByteRange { start, end })
+    }
+
+    #[test]
+    fn parses_bounded_and_open_ranges() {
+        assert_eq!(ByteRange::parse("bytes=0-99", 1000), Ok(range(0, 99)));
+        assert_eq!(ByteRange::parse("bytes=500-", 1000), Ok(range(500, 999)));
+        assert_eq!(ByteRange::parse(" bytes= 10 - 19 ", 1000), Ok(range(10, 19)));
+    }
+
+    #[test]
+    fn parses_suffix_ranges() {
+        assert_eq!(ByteRange::parse("bytes=-100", 1000), Ok(range(900, 999)));
+        // longer than the blob, serves all of it
+        assert_eq!(ByteRange::parse("bytes=-5000", 1000), Ok(range(0, 999)));
+    }
+
+    #[test]
+    fn clamps_the_end_to_the_blob() {
+        assert_eq!(ByteRange::parse("bytes=900-5000", 1000), Ok(range(900, 999)));
+        assert_eq!(ByteRange::parse("bytes=0-0", 1), Ok(range(0, 0)));
+    }
+
+    #[test]
+    fn ignores_headers_it_does_not_handle() {
+        for header in ["items=0-10", "bytes=0-10,20-30", "bytes=abc", "bytes=a-10", "bytes=0-b", "bytes=-x", "bytes=20-10"] {
+            assert_eq!(ByteRange::parse(header, 1000), Ok(None), "{}", header);
+        }
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_ranges() {
+        assert!(ByteRange::parse("bytes=1000-", 1000).is_err());
+        assert!(ByteRange::parse("bytes=1000-1005", 1000).is_err());
+        assert!(ByteRange::parse("bytes=-0", 1000).is_err());
+        assert!(ByteRange::parse("bytes=-10", 0).is_err());
+        assert!(ByteRange::parse("bytes=0-", 0).is_err());
+    }
+
+    #[test]
+    fn len_is_inclusive() {
+        assert_eq!(ByteRange { start: 0, end: 0 }.len(), 1);
+        assert_eq!(ByteRange { start: 10, end: 19 }.len(), 10);
+    }
+}
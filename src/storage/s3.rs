@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use s3::creds::Credentials;
+use s3::{Bucket, Region};
+use crate::storage::{BlobStore, ByteRange};
+
+// Stores blobs in an S3 compatible bucket (AWS, MinIO, R2, ...)
+pub struct S3BlobStore {
+    bucket: Box<Bucket>,
+    prefix: String,
+}
+
+impl S3BlobStore {
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        endpoint: Option<&str>,
+        access_key: Option<&str>,
+        secret_key: Option<&str>,
+    ) -> Result<Self> {
+        let region = match endpoint {
+            Some(endpoint) => Region::Custom {
+                region: region.to_string(),
+                endpoint: endpoint.to_string(),
+            },
+            None => region.parse()?,
+        };
+
+        let credentials = Credentials::new(access_key, secret_key, None, None, None)
+            .map_err(|e| anyhow!("Invalid S3 credentials: {}", e))?;
+
+        let mut bucket = Bucket::new(bucket, region.clone(), credentials)?;
+        // custom endpoints (MinIO and friends) usually only support path style addressing
+        if matches!(region, Region::Custom { .. }) {
+            bucket = bucket.with_path_style();
+        }
+
+        Ok(Self {
+            bucket,
+            prefix: "attachments".to_string(),
+        })
+    }
+
+    fn path_for(&self, key: &str) -> String {
+        format!("{}/{}", self.prefix, key)
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, data: Bytes) -> Result<()> {
+        self.bucket.put_object(self.path_for(key), &data).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str, range: Option<ByteRange>) -> Result<Bytes> {
+        let response = match range {
+            Some(range) => {
+                self.bucket
+                    .get_object_range(self.path_for(key), range.start, Some(range.end))
+                    .await?
+            }
+            None => self.bucket.get_object(self.path_for(key)).await?,
+        };
+        Ok(response.bytes().clone())
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.bucket.object_exists(self.path_for(key)).await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.bucket.delete_object(self.path_for(key)).await?;
+        Ok(())
+    }
+}
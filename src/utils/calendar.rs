@@ -0,0 +1,150 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use crate::models::Note;
+
+// Descriptions are cut here, calendar apps only show the first few lines anyway
+const MAX_DESCRIPTION_CHARS: usize = 2000;
+// RFC 5545 lines are at most 75 octets, longer ones are folded
+const MAX_LINE_OCTETS: usize = 75;
+
+pub fn generate_calendar_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Only the hash is stored, a leaked database doesn't expose anyone's feed
+pub fn hash_calendar_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// One VEVENT per note at its due date, or at the reminder when there's no due date,
+// with an alarm at the reminder time
+pub fn render_calendar(notes: &[Note]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//rust_notes_api//Notes//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Notes".to_string(),
+    ];
+
+    for note in notes {
+        let Some(start) = note.due_at.or(note.remind_at) else {
+            continue;
+        };
+        let description: String = note.content.chars().take(MAX_DESCRIPTION_CHARS).collect();
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@rust-notes-api", note.id));
+        lines.push(format!("DTSTAMP:{}", format_timestamp(note.updated_at)));
+        lines.push(format!("CREATED:{}", format_timestamp(note.created_at)));
+        lines.push(format!("LAST-MODIFIED:{}", format_timestamp(note.updated_at)));
+        lines.push(format!("SEQUENCE:{}", note.version));
+        lines.push(format!("DTSTART:{}", format_timestamp(start)));
+        lines.push(format!("SUMMARY:{}", escape_text(&note.title)));
+        if !description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+        }
+        if let Some(remind_at) = note.remind_at {
+            lines.push("BEGIN:VALARM".to_string());
+            lines.push("ACTION:DISPLAY".to_string());
+            lines.push(format!("DESCRIPTION:{}", escape_text(&note.title)));
+            lines.push(format!("TRIGGER;VALUE=DATE-TIME:{}", format_timestamp(remind_at)));
+            lines.push("END:VALARM".to_string());
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect::<Vec<_>>().join("")
+}
+
+fn format_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// CRLF terminated, continuation lines start with a space that counts towards their length
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn physical_lines(folded: &str) -> Vec<&str> {
+        assert!(folded.ends_with("\r\n"));
+        folded.trim_end_matches("\r\n").split("\r\n").collect()
+    }
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape_text(r"a\b;c,d"), r"a\\b\;c\,d");
+        assert_eq!(escape_text("one\ntwo\r\nthree"), r"one\ntwo\nthree");
+        assert_eq!(escape_text("plain: text"), "plain: text");
+    }
+
+    #[test]
+    fn leaves_short_lines_unfolded() {
+        let line = "X".repeat(MAX_LINE_OCTETS);
+        assert_eq!(fold_line(&line), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn folds_long_ascii_lines_at_75_octets() {
+        let line = format!("SUMMARY:{}", "x".repeat(200));
+        let folded = fold_line(&line);
+        let lines = physical_lines(&folded);
+
+        assert_eq!(lines[0].len(), MAX_LINE_OCTETS);
+        for continuation in &lines[1..] {
+            assert!(continuation.starts_with(' '));
+            assert!(continuation.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(folded.replace("\r\n ", "").trim_end_matches("\r\n"), line);
+    }
+
+    #[test]
+    fn folds_multibyte_lines_without_splitting_characters() {
+        // 2, 3 and 4 byte characters, none of which line up with the fold boundary
+        let line = format!("DESCRIPTION:{}", "äé€😀".repeat(30));
+        let folded = fold_line(&line);
+        let lines = physical_lines(&folded);
+
+        assert!(lines.len() > 1);
+        for physical in &lines {
+            assert!(physical.len() <= MAX_LINE_OCTETS, "{} octets", physical.len());
+        }
+        assert_eq!(folded.replace("\r\n ", "").trim_end_matches("\r\n"), line);
+    }
+}
\ No newline at end of file
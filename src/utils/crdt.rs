@@ -0,0 +1,149 @@
+use yrs::updates::decoder::Decode;
+use yrs::{Doc, GetString, OffsetKind, Options, ReadTxn, StateVector, Text, Transact, Update};
+
+// Name of the shared text holding the note content, clients bind their editor to it
+pub const CONTENT_FIELD: &str = "content";
+
+// Yjs in the browser counts text positions in UTF-16 code units
+fn document_options() -> Options {
+    Options {
+        offset_kind: OffsetKind::Utf16,
+        ..Options::default()
+    }
+}
+
+pub fn new_document(content: &str) -> Doc {
+    let doc = Doc::with_options(document_options());
+    let text = doc.get_or_insert_text(CONTENT_FIELD);
+    text.insert(&mut doc.transact_mut(), 0, content);
+    doc
+}
+
+// Rebuild a document from the state saved by encode_document
+pub fn load_document(state: &[u8]) -> anyhow::Result<Doc> {
+    let doc = Doc::with_options(document_options());
+    let update = Update::decode_v1(state)?;
+    doc.transact_mut().apply_update(update)?;
+    Ok(doc)
+}
+
+pub fn encode_document(doc: &Doc) -> Vec<u8> {
+    doc.transact().encode_state_as_update_v1(&StateVector::default())
+}
+
+pub fn document_text(doc: &Doc) -> String {
+    let text = doc.get_or_insert_text(CONTENT_FIELD);
+    text.get_string(&doc.transact())
+}
+
+// Turn the document text into `content` with a single splice around the changed middle,
+// returning the update to send to connected clients
+pub fn replace_document_text(doc: &Doc, content: &str) -> Vec<u8> {
+    let text = doc.get_or_insert_text(CONTENT_FIELD);
+    let mut txn = doc.transact_mut();
+    let current = text.get_string(&txn);
+
+    let prefix: usize = current
+        .chars()
+        .zip(content.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = current[prefix..]
+        .chars()
+        .rev()
+        .zip(content[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+
+    let start = utf16_len(&current[..prefix]);
+    let removed = utf16_len(&current[prefix..current.len() - suffix]);
+    let inserted = &content[prefix..content.len() - suffix];
+
+    if removed > 0 {
+        text.remove_range(&mut txn, start, removed);
+    }
+    if !inserted.is_empty() {
+        text.insert(&mut txn, start, inserted);
+    }
+
+    txn.encode_update_v1()
+}
+
+// Applies the edit turning `base_state`'s text into `content` to the live document, as if
+// another client had made it on top of that state, so edits made in the document since
+// then are kept. Returns the update for connected clients and the edited base state
+pub fn merge_document_text(doc: &Doc, base_state: &[u8], content: &str) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let base = load_document(base_state)?;
+    let update = replace_document_text(&base, content);
+    doc.transact_mut().apply_update(Update::decode_v1(&update)?)?;
+    Ok((update, encode_document(&base)))
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Another replica of the document, like a connected editor
+    fn replica(doc: &Doc) -> Doc {
+        load_document(&encode_document(doc)).unwrap()
+    }
+
+    fn apply(doc: &Doc, update: &[u8]) {
+        doc.transact_mut().apply_update(Update::decode_v1(update).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn replaces_only_the_changed_middle() {
+        let doc = new_document("The quick fox");
+        let client = replica(&doc);
+
+        let update = replace_document_text(&doc, "The quick brown fox");
+        apply(&client, &update);
+
+        assert_eq!(document_text(&doc), "The quick brown fox");
+        assert_eq!(document_text(&client), "The quick brown fox");
+    }
+
+    #[test]
+    fn counts_positions_in_utf16() {
+        let doc = new_document("🎉 party 🎉 time");
+        replace_document_text(&doc, "🎉 party 🎈 time");
+        assert_eq!(document_text(&doc), "🎉 party 🎈 time");
+
+        replace_document_text(&doc, "");
+        assert_eq!(document_text(&doc), "");
+    }
+
+    #[test]
+    fn merges_a_rest_edit_with_edits_made_since() {
+        let doc = new_document("hello world");
+        let base = encode_document(&doc);
+        let client = replica(&doc);
+
+        // typed in the room after the last snapshot
+        let typed = replace_document_text(&client, "hello world!");
+        apply(&doc, &typed);
+
+        let (update, base) = merge_document_text(&doc, &base, "Hello world").unwrap();
+        apply(&client, &update);
+
+        assert_eq!(document_text(&doc), "Hello world!");
+        assert_eq!(document_text(&client), "Hello world!");
+        assert_eq!(document_text(&load_document(&base).unwrap()), "Hello world");
+    }
+
+    #[test]
+    fn merges_successive_rest_edits_from_the_returned_base() {
+        let doc = new_document("a");
+        let (_, base) = merge_document_text(&doc, &encode_document(&doc), "ab").unwrap();
+        merge_document_text(&doc, &base, "abc").unwrap();
+
+        assert_eq!(document_text(&doc), "abc");
+    }
+}
\ No newline at end of file
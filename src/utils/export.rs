@@ -0,0 +1,252 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::Serialize;
+use zip::write::{SimpleFileOptions, StreamWriter};
+use uuid::Uuid;
+use zip::{CompressionMethod, ZipWriter};
+use crate::models::{notebook_paths, ExportFormat, Note, Notebook};
+use crate::utils::{highlight_stylesheet, html_escape, render_enml, render_markdown};
+
+const ENEX_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// Front matter written on top of every exported markdown file, read back by imports
+#[derive(Serialize)]
+struct FrontMatter<'a> {
+    title: &'a str,
+    created: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+}
+
+// Write target the zip writer appends to, drained after every note so the
+// archive goes out in chunks instead of being built in memory
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct ZipExport {
+    writer: ZipWriter<StreamWriter<SharedBuffer>>,
+    buffer: SharedBuffer,
+    names: HashSet<String>,
+}
+
+enum Encoding {
+    Json { first: bool, notebooks: Vec<Notebook> },
+    Html,
+    Enex,
+    MarkdownZip(Box<ZipExport>),
+}
+
+// Turns notes into chunks of an export file one at a time, markdown archives get
+// a folder per notebook
+pub struct ExportEncoder {
+    encoding: Encoding,
+    paths: HashMap<Uuid, Vec<String>>,
+}
+
+impl ExportEncoder {
+    pub fn new(format: ExportFormat, notebooks: Vec<Notebook>) -> Self {
+        let paths = notebook_paths(&notebooks);
+        let encoding = match format {
+            ExportFormat::Json => Encoding::Json { first: true, notebooks },
+            ExportFormat::Html => Encoding::Html,
+            ExportFormat::Enex => Encoding::Enex,
+            ExportFormat::MarkdownZip => {
+                let buffer = SharedBuffer::default();
+                Encoding::MarkdownZip(Box::new(ZipExport {
+                    writer: ZipWriter::new_stream(buffer.clone()),
+                    buffer,
+                    names: HashSet::new(),
+                }))
+            }
+        };
+
+        Self { encoding, paths }
+    }
+
+    // The notebook a note is in as a list of names, empty outside any notebook
+    fn notebook_path(&self, note: &Note) -> &[String] {
+        note.notebook_id
+            .and_then(|notebook_id| self.paths.get(&notebook_id))
+            .map_or(&[], |path| path.as_slice())
+    }
+
+    pub fn header(&self) -> anyhow::Result<Vec<u8>> {
+        let header = match &self.encoding {
+            Encoding::Json { notebooks, .. } => {
+                let mut header = b"{\"notebooks\":".to_vec();
+                serde_json::to_writer(&mut header, notebooks)?;
+                header.extend_from_slice(b",\"notes\":[");
+                header
+            }
+            Encoding::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Notes export</title>\n<style>\n{}</style>\n</head>\n<body>\n",
+                highlight_stylesheet()
+            ).into_bytes(),
+            Encoding::Enex => format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE en-export SYSTEM \"http://xml.evernote.com/pub/evernote-export4.dtd\">\n<en-export export-date=\"{}\" application=\"rust_notes_api\">\n",
+                Utc::now().format(ENEX_DATE_FORMAT)
+            ).into_bytes(),
+            Encoding::MarkdownZip(_) => Vec::new(),
+        };
+
+        Ok(header)
+    }
+
+    pub fn encode(&mut self, note: &Note) -> anyhow::Result<Vec<u8>> {
+        let notebook = self.notebook_path(note).to_vec();
+
+        match &mut self.encoding {
+            Encoding::Json { first, .. } => {
+                let mut chunk = if *first { Vec::new() } else { b",".to_vec() };
+                *first = false;
+                serde_json::to_writer(&mut chunk, note)?;
+                Ok(chunk)
+            }
+            Encoding::Html => Ok(html_note(note, &notebook).into_bytes()),
+            Encoding::Enex => Ok(enex_note(note).into_bytes()),
+            Encoding::MarkdownZip(zip) => {
+                let name = unique_file_name(&mut zip.names, &notebook, &note.title);
+                let front_matter = serde_yaml::to_string(&FrontMatter {
+                    title: &note.title,
+                    created: note.created_at,
+                    updated: note.updated_at,
+                    tags: &note.tags,
+                })?;
+
+                let mut options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+                if let Some(modified) = zip_date_time(note.updated_at) {
+                    options = options.last_modified_time(modified);
+                }
+
+                zip.writer.start_file(name, options)?;
+                write!(zip.writer, "---\n{}---\n\n{}", front_matter, note.content)?;
+                Ok(zip.buffer.take())
+            }
+        }
+    }
+
+    pub fn finish(self) -> anyhow::Result<Vec<u8>> {
+        match self.encoding {
+            Encoding::Json { .. } => Ok(b"]}".to_vec()),
+            Encoding::Html => Ok(b"</body>\n</html>\n".to_vec()),
+            Encoding::Enex => Ok(b"</en-export>\n".to_vec()),
+            Encoding::MarkdownZip(zip) => {
+                zip.writer.finish()?;
+                Ok(zip.buffer.take())
+            }
+        }
+    }
+}
+
+fn html_note(note: &Note, notebook: &[String]) -> String {
+    let mut details = String::new();
+    if !notebook.is_empty() {
+        details.push_str(&format!(" in {}", html_escape(&notebook.join(" / "))));
+    }
+    if !note.tags.is_empty() {
+        let tags: Vec<String> = note.tags.iter().map(|tag| format!("#{}", html_escape(tag))).collect();
+        details.push_str(&format!(", tagged {}", tags.join(" ")));
+    }
+
+    format!(
+        "<article id=\"note-{}\">\n<h1>{}</h1>\n<p><small>Created <time datetime=\"{created}\">{created}</time>, updated <time datetime=\"{updated}\">{updated}</time>{}</small></p>\n{}</article>\n",
+        note.id,
+        html_escape(&note.title),
+        details,
+        render_markdown(&note.content),
+        created = note.created_at.to_rfc3339(),
+        updated = note.updated_at.to_rfc3339()
+    )
+}
+
+fn enex_note(note: &Note) -> String {
+    let enml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><!DOCTYPE en-note SYSTEM \"http://xml.evernote.com/pub/enml2.dtd\"><en-note>{}</en-note>",
+        render_enml(&note.content)
+    );
+
+    let tags: String = note.tags
+        .iter()
+        .map(|tag| format!("<tag>{}</tag>", html_escape(&xml_chars(tag))))
+        .collect();
+
+    format!(
+        "<note><title>{}</title><content><![CDATA[{}]]></content><created>{}</created><updated>{}</updated>{}</note>\n",
+        html_escape(&xml_chars(&note.title)),
+        // a literal ]]> would end the CDATA section early, split it across two sections
+        xml_chars(&enml).replace("]]>", "]]]]><![CDATA[>"),
+        note.created_at.format(ENEX_DATE_FORMAT),
+        note.updated_at.format(ENEX_DATE_FORMAT),
+        tags
+    )
+}
+
+// Drop characters XML 1.0 can't represent, even escaped
+fn xml_chars(text: &str) -> String {
+    text.chars()
+        .filter(|c| matches!(c, '\t' | '\n' | '\r') || *c >= ' ')
+        .filter(|c| !matches!(c, '\u{FFFE}' | '\u{FFFF}'))
+        .collect()
+}
+
+// A title or notebook name that is safe to use as a file or folder name
+fn safe_file_name(name: &str, fallback: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+        .take(200)
+        .collect();
+    let stem = stem.trim().trim_start_matches('.');
+    if stem.is_empty() { fallback.to_string() } else { stem.to_string() }
+}
+
+// "<notebook>/<child notebook>/<title>.md", numbered when several notes in a
+// folder share a title
+fn unique_file_name(names: &mut HashSet<String>, notebook: &[String], title: &str) -> String {
+    let folder: String = notebook
+        .iter()
+        .map(|name| format!("{}/", safe_file_name(name, "Notebook")))
+        .collect();
+    let stem = safe_file_name(title, "Untitled");
+
+    let mut name = format!("{}{}.md", folder, stem);
+    let mut counter = 2;
+    while !names.insert(name.to_lowercase()) {
+        name = format!("{}{} ({}).md", folder, stem, counter);
+        counter += 1;
+    }
+    name
+}
+
+// Zip timestamps only cover 1980 to 2107
+fn zip_date_time(time: DateTime<Utc>) -> Option<zip::DateTime> {
+    let year = u16::try_from(time.year()).ok()?;
+    zip::DateTime::from_date_and_time(
+        year,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    ).ok()
+}
\ No newline at end of file
@@ -0,0 +1,537 @@
+use std::io::{Cursor, Read};
+use std::path::Path;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use htmd::{Element, HtmlToMarkdown};
+use htmd::element_handler::Handlers;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_yaml::Value;
+use zip::ZipArchive;
+use crate::models::{ImportFileError, ImportFormat};
+use crate::utils::{normalize_notebook_name, normalize_tag};
+
+// Guards against zip bombs and runaway exports
+const MAX_IMPORT_FILES: usize = 10_000;
+const MAX_IMPORTED_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+// A note read from an import, ready to be inserted
+#[derive(Debug, Clone)]
+pub struct ImportedNote {
+    // path inside the archive or position in the export, for the error report
+    pub file: String,
+    pub title: String,
+    pub content: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    // already normalized
+    pub tags: Vec<String>,
+    // notebook names from the top level down, empty for notes outside any folder
+    pub notebook: Vec<String>,
+    // tags and folders that couldn't be carried over, the note is imported without them
+    pub skipped: Vec<String>,
+}
+
+pub type ImportedFile = Result<ImportedNote, ImportFileError>;
+
+fn file_error(file: &str, message: impl Into<String>) -> ImportFileError {
+    ImportFileError {
+        file: file.to_string(),
+        message: message.into(),
+    }
+}
+
+// Pick a format from the upload when the client didn't say
+pub fn detect_import_format(filename: &str, data: &[u8]) -> ImportFormat {
+    if filename.to_lowercase().ends_with(".enex") || data.starts_with(b"<?xml") {
+        return ImportFormat::Enex;
+    }
+
+    // a vault always carries its .obsidian settings folder
+    let is_vault = ZipArchive::new(Cursor::new(data))
+        .map(|archive| {
+            archive
+                .file_names()
+                .flatten()
+                .any(|name| name.starts_with(".obsidian/") || name.contains("/.obsidian/"))
+        })
+        .unwrap_or(false);
+
+    if is_vault {
+        ImportFormat::Obsidian
+    } else {
+        ImportFormat::Markdown
+    }
+}
+
+// Split an upload into notes, Err when the upload as a whole can't be read
+pub fn parse_import(format: ImportFormat, data: &[u8]) -> Result<Vec<ImportedFile>, String> {
+    match format {
+        ImportFormat::Markdown | ImportFormat::Obsidian => parse_markdown_zip(format, data),
+        ImportFormat::Enex => parse_enex(data),
+    }
+}
+
+// ===== MARKDOWN / OBSIDIAN =====
+
+fn is_hidden(path: &str) -> bool {
+    path.split('/').any(|segment| segment.starts_with('.') || segment == "__MACOSX")
+}
+
+// Folder of the vault inside the archive, the one holding .obsidian, so a zipped
+// vault folder doesn't turn into a notebook of its own
+fn vault_root(archive: &ZipArchive<Cursor<&[u8]>>) -> String {
+    archive
+        .file_names()
+        .flatten()
+        .filter_map(|name| name.find(".obsidian/").map(|index| name[..index].to_string()))
+        .filter(|root| root.is_empty() || root.ends_with('/'))
+        .min_by_key(|root| root.len())
+        .unwrap_or_default()
+}
+
+fn parse_markdown_zip(format: ImportFormat, data: &[u8]) -> Result<Vec<ImportedFile>, String> {
+    let mut archive = ZipArchive::new(Cursor::new(data)).map_err(|e| format!("Not a valid ZIP archive: {}", e))?;
+
+    if archive.len() > MAX_IMPORT_FILES {
+        return Err(format!("Archives may contain at most {} files", MAX_IMPORT_FILES));
+    }
+
+    let root = match format {
+        ImportFormat::Obsidian => vault_root(&archive),
+        _ => String::new(),
+    };
+    let mut files = Vec::new();
+
+    for index in 0..archive.len() {
+        let mut entry = match archive.by_index(index) {
+            Ok(entry) => entry,
+            Err(e) => {
+                files.push(Err(file_error(&format!("#{}", index), e.to_string())));
+                continue;
+            }
+        };
+
+        let path = entry.name().map(|name| name.to_string()).unwrap_or_default();
+        let extension = Path::new(&path).extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+
+        // attachments, settings and folders aren't notes
+        if entry.is_dir() || is_hidden(&path) || !matches!(extension.to_lowercase().as_str(), "md" | "markdown") {
+            continue;
+        }
+
+        let relative = path.strip_prefix(root.as_str()).unwrap_or(&path);
+        let folders: Vec<&str> = relative.split('/').collect();
+        let folders = &folders[..folders.len() - 1];
+
+        if entry.size() > MAX_IMPORTED_FILE_SIZE {
+            files.push(Err(file_error(&path, format!("File is larger than {} bytes", MAX_IMPORTED_FILE_SIZE))));
+            continue;
+        }
+
+        let mut raw = Vec::new();
+        if let Err(e) = entry.by_ref().take(MAX_IMPORTED_FILE_SIZE + 1).read_to_end(&mut raw) {
+            files.push(Err(file_error(&path, e.to_string())));
+            continue;
+        }
+
+        let Ok(text) = String::from_utf8(raw) else {
+            files.push(Err(file_error(&path, "File is not valid UTF-8")));
+            continue;
+        };
+
+        files.push(parse_markdown_file(format, &path, folders, &text));
+    }
+
+    Ok(files)
+}
+
+fn split_front_matter(text: &str) -> (Option<&str>, &str) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let Some(rest) = text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) else {
+        return (None, text);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" || line.trim_end() == "..." {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    // an opening --- without a closing one is just a horizontal rule
+    (None, text)
+}
+
+fn parse_date(value: &Value) -> Option<DateTime<Utc>> {
+    let value = value.as_str()?.trim();
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+                .iter()
+                .find_map(|pattern| NaiveDateTime::parse_from_str(value, pattern).ok())
+                .map(|date| date.and_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
+
+fn front_matter_date(front_matter: &Value, keys: &[&str]) -> Option<DateTime<Utc>> {
+    keys.iter().find_map(|key| front_matter.get(key).and_then(parse_date))
+}
+
+fn first_heading(body: &str) -> Option<String> {
+    body.lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|heading| heading.trim().to_string())
+        .filter(|heading| !heading.is_empty())
+}
+
+// Front matter tags come as a YAML list or as one comma or space separated string
+fn front_matter_tags(front_matter: &Value) -> Vec<String> {
+    let tags = front_matter.get("tags").or_else(|| front_matter.get("tag"));
+
+    match tags {
+        Some(Value::Sequence(tags)) => tags
+            .iter()
+            .filter_map(|tag| match tag {
+                Value::String(tag) => Some(tag.clone()),
+                Value::Number(tag) => Some(tag.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Some(Value::String(tags)) => tags
+            .split([',', ' '])
+            .filter(|tag| !tag.trim().is_empty())
+            .map(|tag| tag.to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Normalizes what can be kept and describes what can't for the error report
+fn collect_tags(raw: Vec<String>, skipped: &mut Vec<String>) -> Vec<String> {
+    let mut tags: Vec<String> = raw
+        .iter()
+        .filter_map(|tag| match normalize_tag(tag) {
+            Ok(tag) => Some(tag),
+            Err(message) => {
+                skipped.push(format!("Skipped tag '{}': {}", tag.trim(), message));
+                None
+            }
+        })
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+fn collect_notebook(folders: &[&str], skipped: &mut Vec<String>) -> Vec<String> {
+    let names: Result<Vec<String>, String> = folders.iter().map(|folder| normalize_notebook_name(folder)).collect();
+
+    match names {
+        Ok(names) => names,
+        Err(message) => {
+            skipped.push(format!("Imported outside the folder '{}': {}", folders.join("/"), message));
+            Vec::new()
+        }
+    }
+}
+
+fn parse_markdown_file(format: ImportFormat, path: &str, folders: &[&str], text: &str) -> ImportedFile {
+    let (front_matter, body) = split_front_matter(text);
+
+    let front_matter = match front_matter {
+        Some(yaml) => serde_yaml::from_str::<Value>(yaml)
+            .map_err(|e| file_error(path, format!("Invalid front matter: {}", e)))?,
+        None => Value::Null,
+    };
+
+    let file_stem = Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    // Obsidian links notes by file name, so the file name has to stay the title
+    let title = match format {
+        ImportFormat::Obsidian => file_stem,
+        _ => front_matter
+            .get("title")
+            .and_then(|title| title.as_str())
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty())
+            .or_else(|| first_heading(body))
+            .unwrap_or(file_stem),
+    };
+
+    if title.is_empty() {
+        return Err(file_error(path, "Note has no title"));
+    }
+
+    let mut skipped = Vec::new();
+    let tags = collect_tags(front_matter_tags(&front_matter), &mut skipped);
+    let notebook = collect_notebook(folders, &mut skipped);
+
+    Ok(ImportedNote {
+        file: path.to_string(),
+        title,
+        content: body.trim_start_matches(['\r', '\n']).to_string(),
+        created_at: front_matter_date(&front_matter, &["created", "created_at", "date"]),
+        updated_at: front_matter_date(&front_matter, &["updated", "updated_at", "modified"]),
+        tags,
+        notebook,
+        skipped,
+    })
+}
+
+// ===== EVERNOTE =====
+
+#[derive(Default)]
+struct EnexNote {
+    title: String,
+    content: String,
+    created: String,
+    updated: String,
+    tags: Vec<String>,
+}
+
+fn resolve_entity(name: &str) -> Option<&'static str> {
+    match name {
+        "lt" => Some("<"),
+        "gt" => Some(">"),
+        "amp" => Some("&"),
+        "quot" => Some("\""),
+        "apos" => Some("'"),
+        _ => None,
+    }
+}
+
+fn parse_enex(data: &[u8]) -> Result<Vec<ImportedFile>, String> {
+    let text = std::str::from_utf8(data).map_err(|_| "ENEX export is not valid UTF-8".to_string())?;
+    let mut reader = Reader::from_str(text);
+
+    let mut files = Vec::new();
+    let mut note: Option<EnexNote> = None;
+    let mut field: Option<String> = None;
+    let mut resource_depth = 0;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Invalid ENEX at byte {}: {}", reader.error_position(), e))?;
+
+        match event {
+            Event::Start(start) => {
+                let name = start.name().as_ref().to_string();
+                match name.as_str() {
+                    "note" => note = Some(EnexNote::default()),
+                    // embedded files are base64 blobs we don't import
+                    "resource" => resource_depth += 1,
+                    "title" | "content" | "created" | "updated" | "tag" if resource_depth == 0 => {
+                        if let Some(note) = note.as_mut() {
+                            if name == "tag" {
+                                note.tags.push(String::new());
+                            }
+                            field = Some(name);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(end) => match end.name().as_ref() {
+                "note" => {
+                    if let Some(finished) = note.take() {
+                        if files.len() >= MAX_IMPORT_FILES {
+                            return Err(format!("Exports may contain at most {} notes", MAX_IMPORT_FILES));
+                        }
+                        let position = files.len() + 1;
+                        files.push(convert_enex_note(position, finished));
+                    }
+                }
+                "resource" => resource_depth -= 1,
+                _ => field = None,
+            },
+            Event::Text(text) => append_field(&mut note, &field, &text),
+            Event::CData(data) => append_field(&mut note, &field, &data),
+            Event::GeneralRef(reference) => {
+                let resolved = match reference.resolve_char_ref() {
+                    Ok(Some(c)) => c.to_string(),
+                    _ => resolve_entity(&reference).unwrap_or_default().to_string(),
+                };
+                append_field(&mut note, &field, &resolved);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
+
+fn append_field(note: &mut Option<EnexNote>, field: &Option<String>, text: &str) {
+    let (Some(note), Some(field)) = (note.as_mut(), field.as_deref()) else {
+        return;
+    };
+
+    match field {
+        "title" => note.title.push_str(text),
+        "content" => note.content.push_str(text),
+        "created" => note.created.push_str(text),
+        "updated" => note.updated.push_str(text),
+        "tag" => note.tags.last_mut().into_iter().for_each(|tag| tag.push_str(text)),
+        _ => {}
+    }
+}
+
+// Evernote timestamps look like 20240131T154500Z
+fn parse_enex_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value.trim(), "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+fn convert_enex_note(position: usize, note: EnexNote) -> ImportedFile {
+    let title = note.title.trim().to_string();
+    let file = if title.is_empty() {
+        format!("note #{}", position)
+    } else {
+        format!("note #{} ({})", position, title)
+    };
+
+    if title.is_empty() {
+        return Err(file_error(&file, "Note has no title"));
+    }
+
+    let content = enml_to_markdown(&note.content).map_err(|message| file_error(&file, message))?;
+
+    // Evernote exports one notebook per file without naming it, so only tags carry over
+    let mut skipped = Vec::new();
+    let tags = collect_tags(note.tags, &mut skipped);
+
+    Ok(ImportedNote {
+        file,
+        title,
+        content,
+        created_at: parse_enex_date(&note.created),
+        updated_at: parse_enex_date(&note.updated),
+        tags,
+        notebook: Vec::new(),
+        skipped,
+    })
+}
+
+// ENML is XML, but html5ever parses <en-todo/> as an open tag that swallows the
+// rest of the note, so spell out the closing tags of the empty en-* elements
+fn close_empty_elements(enml: &str) -> String {
+    let mut closed = String::with_capacity(enml.len());
+    let mut rest = enml;
+
+    while let Some(start) = rest.find("<en-") {
+        closed.push_str(&rest[..start]);
+        let tag = &rest[start..];
+        let Some(end) = tag.find('>') else {
+            break;
+        };
+
+        let element = &tag[..=end];
+        let name: String = element[1..].chars().take_while(|c| c.is_alphanumeric() || *c == '-').collect();
+        match element.strip_suffix("/>") {
+            Some(open) => closed.push_str(&format!("{}></{}>", open, name)),
+            None => closed.push_str(element),
+        }
+        rest = &tag[end + 1..];
+    }
+
+    closed.push_str(rest);
+    closed
+}
+
+fn enml_to_markdown(enml: &str) -> Result<String, String> {
+    let converter = HtmlToMarkdown::builder()
+        .skip_tags(vec!["en-media", "en-crypt", "script", "style"])
+        .add_handler(vec!["en-todo"], |_: &dyn Handlers, element: Element| {
+            let checked = element
+                .attrs
+                .iter()
+                .any(|attr| &*attr.name.local == "checked" && &*attr.value == "true");
+            Some(if checked { "- [x] " } else { "- [ ] " }.into())
+        })
+        .build();
+
+    converter
+        .convert(&close_empty_elements(enml))
+        .map(|markdown| markdown.trim().to_string())
+        .map_err(|e| format!("Could not convert note content: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn reads_front_matter_tags_as_list_or_string() {
+        let list = parse_markdown_file(ImportFormat::Markdown, "a.md", &[], "---\ntags: [Work, \"#Ideas\"]\n---\nBody").unwrap();
+        assert_eq!(list.tags, vec!["ideas", "work"]);
+
+        let string = parse_markdown_file(ImportFormat::Markdown, "b.md", &[], "---\ntags: work, home\n---\nBody").unwrap();
+        assert_eq!(string.tags, vec!["home", "work"]);
+        assert!(string.skipped.is_empty());
+    }
+
+    #[test]
+    fn reports_tags_it_cant_keep() {
+        let note = parse_markdown_file(ImportFormat::Markdown, "a.md", &[], "---\ntags: [ok, \"not,ok\"]\n---\nBody").unwrap();
+        assert_eq!(note.tags, vec!["ok"]);
+        assert_eq!(note.skipped.len(), 1);
+        assert!(note.skipped[0].contains("not,ok"));
+    }
+
+    #[test]
+    fn maps_vault_folders_to_notebooks() {
+        let data = zip(&[
+            ("Vault/.obsidian/app.json", "{}"),
+            ("Vault/Work/Projects/Plan.md", "Plan"),
+            ("Vault/Inbox.md", "Inbox"),
+        ]);
+        let notes: Vec<ImportedNote> = parse_import(ImportFormat::Obsidian, &data)
+            .unwrap()
+            .into_iter()
+            .map(|file| file.unwrap())
+            .collect();
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].title, "Plan");
+        assert_eq!(notes[0].notebook, vec!["Work", "Projects"]);
+        assert!(notes[1].notebook.is_empty());
+    }
+
+    #[test]
+    fn reads_enex_tags() {
+        let enex = "<en-export><note><title>Trip</title><content><![CDATA[<en-note>Pack</en-note>]]></content>\
+            <tag>Travel</tag><tag>summer plans</tag></note></en-export>";
+        let note = parse_enex(enex.as_bytes()).unwrap().remove(0).unwrap();
+
+        assert_eq!(note.tags, vec!["travel"]);
+        assert_eq!(note.skipped.len(), 1);
+    }
+}
\ No newline at end of file
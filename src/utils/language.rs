@@ -0,0 +1,77 @@
+use crate::models::NoteLanguage;
+
+// Very common function words, enough to tell the supported languages apart
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "the", "and", "is", "are", "of", "to", "in", "that", "it", "with", "for", "this", "was",
+    "on", "be", "you", "not", "have", "but", "from", "they", "we", "will", "what", "which",
+];
+const GERMAN_STOPWORDS: &[&str] = &[
+    "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "ich", "mit", "zu", "den",
+    "von", "sie", "es", "auf", "für", "auch", "dem", "im", "sich", "wir", "wird", "oder", "aber",
+];
+const SPANISH_STOPWORDS: &[&str] = &[
+    "el", "la", "los", "las", "y", "de", "que", "en", "es", "un", "una", "por", "con", "para",
+    "no", "se", "del", "lo", "como", "pero", "más", "su", "al", "este", "está",
+];
+
+// Guess the language of a note from its stopwords, falling back to English
+pub fn detect_language(text: &str) -> NoteLanguage {
+    let mut english = 0;
+    let mut german = 0;
+    let mut spanish = 0;
+
+    for word in text.split(|c: char| !c.is_alphabetic()).filter(|word| !word.is_empty()) {
+        let word = word.to_lowercase();
+        if ENGLISH_STOPWORDS.contains(&word.as_str()) {
+            english += 1;
+        }
+        if GERMAN_STOPWORDS.contains(&word.as_str()) {
+            german += 1;
+        }
+        if SPANISH_STOPWORDS.contains(&word.as_str()) {
+            spanish += 1;
+        }
+    }
+
+    // letters that only show up in one of the languages are strong hints
+    german += text.chars().filter(|c| "äöüßÄÖÜ".contains(*c)).count();
+    spanish += text.chars().filter(|c| "ñ¿¡Ñ".contains(*c)).count();
+
+    if german > english && german >= spanish {
+        NoteLanguage::German
+    } else if spanish > english && spanish > german {
+        NoteLanguage::Spanish
+    } else {
+        NoteLanguage::English
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_languages_from_stopwords() {
+        assert_eq!(detect_language("This is the plan for the release and it will ship"), NoteLanguage::English);
+        assert_eq!(detect_language("Das ist nicht die Aufgabe, die wir mit dem Team besprochen haben"), NoteLanguage::German);
+        assert_eq!(detect_language("Esta es la lista de tareas para el equipo y los clientes"), NoteLanguage::Spanish);
+    }
+
+    #[test]
+    fn language_specific_letters_count_as_hints() {
+        assert_eq!(detect_language("Größe Übung"), NoteLanguage::German);
+        assert_eq!(detect_language("¿Año?"), NoteLanguage::Spanish);
+    }
+
+    #[test]
+    fn stopwords_match_regardless_of_case() {
+        assert_eq!(detect_language("DER DIE DAS UND"), NoteLanguage::German);
+    }
+
+    #[test]
+    fn falls_back_to_english() {
+        assert_eq!(detect_language(""), NoteLanguage::English);
+        assert_eq!(detect_language("kubernetes 42 rustc"), NoteLanguage::English);
+    }
+}
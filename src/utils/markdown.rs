@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use ammonia::Builder;
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use syntect::html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator};
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+use uuid::Uuid;
+
+// Highlighted code uses classes rather than inline styles so the sanitizer can stay strict
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+const HIGHLIGHT_THEME: &str = "InspiredGitHub";
+
+// Loading the syntax definitions takes a while, do it once
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::default();
+    builder
+        .add_tags(["input", "span"])
+        .add_tag_attributes("span", ["class"])
+        .add_tag_attributes("pre", ["class"])
+        .add_tag_attributes("code", ["class"])
+        .add_tag_attributes("sup", ["class"])
+        .add_tag_attributes("div", ["class", "id"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        // task list items are the only inputs we render, and they are read only
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("input", "type") if value != "checkbox" => None,
+            _ => Some(value.into()),
+        })
+        .set_tag_attribute_value("input", "disabled", "");
+    builder
+});
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+}
+
+fn highlight_code(code: &str, language: &str) -> String {
+    let syntax = SYNTAXES
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAXES, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+            return html_escape(code);
+        }
+    }
+    generator.finalize()
+}
+
+pub fn html_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    html::push_html(&mut escaped, std::iter::once(Event::Text(text.into())));
+    escaped
+}
+
+// Render CommonMark + GFM to sanitized HTML with highlighted fenced code blocks
+pub fn render_markdown(source: &str) -> String {
+    let mut events = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+
+    for event in Parser::new_ext(source, markdown_options()) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or_default().to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, code)) = code_block.take() {
+                    let class = if language.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" class=\"language-{}\"", html_escape(&language))
+                    };
+                    events.push(Event::Html(
+                        format!("<pre class=\"hl-code\"><code{}>{}</code></pre>\n", class, highlight_code(&code, &language)).into()
+                    ));
+                }
+            }
+            event => events.push(event),
+        }
+    }
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events.into_iter());
+
+    SANITIZER.clean(&unsafe_html).to_string()
+}
+
+// Render to ENML for Evernote exports, which is stricter XHTML: no raw html, no
+// class or id attributes, checkboxes as <en-todo/>
+pub fn render_enml(source: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS | Options::ENABLE_STRIKETHROUGH;
+
+    let events = Parser::new_ext(source, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::TaskListMarker(true) => Event::Html("<en-todo checked=\"true\"/>".into()),
+        Event::TaskListMarker(false) => Event::Html("<en-todo/>".into()),
+        Event::Start(Tag::CodeBlock(_)) => Event::Html("<pre>".into()),
+        Event::End(TagEnd::CodeBlock) => Event::Html("</pre>\n".into()),
+        event => event,
+    });
+
+    let mut enml = String::new();
+    html::push_html(&mut enml, events);
+    enml
+}
+
+// Stylesheet for the classes emitted around highlighted code
+pub fn highlight_stylesheet() -> String {
+    let themes = ThemeSet::load_defaults();
+    themes
+        .themes
+        .get(HIGHLIGHT_THEME)
+        .and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+        .unwrap_or_default()
+}
+
+// Rendered html along with the note's updated_at it was rendered from
+type CachedRender = (DateTime<Utc>, Arc<str>);
+
+// Rendered note contents, valid for as long as the note's updated_at doesn't change
+pub struct RenderCache {
+    entries: Mutex<HashMap<Uuid, CachedRender>>,
+    capacity: usize,
+}
+
+impl RenderCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    pub fn get_or_render(&self, note_id: Uuid, updated_at: DateTime<Utc>, source: &str) -> Arc<str> {
+        if let Ok(entries) = self.entries.lock()
+            && let Some((cached_at, html)) = entries.get(&note_id)
+            && *cached_at == updated_at
+        {
+            return html.clone();
+        }
+
+        let html: Arc<str> = render_markdown(source).into();
+
+        if let Ok(mut entries) = self.entries.lock() {
+            // dropping everything is crude, but keeps memory bounded without an LRU
+            if entries.len() >= self.capacity && !entries.contains_key(&note_id) {
+                entries.clear();
+            }
+            entries.insert(note_id, (updated_at, html.clone()));
+        }
+
+        html
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_commonmark_and_gfm() {
+        let html = render_markdown("# Title\n\n**bold** ~~gone~~\n\n| a | b |\n|---|---|\n| 1 | 2 |");
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("<del>gone</del>"));
+        assert!(html.contains("<td>1</td>"));
+    }
+
+    #[test]
+    fn strips_scripts_and_event_handlers() {
+        let html = render_markdown("hi <script>alert(1)</script>\n\n<img src=x onerror=alert(1)>\n\n<div onclick=\"steal()\">x</div>");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert(1)</script>"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("onclick"));
+    }
+
+    #[test]
+    fn strips_javascript_urls() {
+        let html = render_markdown("[click](javascript:alert(1)) <a href=\"JaVaScRiPt:alert(2)\">raw</a> [ok](https://example.com)");
+        assert!(!html.to_lowercase().contains("javascript:"));
+        assert!(html.contains("href=\"https://example.com\""));
+    }
+
+    #[test]
+    fn task_checkboxes_are_read_only() {
+        let html = render_markdown("- [x] done\n- [ ] todo\n\n<input type=\"text\" value=\"x\">");
+        assert!(html.contains("type=\"checkbox\""));
+        assert!(html.contains("checked"));
+        assert!(!html.contains("type=\"text\""));
+        assert_eq!(html.matches("<input").count(), html.matches("disabled").count());
+    }
+
+    #[test]
+    fn escapes_markup_inside_code_blocks() {
+        let html = render_markdown("```rust\nlet x = \"</code><script>alert(1)</script>\";\n```");
+        assert!(html.contains("class=\"language-rust\""));
+        assert!(!html.contains("<script"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn enml_keeps_raw_html_as_text() {
+        let enml = render_enml("- [x] done\n\n<script>alert(1)</script>");
+        assert!(enml.contains("<en-todo checked=\"true\"/>"));
+        assert!(!enml.contains("<script"));
+    }
+
+    #[test]
+    fn cache_rerenders_when_the_note_changes() {
+        let cache = RenderCache::new(2);
+        let id = Uuid::new_v4();
+        let first = Utc::now();
+        let second = first + chrono::Duration::seconds(1);
+
+        assert!(cache.get_or_render(id, first, "*old*").contains("<em>old</em>"));
+        assert!(cache.get_or_render(id, first, "*new*").contains("<em>old</em>"));
+        assert!(cache.get_or_render(id, second, "*new*").contains("<em>new</em>"));
+    }
+}
\ No newline at end of file
@@ -1,2 +1,28 @@
+pub mod calendar;
+pub mod crdt;
+pub mod export;
+pub mod import;
+pub mod language;
+pub mod markdown;
 pub mod passwords;
-pub use passwords::*;
\ No newline at end of file
+pub mod search_query;
+pub mod tags;
+pub mod tasks;
+pub mod templates;
+pub mod uploads;
+pub mod webhooks;
+pub mod wiki_links;
+pub use calendar::*;
+pub use crdt::*;
+pub use export::*;
+pub use import::*;
+pub use language::*;
+pub use markdown::*;
+pub use passwords::*;
+pub use search_query::*;
+pub use tags::*;
+pub use tasks::*;
+pub use templates::*;
+pub use uploads::*;
+pub use webhooks::*;
+pub use wiki_links::*;
\ No newline at end of file
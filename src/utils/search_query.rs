@@ -0,0 +1,317 @@
+use chrono::NaiveDate;
+use crate::utils::normalize_tag;
+
+// ===== AST =====
+
+// Which part of the note a text term is matched against
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchField {
+    All,
+    Title,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextMatch {
+    // plain word, stemmed like plainto_tsquery
+    Word(String),
+    // "exact phrase", words must appear next to each other
+    Phrase(String),
+    // prefix*, matches any lexeme starting with the prefix
+    Prefix(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchNode {
+    Text { field: SearchField, text: TextMatch },
+    // normalized like the tags stored on notes
+    Tag(String),
+    // notebook name, also matches the notebooks nested inside it
+    Notebook(String),
+    Before(NaiveDate),
+    After(NaiveDate),
+    Not(Box<SearchNode>),
+    Or(Vec<SearchNode>),
+}
+
+// A parsed search query, every node has to match
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchQuery {
+    pub nodes: Vec<SearchNode>,
+}
+
+impl SearchQuery {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+// to_tsquery input for a prefix* term: every word of the prefix matched as a prefix.
+// Only letters, digits and '_' are kept, so nothing in it is tsquery syntax
+pub fn prefix_tsquery(prefix: &str) -> String {
+    prefix
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+// ===== PARSER =====
+
+// A whitespace separated chunk of the raw query
+#[derive(Debug)]
+struct Token {
+    negated: bool,
+    key: Option<String>,
+    value: String,
+    quoted: bool,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        // skip whitespace between tokens
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let negated = chars.peek() == Some(&'-');
+        if negated {
+            chars.next();
+        }
+
+        let mut key = None;
+        let mut value = String::new();
+        let mut quoted = false;
+
+        loop {
+            match chars.peek() {
+                None => break,
+                Some(c) if c.is_whitespace() => break,
+                Some('"') => {
+                    chars.next();
+                    let mut phrase = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '"' {
+                            closed = true;
+                            break;
+                        }
+                        phrase.push(c);
+                    }
+                    if !closed {
+                        return Err("Unterminated quote in search query".to_string());
+                    }
+                    value.push_str(&phrase);
+                    quoted = true;
+                }
+                Some(':') if key.is_none() && !quoted && !value.is_empty() => {
+                    chars.next();
+                    key = Some(std::mem::take(&mut value).to_lowercase());
+                }
+                Some(&c) => {
+                    chars.next();
+                    value.push(c);
+                }
+            }
+        }
+
+        tokens.push(Token { negated, key, value, quoted });
+    }
+
+    Ok(tokens)
+}
+
+fn parse_date(key: &str, value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}' for {}: filter, expected YYYY-MM-DD", value, key))
+}
+
+fn parse_text(field: SearchField, token: &Token) -> Result<SearchNode, String> {
+    let value = token.value.trim();
+
+    if value.is_empty() {
+        return match &token.key {
+            Some(key) => Err(format!("Missing value for {}: filter", key)),
+            None if token.quoted => Err("Empty phrase in search query".to_string()),
+            None => Err("Dangling '-' in search query".to_string()),
+        };
+    }
+
+    let text = if token.quoted {
+        TextMatch::Phrase(value.to_string())
+    } else if let Some(prefix) = value.strip_suffix('*') {
+        if !prefix.chars().any(|c| c.is_alphanumeric()) {
+            return Err(format!("Invalid prefix search '{}'", value));
+        }
+        TextMatch::Prefix(prefix.to_string())
+    } else {
+        TextMatch::Word(value.to_string())
+    };
+
+    Ok(SearchNode::Text { field, text })
+}
+
+fn parse_token(token: &Token) -> Result<SearchNode, String> {
+    let node = match token.key.as_deref() {
+        Some("tag") | Some("in") | Some("before") | Some("after") if token.value.is_empty() => {
+            return Err(format!("Missing value for {}: filter", token.key.as_deref().unwrap_or_default()));
+        }
+        Some("tag") => SearchNode::Tag(normalize_tag(&token.value)?),
+        Some("in") => SearchNode::Notebook(token.value.trim().to_string()),
+        Some("before") => SearchNode::Before(parse_date("before", &token.value)?),
+        Some("after") => SearchNode::After(parse_date("after", &token.value)?),
+        Some("title") => parse_text(SearchField::Title, token)?,
+        // unknown keys (urls, times, ...) are searched as plain text
+        Some(key) => {
+            let text = Token {
+                negated: token.negated,
+                key: None,
+                value: format!("{}:{}", key, token.value),
+                quoted: token.quoted,
+            };
+            parse_text(SearchField::All, &text)?
+        }
+        None => parse_text(SearchField::All, token)?,
+    };
+
+    if token.negated {
+        Ok(SearchNode::Not(Box::new(node)))
+    } else {
+        Ok(node)
+    }
+}
+
+fn is_or(token: &Token) -> bool {
+    !token.negated && !token.quoted && token.key.is_none() && token.value.eq_ignore_ascii_case("or")
+}
+
+// Parse the raw `search` parameter into a query AST
+//
+// Supports websearch-style syntax: plain words, "exact phrase", -exclude, prefix*,
+// OR between terms, plus the filters tag:foo, in:notebook, before:YYYY-MM-DD,
+// after:YYYY-MM-DD and title:word
+pub fn parse_search_query(input: &str) -> Result<SearchQuery, String> {
+    let tokens = tokenize(input)?;
+    let mut nodes: Vec<SearchNode> = Vec::new();
+    let mut pending_or = false;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if is_or(token) {
+            if nodes.is_empty() || pending_or || index == tokens.len() - 1 {
+                return Err("OR must appear between two search terms".to_string());
+            }
+            pending_or = true;
+            continue;
+        }
+
+        let node = parse_token(token)?;
+
+        if pending_or {
+            pending_or = false;
+            match nodes.pop() {
+                Some(SearchNode::Or(mut alternatives)) => {
+                    alternatives.push(node);
+                    nodes.push(SearchNode::Or(alternatives));
+                }
+                Some(previous) => nodes.push(SearchNode::Or(vec![previous, node])),
+                None => unreachable!(),
+            }
+        } else {
+            nodes.push(node);
+        }
+    }
+
+    Ok(SearchQuery { nodes })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(field: SearchField, text: TextMatch) -> SearchNode {
+        SearchNode::Text { field, text }
+    }
+
+    fn word(value: &str) -> SearchNode {
+        text(SearchField::All, TextMatch::Word(value.to_string()))
+    }
+
+    #[test]
+    fn parses_plain_words_phrases_and_prefixes() {
+        let query = parse_search_query(r#"rust "exact phrase" learn*"#).unwrap();
+        assert_eq!(query.nodes, vec![
+            word("rust"),
+            text(SearchField::All, TextMatch::Phrase("exact phrase".to_string())),
+            text(SearchField::All, TextMatch::Prefix("learn".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn parses_exclusions_and_or() {
+        let query = parse_search_query("python -pandas rust OR go OR zig").unwrap();
+        assert_eq!(query.nodes, vec![
+            word("python"),
+            SearchNode::Not(Box::new(word("pandas"))),
+            SearchNode::Or(vec![word("rust"), word("go"), word("zig")]),
+        ]);
+    }
+
+    #[test]
+    fn parses_filters() {
+        let query = parse_search_query(r#"tag:#Work in:"Team Notes" before:2025-01-01 after:2024-06-30 title:plan"#).unwrap();
+        assert_eq!(query.nodes, vec![
+            SearchNode::Tag("work".to_string()),
+            SearchNode::Notebook("Team Notes".to_string()),
+            SearchNode::Before(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()),
+            SearchNode::After(NaiveDate::from_ymd_opt(2024, 6, 30).unwrap()),
+            text(SearchField::Title, TextMatch::Word("plan".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn searches_unknown_keys_as_text() {
+        let query = parse_search_query("https://example.com 10:30").unwrap();
+        assert_eq!(query.nodes, vec![word("https://example.com"), word("10:30")]);
+    }
+
+    #[test]
+    fn empty_input_is_an_empty_query() {
+        assert!(parse_search_query("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        for input in [
+            r#""unterminated"#,
+            r#""""#,
+            "-",
+            "*",
+            "tag:",
+            "tag:a,b",
+            "in:",
+            "before:yesterday",
+            "after:2025-13-01",
+            "OR rust",
+            "rust OR",
+            "rust OR OR go",
+        ] {
+            assert!(parse_search_query(input).is_err(), "{} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn prefix_tsquery_keeps_only_words() {
+        assert_eq!(prefix_tsquery("learn"), "learn:*");
+        assert_eq!(prefix_tsquery("road-m"), "road:* & m:*");
+        assert_eq!(prefix_tsquery(r"C:\pa"), "C:* & pa:*");
+        assert_eq!(prefix_tsquery(r"it's\"), "it:* & s:*");
+        assert_eq!(prefix_tsquery("snake_case"), "snake_case:*");
+    }
+}
@@ -0,0 +1,54 @@
+const MAX_TAG_LEN: usize = 64;
+const MAX_TAGS: usize = 50;
+const MAX_NOTEBOOK_NAME_LEN: usize = 100;
+
+// Tags are matched exactly by tag:, so "#Work " and "work" have to end up the same.
+// '/' is allowed for nested tags like project/alpha.
+pub fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+
+    if tag.is_empty() {
+        return Err("Tags must not be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err(format!("Tags can be at most {} characters", MAX_TAG_LEN));
+    }
+    if tag.chars().any(|c| c.is_whitespace() || c == ',' || c == '"') {
+        return Err(format!("Tag '{}' must not contain spaces, commas or quotes", tag));
+    }
+
+    Ok(tag)
+}
+
+// Normalized, deduplicated and sorted, the way they are stored on the note
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized = tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .collect::<Result<Vec<String>, String>>()?;
+    normalized.sort();
+    normalized.dedup();
+
+    if normalized.len() > MAX_TAGS {
+        return Err(format!("A note can have at most {} tags", MAX_TAGS));
+    }
+
+    Ok(normalized)
+}
+
+// Names end up as folder names in exports and are looked up by in:, so no '/'
+pub fn normalize_notebook_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err("Notebook name must not be empty".to_string());
+    }
+    if name.chars().count() > MAX_NOTEBOOK_NAME_LEN {
+        return Err(format!("Notebook names can be at most {} characters", MAX_NOTEBOOK_NAME_LEN));
+    }
+    if name.contains('/') || name.contains('\\') {
+        return Err("Notebook names must not contain slashes".to_string());
+    }
+
+    Ok(name.to_string())
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+// Ticks or clears the checkbox on a task line found by the note_tasks view, whose
+// line numbers are 1-based over the content split on \n. None if the line isn't there
+// or has no checkbox. The list marker can't contain '[', so the first one opens the box.
+pub fn set_task_done(content: &str, line_number: usize, done: bool) -> Option<String> {
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    let line = *lines.get(line_number.checked_sub(1)?)?;
+
+    let open = line.find('[')?;
+    let mark = line.get(open + 1..open + 2)?;
+    if !matches!(mark, " " | "x" | "X") || line.get(open + 2..open + 3)? != "]" {
+        return None;
+    }
+
+    let updated = format!("{}[{}]{}", &line[..open], if done { "x" } else { " " }, &line[open + 3..]);
+    lines[line_number - 1] = &updated;
+
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "# Trip\n- [ ] pack\n  - [X] passport\n    * [ ] visa\n```\n- [ ] not a task\n```\n1. [x] book hotel";
+
+    #[test]
+    fn ticks_and_clears_top_level_tasks() {
+        let ticked = set_task_done(CONTENT, 2, true).unwrap();
+        assert_eq!(ticked.lines().nth(1), Some("- [x] pack"));
+        assert_eq!(set_task_done(&ticked, 2, false).unwrap(), CONTENT);
+
+        let cleared = set_task_done(CONTENT, 8, false).unwrap();
+        assert_eq!(cleared.lines().nth(7), Some("1. [ ] book hotel"));
+    }
+
+    #[test]
+    fn keeps_indentation_of_nested_tasks() {
+        let cleared = set_task_done(CONTENT, 3, false).unwrap();
+        assert_eq!(cleared.lines().nth(2), Some("  - [ ] passport"));
+
+        let ticked = set_task_done(CONTENT, 4, true).unwrap();
+        assert_eq!(ticked.lines().nth(3), Some("    * [x] visa"));
+    }
+
+    #[test]
+    fn uppercase_x_counts_as_a_checkbox() {
+        assert_eq!(set_task_done(CONTENT, 3, true).unwrap().lines().nth(2), Some("  - [x] passport"));
+    }
+
+    #[test]
+    fn only_the_given_line_changes() {
+        let ticked = set_task_done(CONTENT, 2, true).unwrap();
+        for (index, (before, after)) in CONTENT.split('\n').zip(ticked.split('\n')).enumerate() {
+            if index != 1 {
+                assert_eq!(before, after);
+            }
+        }
+        // fenced lines are never reported as tasks, but must survive a toggle elsewhere untouched
+        assert!(ticked.contains("```\n- [ ] not a task\n```"));
+    }
+
+    #[test]
+    fn rejects_lines_without_a_checkbox() {
+        assert_eq!(set_task_done(CONTENT, 0, true), None);
+        assert_eq!(set_task_done(CONTENT, 1, true), None);
+        assert_eq!(set_task_done(CONTENT, 9, true), None);
+        assert_eq!(set_task_done("- [y] maybe", 1, true), None);
+        assert_eq!(set_task_done("- [ x", 1, true), None);
+        assert_eq!(set_task_done("- [", 1, true), None);
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        assert_eq!(set_task_done("- [ ] a\r\n- [ ] b\r\n", 2, true).unwrap(), "- [ ] a\r\n- [x] b\r\n");
+    }
+}
\ No newline at end of file
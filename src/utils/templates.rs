@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+const MAX_VARIABLE_NAME_LEN: usize = 64;
+
+pub fn is_valid_template_variable(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_VARIABLE_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+// Replaces every {{name}} (spaces inside the braces are fine) that has a value.
+// Placeholders without one stay as written, and values aren't scanned again so a
+// value containing {{...}} comes out literally.
+pub fn fill_template(template: &str, values: &HashMap<String, String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        let Some(end) = after.find("}}") else {
+            filled.push_str(&rest[start..]);
+            return filled;
+        };
+
+        match values.get(after[..end].trim()) {
+            Some(value) => filled.push_str(value),
+            None => filled.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn fills_known_placeholders() {
+        let values = values(&[("date", "2026-10-18"), ("user.username", "ada")]);
+        assert_eq!(fill_template("Standup {{date}} by {{ user.username }}", &values), "Standup 2026-10-18 by ada");
+        assert_eq!(fill_template("{{date}}{{date}}", &values), "2026-10-182026-10-18");
+    }
+
+    #[test]
+    fn keeps_unknown_placeholders_as_written() {
+        let values = values(&[("date", "today")]);
+        assert_eq!(fill_template("{{ topic }} on {{date}}", &values), "{{ topic }} on today");
+        assert_eq!(fill_template("{{}} {{ }}", &values), "{{}} {{ }}");
+    }
+
+    #[test]
+    fn keeps_unclosed_braces() {
+        let values = values(&[("date", "today")]);
+        assert_eq!(fill_template("{{date}} and {{date", &values), "today and {{date");
+        assert_eq!(fill_template("trailing {{", &values), "trailing {{");
+        assert_eq!(fill_template("{date}} {{date}", &values), "{date}} {{date}");
+    }
+
+    #[test]
+    fn does_not_expand_placeholders_inside_values() {
+        let values = values(&[("topic", "{{secret}}"), ("secret", "leaked")]);
+        assert_eq!(fill_template("Topic: {{topic}}", &values), "Topic: {{secret}}");
+    }
+
+    #[test]
+    fn handles_multibyte_text_around_placeholders() {
+        let values = values(&[("name", "Zoë")]);
+        assert_eq!(fill_template("Grüße, {{name}} — 👋 {{", &values), "Grüße, Zoë — 👋 {{");
+    }
+
+    #[test]
+    fn validates_variable_names() {
+        assert!(is_valid_template_variable("user.username"));
+        assert!(is_valid_template_variable("due-date_2"));
+        assert!(!is_valid_template_variable(""));
+        assert!(!is_valid_template_variable("has space"));
+        assert!(!is_valid_template_variable("}}"));
+        assert!(!is_valid_template_variable(&"a".repeat(MAX_VARIABLE_NAME_LEN + 1)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+use actix_multipart::Multipart;
+use bytes::{Bytes, BytesMut};
+use futures_util::StreamExt;
+
+// A file read from a multipart upload
+pub struct Upload {
+    // as sent by the client, sanitize before using it as a name
+    pub filename: String,
+    pub content_type: String,
+    pub data: Bytes,
+}
+
+pub enum UploadError {
+    Missing,
+    TooLarge,
+    Malformed(String),
+}
+
+// Read the `file` field of the upload, giving up as soon as it exceeds max_size
+pub async fn read_file_upload(mut payload: Multipart, max_size: u64) -> Result<Upload, UploadError> {
+    while let Some(field) = payload.next().await {
+        let mut field = field.map_err(|e| UploadError::Malformed(e.to_string()))?;
+
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let filename = field
+            .content_disposition()
+            .and_then(|disposition| disposition.get_filename())
+            .unwrap_or_default()
+            .to_string();
+        let content_type = field
+            .content_type()
+            .map(|mime| mime.to_string())
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        let mut data = BytesMut::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk.map_err(|e| UploadError::Malformed(e.to_string()))?;
+            if (data.len() + chunk.len()) as u64 > max_size {
+                return Err(UploadError::TooLarge);
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        return Ok(Upload { filename, content_type, data: data.freeze() });
+    }
+
+    Err(UploadError::Missing)
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use sha2::Sha256;
+
+pub fn generate_webhook_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+// X-Webhook-Signature value, receivers recompute the HMAC over "{t}.{body}" with their secret
+// and should reject old timestamps to stop replays
+pub fn sign_webhook_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes()))
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "this network", carrier-grade NAT, IETF protocol assignments, benchmarking and reserved
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && c == 0)
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+// Whether webhooks may be sent to the address. Loopback, private and link-local
+// networks, where cloud metadata endpoints live, are off limits
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(mapped);
+            }
+            let segments = ip.segments();
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                // documentation and NAT64, which can lead back to private IPv4 addresses
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8)
+                || (segments[0] == 0x0064 && segments[1] == 0xff9b))
+        }
+    }
+}
+
+// Resolves a webhook host, failing when any of its addresses isn't public
+pub async fn resolve_public_host(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(format!("Could not resolve {}", host));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_address(addr.ip())) {
+        return Err(format!("{} resolves to {}, which is not a public address", host, addr.ip()));
+    }
+    Ok(addrs)
+}
+
+// DNS resolver for the webhook client, so a host that resolved to a public address when
+// the webhook was registered can't be pointed at the internal network later on
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = resolve_public_host(name.as_str(), 0).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_address(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn rejects_internal_ipv4_addresses() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.64.0.1", "255.255.255.255"] {
+            assert!(!public(ip), "{} should not be public", ip);
+        }
+        assert!(public("93.184.215.14"));
+        assert!(public("8.8.8.8"));
+    }
+
+    #[test]
+    fn rejects_internal_ipv6_addresses() {
+        for ip in ["::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1", "::ffff:169.254.169.254", "64:ff9b::a00:1"] {
+            assert!(!public(ip), "{} should not be public", ip);
+        }
+        assert!(public("2606:4700:4700::1111"));
+        assert!(public("::ffff:8.8.8.8"));
+    }
+
+    #[tokio::test]
+    async fn resolving_localhost_fails() {
+        assert!(resolve_public_host("localhost", 80).await.is_err());
+    }
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        let signature = sign_webhook_payload("secret", 1700000000, b"{}");
+        assert!(signature.starts_with("t=1700000000,v1="));
+        assert_eq!(signature, sign_webhook_payload("secret", 1700000000, b"{}"));
+        assert_ne!(signature, sign_webhook_payload("other", 1700000000, b"{}"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,159 @@
+use uuid::Uuid;
+
+// A [[target]] reference found in note content
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    // note title or id as written, without any #heading or |alias
+    pub target: String,
+    // set when the target is a note id rather than a title
+    pub note_id: Option<Uuid>,
+}
+
+// Byte ranges of every [[...]] in the content along with the target part inside it,
+// skipping fenced code blocks where brackets are usually code rather than links
+fn find_links(content: &str) -> Vec<(usize, usize)> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    let mut fence: Option<&str> = None;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = if trimmed.starts_with("```") {
+            Some("```")
+        } else if trimmed.starts_with("~~~") {
+            Some("~~~")
+        } else {
+            None
+        };
+
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            (None, None) => find_links_in_line(line, offset, &mut links),
+            _ => {}
+        }
+
+        offset += line.len();
+    }
+
+    links
+}
+
+fn find_links_in_line(line: &str, offset: usize, links: &mut Vec<(usize, usize)>) {
+    let mut rest = 0;
+
+    while let Some(start) = line[rest..].find("[[") {
+        let inner_start = rest + start + 2;
+        let Some(end) = line[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &line[inner_start..inner_start + end];
+
+        // [[a [[b]] -> only b is a link
+        if let Some(nested) = inner.rfind("[[") {
+            rest = inner_start + nested;
+            continue;
+        }
+
+        let target_len = inner.find(['|', '#']).unwrap_or(inner.len());
+        if !inner[..target_len].trim().is_empty() {
+            links.push((offset + inner_start, offset + inner_start + target_len));
+        }
+
+        rest = inner_start + end + 2;
+    }
+}
+
+// Distinct link targets in the order they first appear
+pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
+    let mut links: Vec<WikiLink> = Vec::new();
+
+    for (start, end) in find_links(content) {
+        let target = content[start..end].trim();
+        if links.iter().any(|link| link.target.to_lowercase() == target.to_lowercase()) {
+            continue;
+        }
+        links.push(WikiLink {
+            target: target.to_string(),
+            note_id: Uuid::parse_str(target).ok(),
+        });
+    }
+
+    links
+}
+
+// Point every [[old_title]] link at new_title, keeping any #heading or |alias
+pub fn rewrite_wiki_links(content: &str, old_title: &str, new_title: &str) -> String {
+    let old_title = old_title.trim().to_lowercase();
+    let mut rewritten = String::with_capacity(content.len());
+    let mut copied = 0;
+
+    for (start, end) in find_links(content) {
+        if content[start..end].trim().to_lowercase() != old_title {
+            continue;
+        }
+        rewritten.push_str(&content[copied..start]);
+        rewritten.push_str(new_title.trim());
+        copied = end;
+    }
+
+    rewritten.push_str(&content[copied..]);
+    rewritten
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(content: &str) -> Vec<String> {
+        parse_wiki_links(content).into_iter().map(|link| link.target).collect()
+    }
+
+    #[test]
+    fn extracts_targets_without_heading_or_alias() {
+        assert_eq!(
+            targets("See [[Project Plan]], [[Meeting#Agenda]] and [[Budget|the budget]]."),
+            vec!["Project Plan", "Meeting", "Budget"]
+        );
+    }
+
+    #[test]
+    fn deduplicates_ignoring_case_and_whitespace() {
+        assert_eq!(targets("[[Plan]] [[ plan ]] [[PLAN|again]] [[Other]]"), vec!["Plan", "Other"]);
+    }
+
+    #[test]
+    fn recognizes_note_ids() {
+        let id = Uuid::new_v4();
+        let links = parse_wiki_links(&format!("[[{}]] [[Title]]", id));
+        assert_eq!(links[0].note_id, Some(id));
+        assert_eq!(links[1].note_id, None);
+    }
+
+    #[test]
+    fn skips_empty_unclosed_and_nested_brackets() {
+        assert_eq!(targets("[[]] [[ |alias]] [[#heading]] [[unclosed"), Vec::<String>::new());
+        assert_eq!(targets("[[a [[b]]"), vec!["b"]);
+    }
+
+    #[test]
+    fn skips_fenced_code_blocks() {
+        let content = "[[Before]]\n```rust\nlet x = v[[0]];\n```\n~~~\n[[Fenced]]\n```\n[[StillFenced]]\n~~~\n[[After]]";
+        assert_eq!(targets(content), vec!["Before", "After"]);
+    }
+
+    #[test]
+    fn rewrites_matching_links_and_keeps_the_rest() {
+        let content = "[[Old]] [[old#Section]] [[ OLD |alias]] [[Older]]\n```\n[[Old]]\n```";
+        assert_eq!(
+            rewrite_wiki_links(content, "Old", "New"),
+            "[[New]] [[New#Section]] [[New|alias]] [[Older]]\n```\n[[Old]]\n```"
+        );
+    }
+
+    #[test]
+    fn handles_multibyte_content() {
+        assert_eq!(targets("Über [[Café Notizen]] — ok"), vec!["Café Notizen"]);
+        assert_eq!(rewrite_wiki_links("ä [[Café]] ö", "café", "Tee"), "ä [[Tee]] ö");
+    }
+}
\ No newline at end of file